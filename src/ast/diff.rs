@@ -0,0 +1,132 @@
+use super::{Statement, StatementData, Value};
+
+/// A single structural difference between two statement trees
+///
+/// Paths are the dot separated keys of the changed value, relative to the statements
+/// being compared.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Change {
+    /// A value that only exists in the new tree
+    Added(String, Value),
+    /// A value that only exists in the old tree
+    Removed(String, Value),
+    /// A value that exists in both trees with different data, holding the old and new value
+    Modified(String, Value, Value),
+}
+
+impl Change {
+    /// Gets the path of the value that changed
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Added(path, ..) | Self::Removed(path, ..) | Self::Modified(path, ..) => path,
+        }
+    }
+}
+
+impl Statement {
+    /// Computes the structural differences from this statement to another
+    ///
+    /// Groups and blocks are compared recursively by key, values are compared by their data.
+    /// When a container and a value share a path, every value under the container is
+    /// reported as removed or added alongside the value replacing it.
+    pub fn diff(&self, other: &Statement) -> Vec<Change> {
+        let mut changes = Vec::new();
+        diff_into(self, other, "", &mut changes);
+        changes
+    }
+}
+
+fn diff_into(left: &Statement, right: &Statement, path: &str, changes: &mut Vec<Change>) {
+    match (&left.data, &right.data) {
+        (StatementData::Single(old), StatementData::Single(new)) => {
+            if old != new {
+                changes.push(Change::Modified(path.to_string(), old.clone(), new.clone()));
+            }
+        }
+        (
+            StatementData::Group(left_children) | StatementData::Labeled(_, left_children),
+            StatementData::Group(right_children) | StatementData::Labeled(_, right_children),
+        ) => {
+            for (key, left_child) in left_children {
+                let child_path = Statement::child_path(path, key);
+                match right_children.get(key) {
+                    Some(right_child) => diff_into(left_child, right_child, &child_path, changes),
+                    None => collect_values(left_child, &child_path, changes, Change::Removed),
+                }
+            }
+            for (key, right_child) in right_children {
+                if !left_children.contains_key(key) {
+                    let child_path = Statement::child_path(path, key);
+                    collect_values(right_child, &child_path, changes, Change::Added);
+                }
+            }
+        }
+        _ => {
+            collect_values(left, path, changes, Change::Removed);
+            collect_values(right, path, changes, Change::Added);
+        }
+    }
+}
+
+fn collect_values(
+    statement: &Statement,
+    path: &str,
+    changes: &mut Vec<Change>,
+    change: fn(String, Value) -> Change,
+) {
+    match &statement.data {
+        StatementData::Single(value) => changes.push(change(path.to_string(), value.clone())),
+        StatementData::Group(children) | StatementData::Labeled(_, children) => {
+            for (key, child) in children {
+                collect_values(child, &Statement::child_path(path, key), changes, change);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statement_diff() {
+        let old = crate::from_str(
+            "name = 'app'\n[server]\nhost = 'localhost'\nport = 80\ndebug = true\n",
+        )
+        .unwrap();
+        let new = crate::from_str(
+            "name = 'app'\n[server]\nhost = 'localhost'\nport = 8080\n[cache]\nsize = 64\n",
+        )
+        .unwrap();
+
+        let changes = old.diff(&new);
+        let summary: Vec<(&str, &str)> = changes
+            .iter()
+            .map(|change| {
+                let kind = match change {
+                    Change::Added(..) => "added",
+                    Change::Removed(..) => "removed",
+                    Change::Modified(..) => "modified",
+                };
+                (change.path(), kind)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("server.port", "modified"),
+                ("server.debug", "removed"),
+                ("cache.size", "added"),
+            ]
+        );
+
+        let Change::Modified(_, before, after) = &changes[0] else {
+            panic!("expected a modified value");
+        };
+        assert_eq!(before.as_int(), Some(&80));
+        assert_eq!(after.as_int(), Some(&8080));
+
+        assert!(old.diff(&old).is_empty());
+    }
+}
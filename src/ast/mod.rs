@@ -10,18 +10,24 @@
 //! - **Values**: Data representation with the `Value` and `Data` enums
 //! - **Statements**: Structural elements with the `Statement` and `StatementData` enums
 //! - **Scope**: Macro resolution and symbol table management
+//! - **Diff**: Structural comparison of statement trees
+//! - **Visit**: Extension point for passes that transform statement trees in place
 //!
 
+mod diff;
 mod scope;
 mod statement;
 mod types;
 mod value;
+mod visit;
 
 // Re-export all public items from submodules
+pub use diff::*;
 pub use scope::*;
 pub use statement::*;
 pub use types::*;
 pub use value::*;
+pub use visit::*;
 
 #[cfg(test)]
 mod tests {
@@ -116,34 +122,22 @@ mod tests {
 
     /// Pretty prints an AST tree with indentation
     pub fn pretty_print(stmt: &Statement, indent: usize) -> String {
-        let mut result = String::new();
-        let indent_str = "  ".repeat(indent);
+        print_tree(&stmt.to_tree(), indent)
+    }
 
-        result.push_str(&format!(
-            "{}{}[{}] ({})\n",
-            indent_str,
-            stmt.id,
-            stmt.uid,
-            match &stmt.type_ {
-                StatementType::Control(_) => "Control",
-                StatementType::Assignment(_) => "Assignment",
-                StatementType::Block { .. } => "Block",
-                StatementType::Section(_) => "Section",
-                StatementType::Module(_) => "Module",
-            }
-        ));
+    fn print_tree(node: &TreeNode, indent: usize) -> String {
+        let indent_str = "  ".repeat(indent);
+        let mut result = format!("{}{} ({})\n", indent_str, node.id, node.kind);
 
-        if let Some(value) = stmt.get_value() {
+        if let (Some(value), Some(value_type)) = (&node.value, &node.value_type) {
             result.push_str(&format!(
                 "{}  Value: {} ({})\n",
-                indent_str,
-                value.type_of(),
-                value.to_macro_string()
+                indent_str, value_type, value
             ));
         }
 
-        for child in stmt.children() {
-            result.push_str(&pretty_print(child, indent + 1));
+        for child in &node.children {
+            result.push_str(&print_tree(child, indent + 1));
         }
 
         result
@@ -1,12 +1,23 @@
 use super::types::StatementType;
+use super::value::write_float;
 use super::{Data, Statement, StatementData, Value};
 use crate::{Result, error};
 use indexmap::{IndexMap, IndexSet};
 use snafu::{OptionExt, ensure};
+use std::fmt;
 use uuid::Uuid;
 
-/// Maximum recursion depth for macro resolution to prevent infinite loops
-const MAX_RECURSION_DEPTH: usize = 100;
+/// Maximum depth of macro references resolving through each other unless configured otherwise
+pub const DEFAULT_RECURSION_LIMIT: usize = 100;
+
+/// Delimiter opening an interpolation segment unless configured otherwise
+pub const DEFAULT_OPEN_DELIMITER: &str = "{";
+
+/// Delimiter closing an interpolation segment unless configured otherwise
+pub const DEFAULT_CLOSE_DELIMITER: &str = "}";
+
+/// Callback receiving the path and value of each resolved macro reference
+type Trace = Box<dyn Fn(&str, &Value) + Send + Sync>;
 
 /// Scope is used to resolve macros and manage symbol references
 ///
@@ -29,6 +40,24 @@ pub struct Scope {
 
     /// Current recursion depth for macro resolution
     recursion_depth: usize,
+
+    /// Maximum recursion depth for macro resolution to prevent infinite loops
+    recursion_limit: usize,
+
+    /// Delimiters surrounding each interpolation segment of a macro string
+    delimiters: (String, String),
+
+    /// Values resolved one at a time along with the uids of every value they referenced
+    resolved_cache: IndexMap<Uuid, (Value, IndexSet<Uuid>)>,
+
+    /// Uids of the values referenced since the last single value resolution started
+    referenced: IndexSet<Uuid>,
+
+    /// Callback invoked with the path and value of every reference that resolves
+    trace: Option<Trace>,
+
+    /// Whether paths are lowercased before they are stored or looked up
+    case_insensitive_keys: bool,
 }
 
 impl Scope {
@@ -39,15 +68,122 @@ impl Scope {
             symbol_table: IndexMap::new(),
             path_lookup: IndexMap::new(),
             recursion_depth: 0,
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
+            delimiters: (
+                DEFAULT_OPEN_DELIMITER.to_string(),
+                DEFAULT_CLOSE_DELIMITER.to_string(),
+            ),
+            resolved_cache: IndexMap::new(),
+            referenced: IndexSet::new(),
+            trace: None,
+            case_insensitive_keys: false,
         };
         Self::build_symbol_table(&mut scope, node, Vec::new());
         scope
     }
 
+    /// Creates a new Scope from a root statement with values supplied from outside of it
+    ///
+    /// Each override is keyed by a dot separated macro path such as `build.sha` and
+    /// seeded into the symbol table after the statement has been walked, so macros can
+    /// reference values that are not part of the document and overrides take precedence
+    /// over document values at the same path. Members of table and array overrides are
+    /// addressable as well. Only macro resolution sees the overrides, the statements of
+    /// the document itself are left unchanged.
+    pub fn with_overrides(node: &Statement, overrides: IndexMap<String, Value>) -> Self {
+        let mut scope = Self::new(node);
+        for (path, value) in overrides {
+            let path = path.split('.').map(|x| x.to_string()).collect();
+            Self::walk_value(&mut scope, &value, path);
+        }
+        scope
+    }
+
+    /// Creates a new Scope from a root statement that reports every macro reference it resolves
+    ///
+    /// The callback receives the absolute path of each reference along with the value it
+    /// resolved to, once the value is fully resolved. A macro referencing another macro
+    /// reports the inner reference before the outer one, and every interpolation segment
+    /// of a macro string is reported on its own. Fallbacks of default segments are not
+    /// references and are not reported.
+    pub fn with_trace<F>(node: &Statement, callback: F) -> Self
+    where
+        F: Fn(&str, &Value) + Send + Sync + 'static,
+    {
+        let mut scope = Self::new(node);
+        scope.trace = Some(Box::new(callback));
+        scope
+    }
+
+    /// Sets the delimiters surrounding interpolation segments in macro strings
+    ///
+    /// Strings that legitimately contain braces, such as JSON snippets, can switch to
+    /// delimiters like `${` and `}` or `<<` and `>>` so their braces pass through as is.
+    /// A doubled opening delimiter writes it literally, the `}}` escape only applies to
+    /// the default delimiters.
+    ///
+    /// # Errors
+    ///
+    /// Returns a macro delimiters error if either delimiter is empty.
+    pub fn with_delimiters(
+        mut self,
+        open: impl Into<String>,
+        close: impl Into<String>,
+    ) -> Result<Self> {
+        let (open, close) = (open.into(), close.into());
+        ensure!(
+            !open.is_empty() && !close.is_empty(),
+            error::MacroDelimitersSnafu { open, close }
+        );
+        self.delimiters = (open, close);
+        Ok(self)
+    }
+
+    /// Sets how deep macro references may resolve through each other
+    ///
+    /// Every macro that resolves to another macro adds a level, resolution fails with a
+    /// recursion limit error once the limit is reached. Defaults to
+    /// [`DEFAULT_RECURSION_LIMIT`].
+    pub fn with_recursion_limit(mut self, limit: usize) -> Self {
+        self.recursion_limit = limit;
+        self
+    }
+
+    /// Sets whether paths are compared case-insensitively
+    ///
+    /// When enabled every path of the symbol table is lowercased, and so is every path
+    /// looked up or referenced by a macro, so `Server.Port` and `server.port` find the
+    /// same value. Used for modules loaded with case-insensitive keys.
+    pub fn with_case_insensitive_keys(mut self, insensitive: bool) -> Self {
+        self.case_insensitive_keys = insensitive;
+        if insensitive {
+            self.symbol_table = std::mem::take(&mut self.symbol_table)
+                .into_iter()
+                .map(|(path, value)| (path.to_lowercase(), value))
+                .collect();
+            for path in self.path_lookup.values_mut() {
+                *path = path.to_lowercase();
+            }
+        }
+        self
+    }
+
+    /// Normalizes a path to the form used as a symbol table key
+    fn key(&self, path: String) -> String {
+        if self.case_insensitive_keys {
+            path.to_lowercase()
+        } else {
+            path
+        }
+    }
+
     /// Builds the symbol table by walking the AST
     fn build_symbol_table(scope: &mut Scope, node: &Statement, path: Vec<String>) {
         let mut new_path = path;
-        new_path.push(node.id.clone());
+        // The parser names the root module "." which is not part of any macro path
+        if node.id != "." {
+            new_path.push(node.id.clone());
+        }
 
         match &node.data {
             StatementData::Group(children) | StatementData::Labeled(_, children) => {
@@ -86,12 +222,16 @@ impl Scope {
 
     /// Adds a symbol to the symbol table
     fn add_symbol(scope: &mut Scope, path: Vec<String>, node: &Value) {
-        let key = path.join(".");
+        let key = scope.key(path.join("."));
         scope.symbol_table.insert(key.clone(), node.clone());
         scope.path_lookup.insert(node.uid, key);
     }
 
     /// Applies macro resolution to the entire scope
+    ///
+    /// The resolved statement keeps the exact order of every section, block, statement,
+    /// table entry and label of the root statement, macros referencing statements declared
+    /// later in the document do not move either of them.
     pub fn apply(&mut self) -> Result<Statement> {
         let mut visit_log = IndexSet::new();
         let root = self.root.clone();
@@ -99,10 +239,70 @@ impl Scope {
         self.resolve_statement(&root, &mut visit_log)
     }
 
+    /// Resolves a single value by its uid and caches the result
+    ///
+    /// The value is looked up through [`Scope::path_lookup`], so any value of the symbol
+    /// table can be resolved, including members of tables and arrays. Later calls for the
+    /// same uid return the cached value until it, or any value it referenced while
+    /// resolving, is passed to [`Scope::invalidate`]. Fails with a field not found error
+    /// when no value has the uid.
+    pub fn resolved_value_at(&mut self, uid: Uuid) -> Result<Value> {
+        if let Some((value, _)) = self.resolved_cache.get(&uid) {
+            return Ok(value.clone());
+        }
+
+        let value = self
+            .path_lookup
+            .get(&uid)
+            .and_then(|path| self.symbol_table.get(path))
+            .cloned()
+            .context(error::NoFieldSnafu {
+                location: self.root.meta.location.clone(),
+                field: uid.to_string(),
+            })?;
+        self.recursion_depth = 0;
+        self.referenced.clear();
+        let resolved = self.resolve_value(&value, &mut IndexSet::new())?;
+        let referenced = std::mem::take(&mut self.referenced);
+        self.resolved_cache
+            .insert(uid, (resolved.clone(), referenced));
+        Ok(resolved)
+    }
+
+    /// Drops the cached resolution of a value and of every value that referenced it
+    ///
+    /// Returns true if any cached value was dropped.
+    pub fn invalidate(&mut self, uid: Uuid) -> bool {
+        let count = self.resolved_cache.len();
+        self.resolved_cache
+            .retain(|key, (_, referenced)| *key != uid && !referenced.contains(&uid));
+        self.resolved_cache.len() != count
+    }
+
+    /// Sets the value at a dot separated path such as `build.sha`
+    ///
+    /// A value already at the path is replaced, and the cached resolution of it and of
+    /// every value that referenced it is invalidated so the next resolution sees the new
+    /// value. Members of table and array values are addressable as well. Like overrides,
+    /// only macro resolution sees the value, the statements of the root are unchanged.
+    pub fn set_symbol(&mut self, path: &str, value: Value) {
+        if let Some(previous) = self.lookup(path) {
+            let uid = previous.uid;
+            self.invalidate(uid);
+        }
+        self.invalidate(value.uid);
+        let path = path.split('.').map(|x| x.to_string()).collect();
+        Self::walk_value(self, &value, path);
+    }
+
     /// Resolves a path reference, handling relative paths like 'self' and 'super'
+    ///
+    /// Paths starting with `@` are absolute and always resolve from the module root,
+    /// wherever the referencing value is declared.
     fn resolve_path(&self, current: &Value, input: String) -> Result<String> {
-        let operating_path: Vec<String> = if input.starts_with("self") || input.starts_with("super")
-        {
+        let operating_path: Vec<String> = if let Some(absolute) = input.strip_prefix('@') {
+            absolute.split('.').map(|x| x.to_string()).collect()
+        } else if input.starts_with("self") || input.starts_with("super") {
             let current_path = self
                 .path_lookup
                 .get(&current.uid)
@@ -151,7 +351,7 @@ impl Scope {
             }
         }
 
-        Ok(final_path.join("."))
+        Ok(self.key(final_path.join(".")))
     }
     /// Resolves a macro reference to its actual value
     fn resolve_macro(
@@ -162,10 +362,10 @@ impl Scope {
     ) -> Result<Value> {
         // Check recursion depth
         ensure!(
-            self.recursion_depth < MAX_RECURSION_DEPTH,
+            self.recursion_depth < self.recursion_limit,
             error::RecursionLimitSnafu {
                 location: at.meta.location.clone(),
-                limit: MAX_RECURSION_DEPTH,
+                limit: self.recursion_limit,
             }
         );
 
@@ -185,6 +385,7 @@ impl Scope {
         let path = self.resolve_path(at, input.clone())?;
 
         if let Some(data) = self.symbol_table.get(&path) {
+            self.referenced.insert(data.uid);
             let mut resolved_value = Value {
                 uid: at.uid,
                 data: data.data.clone(),
@@ -196,6 +397,7 @@ impl Scope {
                 resolved_value = self.resolve_value(&resolved_value, visit_log)?;
             }
 
+            self.trace(&path, &resolved_value);
             Ok(resolved_value)
         } else {
             // Handle macro string interpolation
@@ -204,6 +406,10 @@ impl Scope {
     }
 
     /// Resolves macro string interpolation (e.g., "Hello {name}")
+    ///
+    /// Each `{path}` segment is replaced with the value at that path, including indexed
+    /// array members like `{servers.0.host}`. Literal braces are written as `{{` and `}}`,
+    /// other delimiters can be configured with [`Scope::with_delimiters`].
     fn resolve_macro_string(
         &mut self,
         at: &Value,
@@ -211,7 +417,8 @@ impl Scope {
         visit_log: &mut IndexSet<Uuid>,
     ) -> Result<Value> {
         // Check if there are any interpolation markers
-        if !input.contains('{') {
+        let (open, close) = self.delimiters.clone();
+        if !input.contains(open.as_str()) {
             return error::NoMacroSnafu {
                 location: at.meta.location.clone(),
                 path: input,
@@ -220,58 +427,63 @@ impl Scope {
         }
 
         let mut result = String::new();
-        let chars = input.chars().peekable();
-        let mut brace_depth = 0;
-        let mut current_macro = String::new();
-
-        for ch in chars {
-            match ch {
-                '{' if brace_depth == 0 => {
-                    brace_depth = 1;
-                    current_macro.clear();
+        for piece in scan_macro_string(&input, &open, &close) {
+            let segment = match piece {
+                MacroPiece::Text(text) => {
+                    result.push_str(text);
+                    continue;
                 }
-                '{' if brace_depth > 0 => {
-                    brace_depth += 1;
-                    current_macro.push(ch);
-                }
-                '}' if brace_depth == 1 => {
-                    // Resolve the macro reference
-                    let path = self.resolve_path(at, current_macro.clone())?;
-                    let resolved_value =
-                        self.symbol_table.get(&path).context(error::NoMacroSnafu {
-                            location: at.meta.location.clone(),
-                            path: current_macro.clone(),
-                        })?;
-
-                    let mut final_value = resolved_value.clone();
-                    if matches!(final_value.data, Data::Macro(_)) {
-                        final_value = self.resolve_value(&final_value, visit_log)?;
+                // Check for unclosed delimiters
+                MacroPiece::Unclosed(segment) => {
+                    return error::NoMacroSnafu {
+                        location: at.meta.location.clone(),
+                        path: format!("Unclosed macro reference: {open}{segment}"),
                     }
-
-                    result.push_str(&final_value.to_macro_string());
-                    brace_depth = 0;
-                    current_macro.clear();
-                }
-                '}' if brace_depth > 1 => {
-                    brace_depth -= 1;
-                    current_macro.push(ch);
-                }
-                _ if brace_depth > 0 => {
-                    current_macro.push(ch);
+                    .fail();
                 }
-                _ => {
-                    result.push(ch);
+                MacroPiece::Segment(segment) => segment,
+            };
+
+            // Default segments fall back to a literal when the reference does not exist
+            if let Some((reference, fallback)) = split_default(segment) {
+                let path = self.resolve_path(at, reference.to_string())?;
+                if self.symbol_table.contains_key(&path) {
+                    let value = self.resolve_segment(at, reference, segment, &input, visit_log)?;
+                    result.push_str(&value.to_macro_string());
+                } else {
+                    result.push_str(unquote(fallback));
                 }
+                continue;
             }
-        }
 
-        // Check for unclosed braces
-        if brace_depth > 0 {
-            return error::NoMacroSnafu {
-                location: at.meta.location.clone(),
-                path: format!("Unclosed macro reference: {{{}", current_macro),
+            // Conditional segments pick one of two literal branches
+            if let Some((condition, branches)) = split_unquoted(segment, '?') {
+                let (when_true, when_false) =
+                    split_unquoted(branches, ':').context(error::NoMacroSegmentSnafu {
+                        location: at.meta.location.clone(),
+                        segment: segment.to_string(),
+                        input: input.clone(),
+                    })?;
+                let condition = condition.trim();
+                let value = self.resolve_segment(at, condition, segment, &input, visit_log)?;
+                let flag = value.as_bool().context(error::MacroConditionSnafu {
+                    location: at.meta.location.clone(),
+                    condition: condition.to_string(),
+                    found: value.type_of(),
+                })?;
+                result.push_str(unquote(if *flag { when_true } else { when_false }));
+                continue;
             }
-            .fail();
+
+            // Arithmetic segments are evaluated to a number
+            if let Some(number) = self.resolve_arithmetic(at, segment, &input, visit_log)? {
+                result.push_str(&number.to_string());
+                continue;
+            }
+
+            let final_value =
+                self.resolve_segment(at, segment.trim(), segment, &input, visit_log)?;
+            result.push_str(&final_value.to_macro_string());
         }
 
         Ok(Value {
@@ -281,6 +493,103 @@ impl Scope {
         })
     }
 
+    /// Resolves a single reference from inside of a macro string
+    fn resolve_segment(
+        &mut self,
+        at: &Value,
+        reference: &str,
+        segment: &str,
+        input: &str,
+        visit_log: &mut IndexSet<Uuid>,
+    ) -> Result<Value> {
+        let path = self.resolve_path(at, reference.to_string())?;
+        let resolved_value = self
+            .symbol_table
+            .get(&path)
+            .context(error::NoMacroSegmentSnafu {
+                location: at.meta.location.clone(),
+                segment: segment.to_string(),
+                input: input.to_string(),
+            })?;
+        self.referenced.insert(resolved_value.uid);
+
+        let mut final_value = resolved_value.clone();
+        if matches!(final_value.data, Data::Macro(_)) {
+            final_value = self.resolve_value(&final_value, visit_log)?;
+        }
+        self.trace(&path, &final_value);
+        Ok(final_value)
+    }
+
+    /// Reports a resolved reference to the trace callback, if any
+    fn trace(&self, path: &str, value: &Value) {
+        if let Some(trace) = &self.trace {
+            trace(path, value);
+        }
+    }
+
+    /// Evaluates an arithmetic segment such as `{port + 1}`
+    ///
+    /// Operands and the `+ - * /` operators must be separated by whitespace, as `-` is valid
+    /// inside of a path. Operators are applied from left to right without precedence.
+    /// Returns None when the segment is not an arithmetic expression.
+    fn resolve_arithmetic(
+        &mut self,
+        at: &Value,
+        segment: &str,
+        input: &str,
+        visit_log: &mut IndexSet<Uuid>,
+    ) -> Result<Option<Number>> {
+        let Some(tokens) = arithmetic_tokens(segment) else {
+            return Ok(None);
+        };
+
+        let mut total = self.resolve_operand(at, tokens[0], segment, input, visit_log)?;
+        for pair in tokens[1..].chunks(2) {
+            let right = self.resolve_operand(at, pair[1], segment, input, visit_log)?;
+            total =
+                total
+                    .apply(pair[0], right)
+                    .map_err(|reason| error::Error::MacroArithmetic {
+                        location: at.meta.location.clone(),
+                        expression: segment.trim().to_string(),
+                        reason,
+                    })?;
+        }
+        Ok(Some(total))
+    }
+
+    /// Reads an arithmetic operand as a number literal or a reference to a numeric value
+    fn resolve_operand(
+        &mut self,
+        at: &Value,
+        operand: &str,
+        segment: &str,
+        input: &str,
+        visit_log: &mut IndexSet<Uuid>,
+    ) -> Result<Number> {
+        if is_number_literal(operand) {
+            if let Ok(value) = operand.parse::<i128>() {
+                return Ok(Number::Integer(value));
+            }
+            if let Ok(value) = operand.parse::<f64>() {
+                return Ok(Number::Float(value));
+            }
+        }
+
+        let value = self.resolve_segment(at, operand, segment, input, visit_log)?;
+        value
+            .data
+            .integer_value()
+            .map(Number::Integer)
+            .or_else(|| value.data.float_value().map(Number::Float))
+            .context(error::MacroArithmeticSnafu {
+                location: at.meta.location.clone(),
+                expression: segment.trim().to_string(),
+                reason: format!("'{}' is a '{}', not a number", operand, value.type_of()),
+            })
+    }
+
     /// Resolves all macros in a statement
     fn resolve_statement(
         &mut self,
@@ -362,6 +671,18 @@ impl Scope {
 
                 Statement::new_assign(&at.id, Some(expected.clone()), new_value, at.meta.clone())?
             }
+            StatementType::Append(expected) => {
+                let new_value = self.resolve_value(at.get_value().unwrap(), visit_log)?;
+                ensure!(
+                    expected.can_assign(&new_value.type_of()),
+                    error::ImplicitConvertSnafu {
+                        left: expected.clone(),
+                        right: new_value.type_of()
+                    }
+                );
+
+                Statement::new_append(&at.id, Some(expected.clone()), new_value, at.meta.clone())?
+            }
         };
 
         visit_log.insert(uid);
@@ -423,7 +744,7 @@ impl Scope {
 
     /// Looks up a value by path
     pub fn lookup(&self, path: &str) -> Option<&Value> {
-        self.symbol_table.get(path)
+        self.symbol_table.get(&self.key(path.to_string()))
     }
 
     /// Returns all available paths in the symbol table
@@ -431,24 +752,359 @@ impl Scope {
         self.symbol_table.keys().collect()
     }
 
-    /// Validates that all macro references can be resolved
+    /// Maps the path of every macro to the paths it references
+    ///
+    /// Edges are recorded instead of resolved, so broken references and cycles show up as
+    /// they are written. A plain macro references a single path, a macro string references
+    /// the path of every interpolation segment, including the condition of conditional
+    /// segments, the reference of default segments and the non-literal operands of
+    /// arithmetic segments. Relative paths are
+    /// made absolute, each referenced path is listed once in the order it first appears.
+    pub fn dependency_graph(&self) -> IndexMap<String, Vec<String>> {
+        let mut graph = IndexMap::new();
+        for (path, value) in self.symbol_table.iter() {
+            let Data::Macro(input) = &value.data else {
+                continue;
+            };
+
+            let (open, close) = &self.delimiters;
+            let references = match self.resolve_path(value, input.clone()) {
+                Ok(reference)
+                    if self.symbol_table.contains_key(&reference)
+                        || !input.contains(open.as_str()) =>
+                {
+                    vec![reference]
+                }
+                _ => scan_macro_string(input, open, close)
+                    .into_iter()
+                    .filter_map(|piece| match piece {
+                        MacroPiece::Segment(segment) => Some(segment),
+                        _ => None,
+                    })
+                    .flat_map(segment_references)
+                    .filter_map(|reference| self.resolve_path(value, reference.to_string()).ok())
+                    .collect::<IndexSet<_>>()
+                    .into_iter()
+                    .collect(),
+            };
+            graph.insert(path.clone(), references);
+        }
+        graph
+    }
+
+    /// Validates that all macro references can be resolved, failing on the first one that cannot
     pub fn validate_macros(&self) -> Result<()> {
-        for (_path, value) in &self.symbol_table {
-            if let Data::Macro(macro_ref) = &value.data {
-                let resolved_path = self.resolve_path(value, macro_ref.clone())?;
-                if !self.symbol_table.contains_key(&resolved_path) {
-                    return error::NoMacroSnafu {
-                        location: value.meta.location.clone(),
-                        path: macro_ref.clone(),
+        match self.validate_macros_all().into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates every macro reference and returns all of the errors found
+    ///
+    /// Each interpolation segment of a macro string is checked on its own, so a string
+    /// with several broken references reports each of them. A broken macro referenced by
+    /// other macros is only reported where it is defined.
+    pub fn validate_macros_all(&self) -> Vec<error::Error> {
+        let mut resolver = Scope {
+            root: self.root.clone(),
+            symbol_table: self.symbol_table.clone(),
+            path_lookup: self.path_lookup.clone(),
+            recursion_depth: 0,
+            recursion_limit: self.recursion_limit,
+            delimiters: self.delimiters.clone(),
+            resolved_cache: IndexMap::new(),
+            referenced: IndexSet::new(),
+            trace: None,
+            case_insensitive_keys: self.case_insensitive_keys,
+        };
+
+        let mut errors = Vec::new();
+        let mut seen = IndexSet::new();
+        for value in self.symbol_table.values() {
+            let Data::Macro(input) = &value.data else {
+                continue;
+            };
+
+            let is_reference = self
+                .resolve_path(value, input.clone())
+                .is_ok_and(|path| self.symbol_table.contains_key(&path));
+            let checks = if is_reference || !input.contains(self.delimiters.0.as_str()) {
+                vec![input.clone()]
+            } else {
+                macro_segments(input, &self.delimiters)
+            };
+
+            for check in checks {
+                let check = Value {
+                    uid: value.uid,
+                    data: Data::Macro(check),
+                    meta: value.meta.clone(),
+                };
+                resolver.recursion_depth = 0;
+                let mut error = match resolver.resolve_value(&check, &mut IndexSet::new()) {
+                    Ok(_) => continue,
+                    // A reference that exists can only fail further down the chain, the
+                    // macro it points at reports that failure itself unless it is a cycle
+                    Err(error::Error::NoMacro { .. } | error::Error::NoMacroSegment { .. })
+                        if is_reference =>
+                    {
+                        continue;
                     }
-                    .fail();
+                    Err(error) => error,
+                };
+                if let error::Error::NoMacroSegment {
+                    input: segment_input,
+                    ..
+                } = &mut error
+                {
+                    *segment_input = input.clone();
+                }
+                if seen.insert(error.to_string()) {
+                    errors.push(error);
+                }
+            }
+        }
+        errors
+    }
+
+    /// Validates that every symbol used as a reference names an existing path
+    ///
+    /// Symbols holding a `/` are references, their segments are read as a path so
+    /// `:server/port` references `server.port`. The path may name a value or anything with
+    /// values below it such as a section. Plain symbols like `:info` are not references
+    /// and are never reported. Returns an error for every broken reference in the document.
+    pub fn validate_symbols(&self) -> Vec<error::Error> {
+        self.root
+            .symbols()
+            .into_iter()
+            .filter(|(symbol, _)| symbol.contains('/'))
+            .filter(|(symbol, _)| {
+                let path = self.key(symbol.replace('/', "."));
+                let prefix = format!("{path}.");
+                !self
+                    .symbol_table
+                    .keys()
+                    .any(|key| key == &path || key.starts_with(&prefix))
+            })
+            .map(|(symbol, location)| error::Error::NoSymbol {
+                location: location.clone(),
+                symbol,
+            })
+            .collect()
+    }
+}
+
+/// Splits a macro string into standalone macro strings holding one segment each
+///
+/// Unclosed segments are kept with the rest of the input so resolving them reports the
+/// same error as resolving the whole string.
+fn macro_segments(input: &str, (open, close): &(String, String)) -> Vec<String> {
+    scan_macro_string(input, open, close)
+        .into_iter()
+        .filter_map(|piece| match piece {
+            MacroPiece::Text(_) => None,
+            MacroPiece::Segment(segment) => Some(format!("{open}{segment}{close}")),
+            MacroPiece::Unclosed(segment) => Some(format!("{open}{segment}")),
+        })
+        .collect()
+}
+
+/// Lists the references read by a single interpolation segment
+fn segment_references(segment: &str) -> Vec<&str> {
+    if let Some((reference, _)) = split_default(segment) {
+        return vec![reference];
+    }
+    if let Some((condition, _)) = split_unquoted(segment, '?') {
+        return vec![condition.trim()];
+    }
+    match arithmetic_tokens(segment) {
+        Some(tokens) => tokens
+            .into_iter()
+            .step_by(2)
+            .filter(|operand| !is_number_literal(operand))
+            .collect(),
+        None => vec![segment.trim()],
+    }
+}
+
+/// Splits an arithmetic segment into alternating operands and operators
+///
+/// Returns None when the segment is not an arithmetic expression.
+fn arithmetic_tokens(segment: &str) -> Option<Vec<&str>> {
+    let tokens: Vec<&str> = segment.split_whitespace().collect();
+    let is_expression = tokens.len() >= 3
+        && tokens.len() % 2 == 1
+        && tokens
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|token| matches!(*token, "+" | "-" | "*" | "/"));
+    is_expression.then_some(tokens)
+}
+
+/// Returns true if an arithmetic operand is a number rather than a reference
+fn is_number_literal(operand: &str) -> bool {
+    operand
+        .trim_start_matches('-')
+        .starts_with(|ch: char| ch.is_ascii_digit())
+}
+
+/// Part of a macro string found while scanning it for interpolation segments
+#[derive(Debug, PartialEq)]
+enum MacroPiece<'a> {
+    /// Literal text, escaped delimiters are already unescaped
+    Text(&'a str),
+    /// Contents of a segment between the delimiters
+    Segment(&'a str),
+    /// Contents following an opening delimiter that is never closed
+    Unclosed(&'a str),
+}
+
+/// Scans a macro string for segments surrounded by the given delimiters
+///
+/// Closing delimiters inside double quotes do not end a segment so conditional branches
+/// may contain them.
+fn scan_macro_string<'a>(input: &'a str, open: &str, close: &str) -> Vec<MacroPiece<'a>> {
+    let escape_close = open == DEFAULT_OPEN_DELIMITER && close == DEFAULT_CLOSE_DELIMITER;
+    let mut pieces = Vec::new();
+    let mut text_start = 0;
+    let mut index = 0;
+    while index < input.len() {
+        let rest = &input[index..];
+        let escaped = if rest.starts_with(open) && rest[open.len()..].starts_with(open) {
+            Some(open.len())
+        } else if escape_close && rest.starts_with(close) && rest[close.len()..].starts_with(close)
+        {
+            Some(close.len())
+        } else {
+            None
+        };
+        if let Some(length) = escaped {
+            pieces.push(MacroPiece::Text(&input[text_start..index + length]));
+            index += length * 2;
+            text_start = index;
+            continue;
+        }
+
+        if let Some(contents) = rest.strip_prefix(open) {
+            if text_start < index {
+                pieces.push(MacroPiece::Text(&input[text_start..index]));
+            }
+            let mut quoted = false;
+            let end = contents.char_indices().find_map(|(offset, ch)| {
+                if ch == '"' {
+                    quoted = !quoted;
                 }
+                (!quoted && contents[offset..].starts_with(close)).then_some(offset)
+            });
+            let Some(end) = end else {
+                pieces.push(MacroPiece::Unclosed(contents));
+                return pieces;
+            };
+            pieces.push(MacroPiece::Segment(&contents[..end]));
+            index += open.len() + end + close.len();
+            text_start = index;
+            continue;
+        }
+
+        index += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    if text_start < input.len() {
+        pieces.push(MacroPiece::Text(&input[text_start..]));
+    }
+    pieces
+}
+
+/// Number produced while evaluating an arithmetic macro segment
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Integer(i128),
+    Float(f64),
+}
+
+impl Number {
+    /// Applies an operator, integers stay integers unless either side is a float
+    fn apply(self, operator: &str, right: Number) -> std::result::Result<Number, String> {
+        match (self, right) {
+            (Number::Integer(left), Number::Integer(right)) => {
+                let value = match operator {
+                    "+" => left.checked_add(right),
+                    "-" => left.checked_sub(right),
+                    "*" => left.checked_mul(right),
+                    _ if right == 0 => return Err("division by zero".to_string()),
+                    _ => left.checked_div(right),
+                };
+                value
+                    .map(Number::Integer)
+                    .ok_or_else(|| "integer overflow".to_string())
+            }
+            (left, right) => {
+                let (left, right) = (left.as_float(), right.as_float());
+                Ok(Number::Float(match operator {
+                    "+" => left + right,
+                    "-" => left - right,
+                    "*" => left * right,
+                    _ if right == 0.0 => return Err("division by zero".to_string()),
+                    _ => left / right,
+                }))
             }
         }
-        Ok(())
+    }
+
+    fn as_float(self) -> f64 {
+        match self {
+            Number::Integer(value) => value as f64,
+            Number::Float(value) => value,
+        }
     }
 }
 
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Integer(value) => write!(f, "{}", value),
+            // Floats keep their decimal point so a whole result still reads as a float
+            Number::Float(value) => write_float(f, *value, ""),
+        }
+    }
+}
+
+/// Splits the input at the first delimiter that is not inside of a double quoted literal
+fn split_unquoted(input: &str, delimiter: char) -> Option<(&str, &str)> {
+    let mut quoted = false;
+    for (index, ch) in input.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            ch if ch == delimiter && !quoted => {
+                return Some((&input[..index], &input[index + ch.len_utf8()..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits a default segment such as `{db.host:-localhost}` into its trimmed reference and
+/// the fallback written after `:-`
+///
+/// The fallback may contain anything, including a `?`. A reference holding a `?` is the
+/// condition of a conditional segment whose false branch starts with `-` instead.
+fn split_default(segment: &str) -> Option<(&str, &str)> {
+    let (reference, rest) = split_unquoted(segment, ':')?;
+    let fallback = rest.strip_prefix('-')?;
+    (!reference.contains('?')).then(|| (reference.trim(), fallback))
+}
+
+/// Trims a conditional branch or fallback and removes the double quotes around it if present
+fn unquote(input: &str) -> &str {
+    let input = input.trim();
+    input
+        .strip_prefix('"')
+        .and_then(|x| x.strip_suffix('"'))
+        .unwrap_or(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -497,4 +1153,635 @@ mod tests {
             assert_eq!(resolved_value.as_string(), Some(&"hello".to_string()));
         }
     }
+
+    fn resolve(code: &str) -> Result<Statement> {
+        crate::from_str(code)
+    }
+
+    #[test]
+    fn test_indexed_interpolation() {
+        let module = resolve(
+            "servers = [{ host = 'alpha' }, { host = 'beta' }]\n\
+             ports = [80, 443]\n\
+             first = m!servers.0.host\n\
+             url = m'https://{servers.1.host}:{ports.1}'\n",
+        )
+        .unwrap();
+
+        let first = module.find_by_path("first").unwrap().get_value().unwrap();
+        assert_eq!(first.as_string(), Some(&"alpha".to_string()));
+
+        let url = module.find_by_path("url").unwrap().get_value().unwrap();
+        assert_eq!(url.as_string(), Some(&"https://beta:443".to_string()));
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        let module =
+            resolve("name = 'barkml'\ntemplate = m'{{{name}}} uses {{braces}}'\n").unwrap();
+
+        let template = module
+            .find_by_path("template")
+            .unwrap()
+            .get_value()
+            .unwrap();
+        assert_eq!(
+            template.as_string(),
+            Some(&"{barkml} uses {braces}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_failing_segment_error() {
+        let error =
+            resolve("servers = [{ host = 'alpha' }]\nurl = m'{servers.0.host}:{servers.3.port}'\n")
+                .unwrap_err();
+
+        assert!(matches!(
+            &error,
+            error::Error::NoMacroSegment { segment, .. } if segment == "servers.3.port"
+        ));
+        assert!(error.to_string().contains("'{servers.3.port}'"));
+    }
+
+    #[test]
+    fn test_trace() {
+        use std::sync::{Arc, Mutex};
+
+        let parsed = crate::Parser::new(
+            "root",
+            logos::Logos::lexer(
+                "port = 80\nbase = m!port\nnext = m!base\n\
+                 [app]\nurl = m'http://{@app.host}:{port}/{missing:-index}'\nhost = 'localhost'\n",
+            ),
+        )
+        .parse()
+        .unwrap();
+        let trace = Arc::new(Mutex::new(Vec::new()));
+        let log = trace.clone();
+        let mut scope = Scope::with_trace(&parsed, move |path, value| {
+            log.lock()
+                .unwrap()
+                .push((path.to_string(), value.to_macro_string()));
+        });
+        let module = scope.apply().unwrap();
+        let url = module.find_by_path("app.url").unwrap().get_value().unwrap();
+        assert_eq!(
+            url.as_string(),
+            Some(&"http://localhost:80/index".to_string())
+        );
+
+        let expected = [
+            ("port", "80"),
+            ("port", "80"),
+            ("base", "80"),
+            ("app.host", "localhost"),
+            ("port", "80"),
+        ];
+        let trace = trace.lock().unwrap();
+        assert_eq!(
+            trace
+                .iter()
+                .map(|(path, value)| (path.as_str(), value.as_str()))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_default_interpolation() {
+        let source = "[db]\nhost = 'db.internal'\n[app]\n\
+                      primary = m'{db.host:-localhost}'\n\
+                      replica = m'{db.replica:-localhost}:{db.port:-5432}'\n\
+                      banner = m'{app.title:- \"my app: {v}\" }'\n\
+                      query = m'{app.filter:-a?b}'\n";
+        let module = resolve(source).unwrap();
+        let string = |path: &str| {
+            module
+                .find_by_path(path)
+                .unwrap()
+                .get_value()
+                .unwrap()
+                .as_string()
+                .cloned()
+        };
+        assert_eq!(string("app.primary"), Some("db.internal".to_string()));
+        assert_eq!(string("app.replica"), Some("localhost:5432".to_string()));
+        assert_eq!(string("app.banner"), Some("my app: {v}".to_string()));
+        assert_eq!(string("app.query"), Some("a?b".to_string()));
+
+        // Fallbacks count as resolved while their reference is still a dependency
+        let parsed = crate::Parser::new("root", logos::Logos::lexer(source))
+            .parse()
+            .unwrap();
+        let scope = Scope::new(&parsed);
+        assert!(scope.validate_macros_all().is_empty());
+        assert_eq!(
+            scope.dependency_graph()["app.replica"],
+            vec!["db.replica", "db.port"]
+        );
+
+        // Without a fallback a missing reference is still an error
+        assert!(matches!(
+            resolve("host = m'{db.host}'\n"),
+            Err(error::Error::NoMacroSegment { .. })
+        ));
+    }
+
+    #[test]
+    fn test_conditional_interpolation() {
+        let module = resolve(
+            "debug = true\nverbose = false\nmode = m'{debug ? \"on\" : \"off\"}'\nlevel = m'log {verbose ? \"trace\" : \"info: {}\"}'\noffset = m'{verbose ? 1 :-1}'\n",
+        )
+        .unwrap();
+
+        let mode = module.find_by_path("mode").unwrap().get_value().unwrap();
+        assert_eq!(mode.as_string(), Some(&"on".to_string()));
+        let level = module.find_by_path("level").unwrap().get_value().unwrap();
+        assert_eq!(level.as_string(), Some(&"log info: {}".to_string()));
+        let offset = module.find_by_path("offset").unwrap().get_value().unwrap();
+        assert_eq!(offset.as_string(), Some(&"-1".to_string()));
+    }
+
+    fn unresolved(code: &str) -> Statement {
+        use crate::Loader;
+        crate::StandardLoader::default()
+            .skip_macro_resolution()
+            .unwrap()
+            .add_file_str(code, None)
+            .unwrap()
+            .load()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_absolute_paths() {
+        let module = resolve(
+            "host = 'root'\n\
+             [app]\n\
+             host = 'app'\n\
+             absolute = m!@host\n\
+             parent = m!super.host\n\
+             bare = m!host\n\
+             joined = m'{@host}-{super.host}'\n\
+             [other]\n\
+             elsewhere = m!@app.host\n\
+             server 'web' { target = m!@app.host }\n",
+        )
+        .unwrap();
+        let string = |path: &str| {
+            module
+                .find_by_path(path)
+                .and_then(|x| x.get_value())
+                .and_then(|x| x.as_string())
+                .cloned()
+        };
+        assert_eq!(string("app.absolute"), Some("root".to_string()));
+        assert_eq!(string("app.parent"), Some("app".to_string()));
+        assert_eq!(string("app.bare"), Some("root".to_string()));
+        assert_eq!(string("app.joined"), Some("root-app".to_string()));
+        assert_eq!(string("other.elsewhere"), Some("app".to_string()));
+        let server = module
+            .find_by_path("other")
+            .and_then(|x| x.find_block("server", &["web"]))
+            .unwrap();
+        assert_eq!(
+            server
+                .find_child("target")
+                .unwrap()
+                .get_value()
+                .unwrap()
+                .as_string(),
+            Some(&"app".to_string())
+        );
+
+        assert_matches::assert_matches!(
+            resolve("[app]\nvalue = m!@missing\n"),
+            Err(error::Error::Parse { .. } | error::Error::NoMacro { .. })
+        );
+    }
+
+    #[test]
+    fn test_apply_preserves_order() {
+        let code = "\
+[zeta]
+name = m!alpha.name
+port = 1
+[alpha]
+name = 'first'
+listener 'b' { port = m!zeta.port }
+listener 'a' { port = 2 }
+[mid]
+values = { z = 3, a = 1, m = 2 }
+[delta]
+count = 3
+banner = m'{omega.tag}-{zeta.port}'
+[omega]
+tag = 'last'
+first = 1
+";
+        let module = unresolved(code);
+        let resolved = Scope::new(&module).apply().unwrap();
+
+        let order = |statement: &Statement| {
+            let mut ids = Vec::new();
+            for section in statement.children() {
+                ids.push(section.id.clone());
+                for child in section.children() {
+                    ids.push(format!("{}.{}", section.id, child.inject_id()));
+                }
+            }
+            ids
+        };
+        assert_eq!(order(&resolved), order(&module));
+        assert_eq!(
+            order(&resolved)
+                .iter()
+                .filter(|x| !x.contains('.'))
+                .collect::<Vec<_>>(),
+            vec!["zeta", "alpha", "mid", "delta", "omega"]
+        );
+
+        let values = resolved.find_by_path("mid.values").unwrap().get_value();
+        let keys = values
+            .unwrap()
+            .as_table()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["z", "a", "m"]);
+
+        // The printed document lists everything in its original order as well
+        let printed = resolved.to_string();
+        let position = |needle: &str| printed.find(needle).unwrap();
+        let needles = [
+            "[zeta]",
+            "[alpha]",
+            "listener 'b'",
+            "listener 'a'",
+            "[mid]",
+            "[delta]",
+            "[omega]",
+            "tag:",
+            "first:",
+        ];
+        for pair in needles.windows(2) {
+            assert!(position(pair[0]) < position(pair[1]), "{printed}");
+        }
+        assert_eq!(
+            crate::from_str(code).unwrap().to_string(),
+            printed,
+            "loading resolves macros the same way"
+        );
+    }
+
+    #[test]
+    fn test_custom_delimiters() {
+        let module = unresolved(
+            "name = 'web'
+             port = 8080
+             shell = m'echo ${name}:${port + 1} {} ${${name}'
+             json = m'{\"host\": \"${name}\", \"ports\": {}}'
+",
+        );
+        let resolved = Scope::new(&module)
+            .with_delimiters("${", "}")
+            .unwrap()
+            .apply()
+            .unwrap();
+        let string = |path: &str| {
+            resolved
+                .find_by_path(path)
+                .and_then(|x| x.get_value())
+                .and_then(|x| x.as_string())
+                .cloned()
+        };
+        assert_eq!(
+            string("shell"),
+            Some("echo web:8081 {} ${name}".to_string())
+        );
+        assert_eq!(
+            string("json"),
+            Some("{\"host\": \"web\", \"ports\": {}}".to_string())
+        );
+
+        let scope = Scope::new(&unresolved("value = m'<<missing>> {}'\n"))
+            .with_delimiters("<<", ">>")
+            .unwrap();
+        let errors = scope.validate_macros_all();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], error::Error::NoMacroSegment { .. }));
+
+        let module = unresolved("value = m'${name'\n");
+        assert!(matches!(
+            Scope::new(&module)
+                .with_delimiters("${", "}")
+                .unwrap()
+                .apply(),
+            Err(error::Error::NoMacro { .. })
+        ));
+
+        assert!(matches!(
+            Scope::new(&module).with_delimiters("", "}"),
+            Err(error::Error::MacroDelimiters { .. })
+        ));
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        // link0 = m!link1, ..., link{length} = 1
+        let chain = |length: usize| {
+            let mut code = (0..length)
+                .map(|x| format!("link{} = m!link{}\n", x, x + 1))
+                .collect::<String>();
+            code.push_str(&format!("link{length} = 1\n"));
+            unresolved(&code)
+        };
+
+        let module = chain(150);
+        assert!(matches!(
+            Scope::new(&module).apply(),
+            Err(error::Error::RecursionLimit { limit, .. }) if limit == DEFAULT_RECURSION_LIMIT
+        ));
+        let resolved = Scope::new(&module)
+            .with_recursion_limit(200)
+            .apply()
+            .unwrap();
+        let first = resolved.find_by_path("link0").and_then(|x| x.get_value());
+        assert_eq!(first.and_then(|x| x.as_int()), Some(&1));
+
+        assert!(matches!(
+            Scope::new(&module).with_recursion_limit(120).apply(),
+            Err(error::Error::RecursionLimit { limit: 120, .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolved_value_at() {
+        let module = unresolved(
+            "name = 'web'\n\
+             port = 80\n\
+             url = m'http://{name}:{port}'\n\
+             alias = m!url\n\
+             other = m!port\n",
+        );
+        let mut scope = Scope::new(&module);
+        let uid = |scope: &Scope, path: &str| scope.lookup(path).unwrap().uid;
+        let (alias, url, name, other) = (
+            uid(&scope, "alias"),
+            uid(&scope, "url"),
+            uid(&scope, "name"),
+            uid(&scope, "other"),
+        );
+
+        let resolved = scope.resolved_value_at(alias).unwrap();
+        assert_eq!(resolved.uid, alias);
+        assert_eq!(resolved.as_string().unwrap(), "http://web:80");
+        assert_eq!(scope.resolved_value_at(other).unwrap().as_int(), Some(&80));
+
+        // Setting a referenced value invalidates every value that referenced it
+        scope.set_symbol(
+            "name",
+            Value::new_string("api".to_string(), Metadata::default()),
+        );
+        assert!(!scope.invalidate(name));
+        assert!(scope.resolved_cache.contains_key(&other));
+        assert!(!scope.resolved_cache.contains_key(&alias));
+        assert_eq!(
+            scope.resolved_value_at(alias).unwrap().as_string().unwrap(),
+            "http://api:80"
+        );
+        assert!(scope.invalidate(url));
+        assert!(!scope.invalidate(url));
+
+        assert!(matches!(
+            scope.resolved_value_at(Uuid::now_v7()),
+            Err(error::Error::NoField { .. })
+        ));
+    }
+
+    #[test]
+    fn test_scope_overrides() {
+        let module = unresolved(
+            "release = 'dev'\n\
+             [app]\n\
+             commit = m!build.sha\n\
+             tag = m'{release}-{build.sha}'\n\
+             region = m!deploy.regions.0\n",
+        );
+        let meta = Metadata::new(Location::new(0, 0));
+        let overrides = IndexMap::from([
+            (
+                "build.sha".to_string(),
+                Value::new_string("abc123".to_string(), meta.clone()),
+            ),
+            (
+                "release".to_string(),
+                Value::new_string("1.0".to_string(), meta.clone()),
+            ),
+            (
+                "deploy".to_string(),
+                Value::new_table(
+                    IndexMap::from([(
+                        "regions".to_string(),
+                        Value::new_array(
+                            vec![Value::new_string("eu".to_string(), meta.clone())],
+                            meta.clone(),
+                        ),
+                    )]),
+                    meta,
+                ),
+            ),
+        ]);
+
+        let resolved = Scope::with_overrides(&module, overrides).apply().unwrap();
+        let string = |path: &str| {
+            resolved
+                .find_by_path(path)
+                .and_then(|x| x.get_value())
+                .and_then(|x| x.as_string())
+                .cloned()
+        };
+        assert_eq!(string("app.commit"), Some("abc123".to_string()));
+        // The override shadows the document value for macros only
+        assert_eq!(string("app.tag"), Some("1.0-abc123".to_string()));
+        assert_eq!(string("release"), Some("dev".to_string()));
+        assert_eq!(string("app.region"), Some("eu".to_string()));
+
+        // Without the overrides the references cannot be resolved
+        assert!(Scope::new(&module).apply().is_err());
+    }
+
+    #[test]
+    fn test_validate_macros_all() {
+        let scope = Scope::new(&unresolved(
+            "name = 'app'\n\
+             owner = m!missing.owner\n\
+             motd = m'{name} on {server.host}'\n\
+             alias = m!owner\n\
+             greeting = m'hello {name}'\n",
+        ));
+
+        let errors = scope.validate_macros_all();
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(matches!(
+            &errors[0],
+            error::Error::NoMacro { path, .. } if path == "missing.owner"
+        ));
+        assert!(matches!(
+            &errors[1],
+            error::Error::NoMacroSegment { segment, input, location }
+                if segment == "server.host" && input == "{name} on {server.host}" && location.line == 2
+        ));
+        assert!(matches!(
+            scope.validate_macros(),
+            Err(error::Error::NoMacro { .. })
+        ));
+
+        let scope = Scope::new(&unresolved(
+            "name = 'app'\nalias = m!name\ngreeting = m'hello {name}, {{literal}}'\n",
+        ));
+        assert!(scope.validate_macros_all().is_empty());
+        assert!(scope.validate_macros().is_ok());
+
+        // Cycles are reported even though every reference exists
+        let scope = Scope::new(&unresolved("first = m!second\nsecond = m!first\n"));
+        assert!(!scope.validate_macros_all().is_empty());
+    }
+
+    #[test]
+    fn test_validate_symbols() {
+        let scope = Scope::new(&unresolved(
+            "level = :info\n\
+             next = :server/port\n\
+             parent = :server\n\
+             [server]\n\
+             port = 80\n\
+             limits = { cpu = 2 }\n\
+             [client]\n\
+             targets = [:server/limits, :server/limits/cpu, :server/host, :client/retry]\n",
+        ));
+
+        let errors = scope.validate_symbols();
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(matches!(
+            &errors[0],
+            error::Error::NoSymbol { symbol, location } if symbol == "server/host" && location.line == 7
+        ));
+        assert!(matches!(
+            &errors[1],
+            error::Error::NoSymbol { symbol, .. } if symbol == "client/retry"
+        ));
+
+        // Sections are references as long as something is declared below them
+        let scope = Scope::new(&unresolved(
+            "[server]\nport = 80\n[client]\ntarget = :server/port\n",
+        ));
+        assert!(scope.validate_symbols().is_empty());
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        fn edges<'a>(graph: &'a IndexMap<String, Vec<String>>, path: &str) -> Option<Vec<&'a str>> {
+            graph
+                .get(path)
+                .map(|x| x.iter().map(|x| x.as_str()).collect())
+        }
+
+        // a -> b -> c
+        let graph = Scope::new(&unresolved(
+            "a = m!b
+b = m!c
+c = 1
+",
+        ))
+        .dependency_graph();
+        assert_eq!(graph.len(), 2);
+        assert_eq!(edges(&graph, "a"), Some(vec!["b"]));
+        assert_eq!(edges(&graph, "b"), Some(vec!["c"]));
+        assert_eq!(edges(&graph, "c"), None);
+
+        // a -> b -> a
+        let graph = Scope::new(&unresolved(
+            "a = m!b
+b = m!a
+",
+        ))
+        .dependency_graph();
+        assert_eq!(edges(&graph, "a"), Some(vec!["b"]));
+        assert_eq!(edges(&graph, "b"), Some(vec!["a"]));
+
+        let graph = Scope::new(&unresolved(
+            "name = 'app'
+             port = 80
+             debug = true
+             [server]
+             host = 'localhost'
+             motd = m'{name} on {super.host}:{port + 1} {debug ? \"debug\" : \"\"} {name} {{literal}}'
+             owner = m!missing.owner
+             alias = m!@name
+",
+        ))
+        .dependency_graph();
+        assert_eq!(
+            edges(&graph, "server.motd"),
+            Some(vec!["name", "server.host", "port", "debug"])
+        );
+        assert_eq!(edges(&graph, "server.owner"), Some(vec!["missing.owner"]));
+        assert_eq!(edges(&graph, "server.alias"), Some(vec!["name"]));
+    }
+
+    #[test]
+    fn test_arithmetic_interpolation() {
+        let module = resolve(
+            "port = 8080\nbase = 2.5\nreplicas = 3\n\
+             next = m'{port + 1}'\n\
+             scaled = m'{base * 2}'\n\
+             quarter = m'{base / 4}'\n\
+             total = m'{replicas * 2 - 1} of {replicas / 2}'\n",
+        )
+        .unwrap();
+
+        let value = |path: &str| {
+            module
+                .find_by_path(path)
+                .unwrap()
+                .get_value()
+                .unwrap()
+                .as_string()
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(value("next"), "8081");
+        assert_eq!(value("scaled"), "5.0");
+        assert_eq!(value("quarter"), "0.625");
+        assert_eq!(value("total"), "5 of 1");
+    }
+
+    #[test]
+    fn test_arithmetic_errors() {
+        let error = resolve("port = 8080\nnext = m'{port / 0}'\n").unwrap_err();
+        assert!(matches!(
+            &error,
+            error::Error::MacroArithmetic { reason, .. } if reason == "division by zero"
+        ));
+
+        let error = resolve("name = 'app'\nnext = m'{name + 1}'\n").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("'name' is a 'string', not a number")
+        );
+    }
+
+    #[test]
+    fn test_conditional_requires_bool() {
+        let error = resolve("port = 80\nmode = m'{port ? \"on\" : \"off\"}'\n").unwrap_err();
+
+        assert!(matches!(
+            &error,
+            error::Error::MacroCondition { condition, .. } if condition == "port"
+        ));
+        assert!(error.to_string().contains("must be a bool"));
+    }
 }
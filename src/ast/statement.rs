@@ -1,8 +1,9 @@
-use super::types::{Metadata, StatementType, ValueType};
+use super::types::{Location, Metadata, StatementType, ValueType, quote_key};
 use super::value::{Data, Value};
 use crate::{Result, error};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
 use std::fmt;
 use uuid::Uuid;
 
@@ -120,6 +121,80 @@ impl Statement {
         self.data.children_mut()
     }
 
+    /// Returns an iterator over the child statements that are sections
+    pub fn sections(&self) -> impl Iterator<Item = &Statement> + '_ {
+        self.children()
+            .filter(|child| matches!(child.type_, StatementType::Section(_)))
+    }
+
+    /// Returns an iterator over the child statements that are blocks
+    ///
+    /// Each item holds the block id, its labels and the block statement itself.
+    pub fn blocks(&self) -> impl Iterator<Item = (&str, &[Value], &Statement)> + '_ {
+        self.children().filter_map(|child| {
+            child
+                .get_labeled()
+                .map(|(labels, _)| (child.id.as_str(), labels.as_slice(), child))
+        })
+    }
+
+    /// Finds a child block by its id and label values
+    ///
+    /// Labels are compared by their textual value, so the string label `'web'` and the
+    /// integer label `80` are matched by `"web"` and `"80"`. The number of labels must match.
+    pub fn find_block(&self, id: &str, labels: &[&str]) -> Option<&Statement> {
+        self.blocks()
+            .find(|(block_id, block_labels, _)| {
+                *block_id == id
+                    && block_labels.len() == labels.len()
+                    && block_labels
+                        .iter()
+                        .zip(labels)
+                        .all(|(label, expected)| label.to_macro_string() == *expected)
+            })
+            .map(|(_, _, block)| block)
+    }
+
+    /// Iterates over every leaf value in the tree with its dot separated path
+    ///
+    /// Paths are built from statement ids, using the inject id of blocks so `listener 'http'`
+    /// contributes `listener.http`, and continue into tables and arrays with their keys and
+    /// indices like [`Value::flatten`]. The root module is not part of any path. Empty tables
+    /// and arrays are leaves themselves.
+    pub fn leaves(&self) -> impl Iterator<Item = (String, &Value)> + '_ {
+        let mut entries = Vec::new();
+        self.leaves_into(String::new(), &mut entries);
+        entries.into_iter()
+    }
+
+    /// Collects every symbol value in the tree with its location, in declaration order
+    ///
+    /// Symbols inside tables and arrays are included, block labels are not. The same
+    /// symbol is listed once for every place it is used.
+    pub fn symbols(&self) -> Vec<(String, &Location)> {
+        self.leaves()
+            .filter_map(|(_, value)| match &value.data {
+                Data::Symbol(symbol) => Some((symbol.clone(), &value.meta.location)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn leaves_into<'a>(&'a self, path: String, entries: &mut Vec<(String, &'a Value)>) {
+        let path = match self.type_ {
+            StatementType::Module(_) => path,
+            _ => Self::child_path(&path, &self.inject_id()),
+        };
+        match &self.data {
+            StatementData::Single(value) => value.leaves_into(path, ".", entries),
+            StatementData::Group(children) | StatementData::Labeled(_, children) => {
+                for child in children.values() {
+                    child.leaves_into(path.clone(), entries);
+                }
+            }
+        }
+    }
+
     /// Recursively counts all statements in the tree
     pub fn total_statement_count(&self) -> usize {
         1 + self
@@ -149,23 +224,237 @@ impl Statement {
     }
 
     /// Recursively searches for a statement by path (dot-separated)
+    ///
+    /// Segments can be wrapped in single or double quotes to reach keys that contain a dot,
+    /// such as `section.'a.b'`, and labels can be given in brackets, such as
+    /// `server['a.b'].port`. Paths are resolved like [`Statement::find_by_segments`].
     pub fn find_by_path(&self, path: &str) -> Option<&Statement> {
-        let parts: Vec<&str> = path.split('.').collect();
-        self.find_by_path_parts(&parts)
+        self.find_by_segments(&split_path(path))
     }
 
-    fn find_by_path_parts(&self, parts: &[&str]) -> Option<&Statement> {
-        if parts.is_empty() {
-            return Some(self);
+    /// Recursively searches for a statement by already split path segments
+    ///
+    /// Each segment is first looked up as the key of a child. Otherwise it is matched
+    /// against the id of a labeled block whose labels, compared like
+    /// [`Statement::find_block`], are the segments that follow. Segments are never split
+    /// on dots, so `["server", "a.b", "port"]` reaches `port` in the block `server 'a.b'`.
+    pub fn find_by_segments(&self, segments: &[&str]) -> Option<&Statement> {
+        let mut current = self;
+        let mut rest = segments;
+        while !rest.is_empty() {
+            let (key, used) = current.segment_key(rest)?;
+            current = current.find_child(&key)?;
+            rest = &rest[used..];
         }
+        Some(current)
+    }
 
-        let child = self.find_child(parts[0])?;
-        if parts.len() == 1 {
-            Some(child)
-        } else {
-            child.find_by_path_parts(&parts[1..])
+    /// Finds the key of the child reached by the leading segments and how many it used
+    fn segment_key(&self, segments: &[&str]) -> Option<(String, usize)> {
+        let (first, rest) = segments.split_first()?;
+        let children = self.get_grouped()?;
+        if children.contains_key(*first) {
+            return Some((first.to_string(), 1));
+        }
+        children.iter().find_map(|(key, child)| {
+            let (labels, _) = child.get_labeled()?;
+            let matches = child.id == *first
+                && labels.len() <= rest.len()
+                && labels
+                    .iter()
+                    .zip(rest)
+                    .all(|(label, expected)| label.to_macro_string() == *expected);
+            matches.then(|| (key.clone(), 1 + labels.len()))
+        })
+    }
+
+    /// Returns the path segment that unambiguously names this statement within its parent
+    ///
+    /// The id is quoted when it is not a plain identifier and each label follows in
+    /// brackets, so the block `server 'a.b'` is named `server['a.b']`. Joining the
+    /// segments of nested statements with `.` gives a path [`Statement::find_by_path`]
+    /// resolves back to the statement.
+    pub fn canonical_id_path(&self) -> String {
+        let mut path = quote_key(&self.id);
+        if let Some((labels, _)) = self.get_labeled() {
+            for label in labels {
+                path.push('[');
+                path.push_str(&quote_key(&label.to_macro_string()));
+                path.push(']');
+            }
+        }
+        path
+    }
+
+    /// Renames a direct child, keeping its position among the other children
+    ///
+    /// Both the id of the child and its key are updated, the key of a labeled block is
+    /// rebuilt from the new id and its labels. Fails when there is no child with the key
+    /// `old` or when the new key is already taken by another child.
+    pub fn rename_child(&mut self, old: &str, new: &str) -> Result<()> {
+        let location = self.meta.location.clone();
+        let children = match &mut self.data {
+            StatementData::Labeled(_, children) | StatementData::Group(children) => children,
+            StatementData::Single(_) => {
+                return error::NotScopeSnafu { location }.fail();
+            }
+        };
+        let index = children.get_index_of(old).context(error::NoFieldSnafu {
+            location,
+            field: old.to_string(),
+        })?;
+
+        let mut child = children[index].clone();
+        child.id = new.to_string();
+        let key = child.inject_id();
+        if let Some(existing) = children.get(&key).filter(|_| key != old) {
+            return error::CollisionSnafu {
+                left_id: existing.id.clone(),
+                left_location: existing.meta.location.clone(),
+                right_id: child.id.clone(),
+                right_location: child.meta.location.clone(),
+            }
+            .fail();
+        }
+
+        children.shift_remove_index(index);
+        children.shift_insert(index, key, child);
+        Ok(())
+    }
+
+    /// Appends a child to a block, section or module under its [`Statement::inject_id`]
+    ///
+    /// The type recorded for the container is updated with the type of the new child.
+    /// Fails for assignments and control statements, or when another child already uses
+    /// the same key.
+    pub fn insert_child(&mut self, child: Statement) -> Result<()> {
+        let location = self.meta.location.clone();
+        let (children, types) = match (&mut self.data, &mut self.type_) {
+            (
+                StatementData::Labeled(_, children),
+                StatementType::Block {
+                    contents: types, ..
+                },
+            )
+            | (StatementData::Group(children), StatementType::Section(types))
+            | (StatementData::Group(children), StatementType::Module(types)) => (children, types),
+            _ => {
+                return error::NotScopeSnafu { location }.fail();
+            }
+        };
+
+        let key = child.inject_id();
+        if let Some(existing) = children.get(&key) {
+            return error::CollisionSnafu {
+                left_id: existing.id.clone(),
+                left_location: existing.meta.location.clone(),
+                right_id: child.id.clone(),
+                right_location: child.meta.location.clone(),
+            }
+            .fail();
         }
+        types.insert(key.clone(), child.type_.clone());
+        children.insert(key, child);
+        Ok(())
     }
+
+    /// Replaces the value of an assignment or control statement
+    ///
+    /// The recorded type follows the new value, so a previous annotation is dropped. The
+    /// type recorded for this statement by its parent is not updated, see
+    /// [`Statement::to_schema`]. Fails with a no value error for statements holding
+    /// children instead of a value.
+    pub fn set_value(&mut self, value: Value) -> Result<()> {
+        let type_ = match &self.type_ {
+            StatementType::Control(_) => StatementType::Control(value.type_of()),
+            StatementType::Assignment(_) => StatementType::Assignment(value.type_of()),
+            StatementType::Append(_) => StatementType::Append(value.type_of()),
+            _ => {
+                return error::NoValueSnafu {
+                    location: self.meta.location.clone(),
+                    field: self.id.clone(),
+                }
+                .fail();
+            }
+        };
+        self.type_ = type_;
+        self.data = StatementData::Single(value);
+        Ok(())
+    }
+
+    /// Removes the statement at a dot separated path and returns it
+    ///
+    /// Paths are interpreted like [`Statement::find_by_path`], the remaining siblings keep
+    /// their order. Returns None when nothing exists at the path.
+    pub fn remove_by_path(&mut self, path: &str) -> Option<Statement> {
+        let parts = split_path(path);
+        let mut parent = self;
+        let mut rest = parts.as_slice();
+        loop {
+            let (key, used) = parent.segment_key(rest)?;
+            if used == rest.len() {
+                return match &mut parent.data {
+                    StatementData::Labeled(_, children) | StatementData::Group(children) => {
+                        children.shift_remove(&key)
+                    }
+                    StatementData::Single(_) => None,
+                };
+            }
+            parent = parent.find_child_mut(&key)?;
+            rest = &rest[used..];
+        }
+    }
+}
+
+/// Splits a dot separated path into its segments, removing the quotes around quoted segments
+///
+/// A segment in brackets, such as the `'a.b'` in `server['a.b'].port`, is split off on its
+/// own and may be followed directly by a dot or another bracketed segment.
+fn split_path(path: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut bracket = false;
+    let mut closed = false;
+    let mut start = 0;
+    for (index, ch) in path.char_indices() {
+        match (quote, ch) {
+            (None, '\'' | '"') if index == start => quote = Some(ch),
+            (Some(open), ch) if ch == open => quote = None,
+            (None, '[') if !bracket => {
+                if index > start {
+                    parts.push(unquote_key(&path[start..index]));
+                }
+                bracket = true;
+                start = index + 1;
+            }
+            (None, ']') if bracket => {
+                parts.push(unquote_key(&path[start..index]));
+                bracket = false;
+                closed = true;
+                start = index + 1;
+                continue;
+            }
+            (None, '.') if !bracket => {
+                if !(closed && index == start) {
+                    parts.push(unquote_key(&path[start..index]));
+                }
+                start = index + 1;
+            }
+            _ => {}
+        }
+        closed = false;
+    }
+    if !(closed && start == path.len()) {
+        parts.push(unquote_key(&path[start..]));
+    }
+    parts
+}
+
+fn unquote_key(key: &str) -> &str {
+    ['\'', '"']
+        .iter()
+        .find_map(|quote| key.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(key)
 }
 
 impl PartialEq for Statement {
@@ -176,7 +465,7 @@ impl PartialEq for Statement {
 
 impl Statement {
     /// Check and convert a value for an assignment with improved error handling
-    fn convert_value(expected_type: &ValueType, value: &Value) -> Result<Value> {
+    pub(crate) fn convert_value(expected_type: &ValueType, value: &Value) -> Result<Value> {
         // If types match exactly, no conversion needed
         if expected_type == &value.type_of() {
             return Ok(value.clone());
@@ -193,6 +482,43 @@ impl Statement {
 
         // Perform the conversion
         let converted_data = match (expected_type, &value.data) {
+            // Any keeps the value untouched
+            (ValueType::Any, data) => data.clone(),
+
+            // Enums only accept the symbols they list
+            (ValueType::Enum(allowed), Data::Symbol(symbol)) => {
+                ensure!(
+                    allowed.contains(symbol),
+                    error::EnumSymbolSnafu {
+                        location: value.meta.location.clone(),
+                        symbol: symbol.clone(),
+                        allowed: allowed.clone(),
+                    }
+                );
+                Data::Symbol(symbol.clone())
+            }
+
+            // Optional types keep null and convert anything else to the inner type
+            (ValueType::Optional(_), Data::Null) => Data::Null,
+            (ValueType::Optional(inner), _) => return Self::convert_value(inner, value),
+
+            // Arrays convert element by element, array[any] keeps every element
+            (ValueType::Array(types), Data::Array(values)) => match types.as_slice() {
+                [ValueType::Any] => Data::Array(values.clone()),
+                _ => Data::Array(
+                    types
+                        .iter()
+                        .zip(values)
+                        .map(|(type_, value)| Self::convert_value(type_, value))
+                        .collect::<Result<_>>()?,
+                ),
+            },
+
+            // Empty tables take on the declared field types
+            (ValueType::Table(_), Data::Table(values)) if values.is_empty() => {
+                Data::Table(IndexMap::new())
+            }
+
             // Unsigned integer conversions
             (ValueType::Unsigned, Data::U8(v)) => Data::Unsigned(*v as u64),
             (ValueType::Unsigned, Data::U16(v)) => Data::Unsigned(*v as u64),
@@ -287,6 +613,25 @@ impl Statement {
         ))
     }
 
+    /// Creates a new append statement whose array is concatenated onto an existing
+    /// array with the same id when modules are merged
+    pub fn new_append(
+        id: &str,
+        type_hint: Option<ValueType>,
+        value: Value,
+        meta: Metadata,
+    ) -> Result<Self> {
+        let expected_type = type_hint.unwrap_or_else(|| value.type_of());
+        let converted_value = Self::convert_value(&expected_type, &value)?;
+
+        Ok(Self::new(
+            id,
+            StatementType::Append(expected_type),
+            StatementData::Single(converted_value),
+            meta,
+        ))
+    }
+
     /// Creates a new block statement
     pub fn new_block(
         id: &str,
@@ -334,6 +679,35 @@ impl Statement {
         Self::new(id, statement_type, StatementData::Group(children), meta)
     }
 
+    /// Builds the structural schema of this statement and all of its children
+    ///
+    /// Unlike `type_`, which is computed when a statement is created, the schema is derived
+    /// from the current children so it also reflects statements merged in afterwards.
+    pub fn to_schema(&self) -> StatementType {
+        let schema_of = |children: &IndexMap<String, Statement>| {
+            children
+                .iter()
+                .map(|(k, v)| (k.clone(), v.to_schema()))
+                .collect()
+        };
+
+        match (&self.type_, &self.data) {
+            (StatementType::Block { .. }, StatementData::Labeled(labels, children)) => {
+                StatementType::Block {
+                    labels: labels.iter().map(|x| x.type_of()).collect(),
+                    contents: schema_of(children),
+                }
+            }
+            (StatementType::Section(_), StatementData::Group(children)) => {
+                StatementType::Section(schema_of(children))
+            }
+            (StatementType::Module(_), StatementData::Group(children)) => {
+                StatementType::Module(schema_of(children))
+            }
+            (type_, _) => type_.clone(),
+        }
+    }
+
     /// Gets the value for assignment statements
     pub fn get_value(&self) -> Option<&Value> {
         match &self.data {
@@ -342,6 +716,21 @@ impl Statement {
         }
     }
 
+    /// Converts the value of an assignment or control statement, see [`Value::get`]
+    ///
+    /// Fails with a no value error for statements holding children instead of a value.
+    pub fn get<T>(&self) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Value, Error = error::Error>,
+    {
+        self.get_value()
+            .context(error::NoValueSnafu {
+                location: self.meta.location.clone(),
+                field: self.id.clone(),
+            })?
+            .get()
+    }
+
     /// Gets the labels and children for block statements
     pub fn get_labeled(&self) -> Option<(&Vec<Value>, &IndexMap<String, Statement>)> {
         match &self.data {
@@ -359,6 +748,21 @@ impl Statement {
         }
     }
 
+    /// Gets the comment written ahead of this statement
+    ///
+    /// Consecutive comments are joined with newlines, one line per comment line.
+    pub fn comment(&self) -> Option<&str> {
+        self.meta.comment.as_deref()
+    }
+
+    /// Replaces or, given None, removes the comment written ahead of this statement
+    ///
+    /// The comment is displayed as `#` line comments, one for each of its lines.
+    pub fn set_comment(&mut self, comment: Option<String>) {
+        self.meta.comment = comment;
+        self.meta.line_comment = true;
+    }
+
     /// Generates the injection ID for this statement (used for macro resolution)
     pub fn inject_id(&self) -> String {
         match &self.data {
@@ -382,11 +786,101 @@ impl Statement {
         }
     }
 
+    /// Recursively resets the locations of this statement, its labels, values and children
+    ///
+    /// Comments and labels are kept, so two trees parsed from sources that only differ in
+    /// layout have identical metadata once stripped. See [`Metadata::strip_locations`].
+    pub fn strip_locations(&mut self) {
+        self.meta.strip_locations();
+        match &mut self.data {
+            StatementData::Single(value) => value.strip_locations(),
+            StatementData::Labeled(labels, children) => {
+                labels.iter_mut().for_each(Value::strip_locations);
+                children.values_mut().for_each(Statement::strip_locations);
+            }
+            StatementData::Group(children) => {
+                children.values_mut().for_each(Statement::strip_locations);
+            }
+        }
+    }
+
+    /// Computes a fingerprint of the meaningful content of this statement
+    ///
+    /// Ids, statement kinds, annotated types, labels and values are hashed in document
+    /// order while uids, locations and comments are ignored, so reformatting a document
+    /// keeps its hash and changing any value changes it. The hash is 64-bit FNV-1a, which
+    /// is stable across runs and platforms and can be persisted for change detection.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::default();
+        self.hash_content(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Builds a structured tree of this statement and its children
+    ///
+    /// The tree holds the id, kind and value of each statement and serializes to a plain
+    /// nested structure, which suits tools that render a document rather than print it.
+    pub fn to_tree(&self) -> TreeNode {
+        let kind = match &self.type_ {
+            StatementType::Control(_) => "Control",
+            StatementType::Assignment(_) => "Assignment",
+            StatementType::Append(_) => "Append",
+            StatementType::Block { .. } => "Block",
+            StatementType::Section(_) => "Section",
+            StatementType::Module(_) => "Module",
+        };
+        let value = self.get_value();
+        TreeNode {
+            id: self.id.clone(),
+            kind: kind.to_string(),
+            value: value.map(Value::to_macro_string),
+            value_type: value.map(|x| x.type_of().to_string()),
+            children: self.children().map(Statement::to_tree).collect(),
+        }
+    }
+
+    fn hash_content(&self, state: &mut ContentHasher) {
+        state.write_str(&self.id);
+        let (kind, type_) = match &self.type_ {
+            StatementType::Control(type_) => ("control", Some(type_)),
+            StatementType::Assignment(type_) => ("assignment", Some(type_)),
+            StatementType::Append(type_) => ("append", Some(type_)),
+            StatementType::Block { .. } => ("block", None),
+            StatementType::Section(_) => ("section", None),
+            StatementType::Module(_) => ("module", None),
+        };
+        state.write_str(kind);
+        if let Some(type_) = type_ {
+            state.write_str(&type_.to_annotation());
+        }
+        match &self.data {
+            StatementData::Single(value) => value.hash_content(state),
+            StatementData::Labeled(labels, children) => {
+                state.write_u64(labels.len() as u64);
+                for label in labels {
+                    label.hash_content(state);
+                }
+                Self::hash_children(children, state);
+            }
+            StatementData::Group(children) => Self::hash_children(children, state),
+        }
+    }
+
+    fn hash_children(children: &IndexMap<String, Statement>, state: &mut ContentHasher) {
+        state.write_u64(children.len() as u64);
+        for (key, child) in children {
+            state.write_str(key);
+            child.hash_content(state);
+        }
+    }
+
     /// Validates the statement structure recursively
     pub fn validate(&self) -> Result<()> {
         // Validate this statement
         match &self.type_ {
-            StatementType::Control(expected) | StatementType::Assignment(expected) => {
+            StatementType::Control(expected)
+            | StatementType::Assignment(expected)
+            | StatementType::Append(expected) => {
                 if let Some(value) = self.get_value() {
                     if !expected.can_assign(&value.type_of()) {
                         return error::ImplicitConvertSnafu {
@@ -407,22 +901,304 @@ impl Statement {
 
         Ok(())
     }
+
+    /// Merges the contents of another statement into this one
+    ///
+    /// Child statements of groups and blocks are merged recursively, children only present
+    /// in `other` are added. An append statement (`key += [..]`) in `other` concatenates its
+    /// array onto the existing array, see [`Statement::append`]. Any other overlap is a
+    /// collision, when collisions are allowed the statement from `other` replaces the
+    /// existing one, otherwise an error is returned.
+    pub fn merge(&mut self, other: &Statement, allow_collisions: bool) -> Result<()> {
+        self.merge_at(other, "", allow_collisions, None)
+    }
+
+    /// Merges another statement into this one, recording collisions when requested
+    ///
+    /// `path` is the dot separated path of the statements being merged. When `collisions`
+    /// is given and collisions are not allowed, collisions are recorded there instead of
+    /// failing the merge.
+    pub(crate) fn merge_at(
+        &mut self,
+        other: &Statement,
+        path: &str,
+        allow_collisions: bool,
+        mut collisions: Option<&mut Vec<Collision>>,
+    ) -> Result<()> {
+        match &other.data {
+            StatementData::Group(right_stmts) | StatementData::Labeled(_, right_stmts) => {
+                match &mut self.data {
+                    StatementData::Group(left_stmts) | StatementData::Labeled(_, left_stmts) => {
+                        // Pre-allocate capacity for better performance
+                        let additional_capacity =
+                            right_stmts.len().saturating_sub(left_stmts.len());
+                        if additional_capacity > 0 {
+                            left_stmts.reserve(additional_capacity);
+                        }
+
+                        // Merge each child statement
+                        for (key, value) in right_stmts {
+                            if let Some(target) = left_stmts.get_mut(key) {
+                                // Recursive merge for existing keys
+                                target.merge_at(
+                                    value,
+                                    &Self::child_path(path, key),
+                                    allow_collisions,
+                                    collisions.as_deref_mut(),
+                                )?;
+                            } else {
+                                // Simple insert for new keys
+                                left_stmts.insert(key.clone(), value.clone());
+                            }
+                        }
+                    }
+                    _ => {
+                        // Type mismatch - replace if collisions are allowed
+                        self.collide(other, path, allow_collisions, collisions)?;
+                    }
+                }
+            }
+            StatementData::Single(_) if matches!(other.type_, StatementType::Append(_)) => {
+                // Append intent - concatenate onto the existing array
+                self.append(other)?;
+            }
+            StatementData::Single(_) => {
+                // Value collision - replace if allowed
+                self.collide(other, path, allow_collisions, collisions)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Concatenates the array held by another statement onto the array held by this one
+    ///
+    /// This statement keeps its kind and its recorded type while that type accepts the
+    /// combined array, an inferred type follows the combined array instead. Fails with an
+    /// assign error when either statement does not hold an array or when an annotated type
+    /// rejects the combined array.
+    pub fn append(&mut self, other: &Statement) -> Result<()> {
+        let fail = || {
+            error::AssignSnafu {
+                location: other.meta.location.clone(),
+                left: self
+                    .get_value()
+                    .map(Value::type_of)
+                    .unwrap_or(ValueType::Null),
+                right: other
+                    .get_value()
+                    .map(Value::type_of)
+                    .unwrap_or(ValueType::Null),
+            }
+            .fail()
+        };
+
+        let (Some(left), Some(right), Some(type_)) =
+            (self.get_value(), other.get_value(), self.type_.value_type())
+        else {
+            return fail();
+        };
+        let (Data::Array(left_values), Data::Array(right_values)) = (&left.data, &right.data)
+        else {
+            return fail();
+        };
+
+        let mut values = left_values.clone();
+        values.extend(right_values.iter().cloned());
+        let combined = Value {
+            uid: left.uid,
+            data: Data::Array(values),
+            meta: left.meta.clone(),
+        };
+        let combined_type = combined.type_of();
+        let type_ = if type_.can_assign(&combined_type) {
+            type_.clone()
+        } else if *type_ == left.type_of() {
+            combined_type
+        } else {
+            return fail();
+        };
+
+        self.type_ = match self.type_ {
+            StatementType::Control(_) => StatementType::Control(type_),
+            StatementType::Append(_) => StatementType::Append(type_),
+            _ => StatementType::Assignment(type_),
+        };
+        self.data = StatementData::Single(combined);
+        Ok(())
+    }
+
+    /// Joins a child key onto the dot separated path of its parent
+    pub(crate) fn child_path(path: &str, key: &str) -> String {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    }
+
+    /// Resolves a collision with another statement according to the collision policy
+    ///
+    /// The other statement replaces this one when collisions are allowed. Otherwise
+    /// the collision is recorded if collisions are being collected, or returned as an error.
+    fn collide(
+        &mut self,
+        other: &Statement,
+        path: &str,
+        allow_collisions: bool,
+        collisions: Option<&mut Vec<Collision>>,
+    ) -> Result<()> {
+        if allow_collisions {
+            *self = other.clone();
+            return Ok(());
+        }
+
+        match collisions {
+            Some(collisions) => {
+                collisions.push(Collision {
+                    path: path.to_string(),
+                    left_location: self.meta.location.clone(),
+                    right_location: other.meta.location.clone(),
+                });
+                Ok(())
+            }
+            None => error::CollisionSnafu {
+                left_id: self.id.clone(),
+                left_location: self.meta.location.clone(),
+                right_id: other.id.clone(),
+                right_location: other.meta.location.clone(),
+            }
+            .fail(),
+        }
+    }
+}
+
+/// Structured view of a statement tree, see [`Statement::to_tree`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TreeNode {
+    /// Identifier of the statement
+    pub id: String,
+
+    /// Kind of statement such as `Assignment`, `Block` or `Section`
+    pub kind: String,
+
+    /// Value of assignments and control statements rendered as a macro string
+    pub value: Option<String>,
+
+    /// Type of the value of assignments and control statements
+    pub value_type: Option<String>,
+
+    /// Child statements in document order
+    pub children: Vec<TreeNode>,
+}
+
+/// A name collision found while merging statements
+///
+/// Collisions are only recorded when `LoaderConfig::collect_collisions` is enabled,
+/// otherwise the first collision fails the merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collision {
+    /// Dot separated path of the colliding statement
+    pub path: String,
+
+    /// Location of the statement that was already loaded
+    ///
+    /// Statements keep the location they were parsed at when merged, so this names the
+    /// file that defined the statement even after several files have been merged.
+    pub left_location: Location,
+
+    /// Location of the statement that collided with it
+    pub right_location: Location,
+}
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (defined at {}, redefined at {})",
+            self.path, self.left_location, self.right_location
+        )
+    }
+}
+
+/// 64-bit FNV-1a hasher used for content fingerprints
+///
+/// Only fixed width little-endian integers and length prefixed strings are written, so a
+/// fingerprint does not depend on the platform that computed it.
+pub(crate) struct ContentHasher(u64);
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl ContentHasher {
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    pub(crate) fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write(value.as_bytes());
+    }
+
+    pub(crate) fn write_option_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.write_u64(1);
+                self.write_str(value);
+            }
+            None => self.write_u64(0),
+        }
+    }
 }
 
 impl fmt::Display for Statement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Write comment if present
         if let Some(comment) = self.meta.comment.as_ref() {
-            writeln!(f, "/*\n{}\n*/", comment)?;
+            if self.meta.line_comment {
+                for line in comment.lines() {
+                    writeln!(f, "# {}", line)?;
+                }
+            } else {
+                writeln!(f, "/*\n{}\n*/", comment)?;
+            }
         }
 
         match &self.type_ {
-            StatementType::Control(type_) => {
-                write!(f, "${}: {} = {}", self.id, type_, self.get_value().unwrap())
-            }
-            StatementType::Assignment(type_) => {
-                write!(f, "{}: {} = {}", self.id, type_, self.get_value().unwrap())
-            }
+            StatementType::Control(type_) => write!(
+                f,
+                "${}: {} = {}",
+                self.id,
+                type_.to_annotation(),
+                self.get_value().unwrap()
+            ),
+            StatementType::Assignment(type_) => write!(
+                f,
+                "{}: {} = {}",
+                quote_key(&self.id),
+                type_.to_annotation(),
+                self.get_value().unwrap()
+            ),
+            StatementType::Append(type_) => write!(
+                f,
+                "{}: {} += {}",
+                quote_key(&self.id),
+                type_.to_annotation(),
+                self.get_value().unwrap()
+            ),
             StatementType::Block { .. } => {
                 let (labels, body) = self.get_labeled().unwrap();
                 let labels_str = labels
@@ -431,7 +1207,7 @@ impl fmt::Display for Statement {
                     .collect::<Vec<_>>()
                     .join(" ");
 
-                writeln!(f, "{} {} {{", self.id, labels_str)?;
+                writeln!(f, "{} {} {{", quote_key(&self.id), labels_str)?;
                 for child in body.values() {
                     writeln!(f, "  {}", child)?;
                 }
@@ -439,7 +1215,7 @@ impl fmt::Display for Statement {
             }
             StatementType::Section(_) => {
                 let body = self.get_grouped().unwrap();
-                writeln!(f, "[{}]", self.id)?;
+                writeln!(f, "[{}]", quote_key(&self.id))?;
                 for child in body.values() {
                     writeln!(f, "{}", child)?;
                 }
@@ -476,6 +1252,423 @@ mod tests {
     use super::*;
     use crate::ast::types::Location;
 
+    #[test]
+    fn test_statement_rename_child() {
+        let mut module = crate::from_str(
+            "first = 1\n\
+             second = 2\n\
+             third = 3\n\
+             [server]\n\
+             listener 'http' { port = 80 }\n",
+        )
+        .unwrap();
+        let keys = |statement: &Statement| {
+            statement
+                .get_grouped()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        module.rename_child("second", "middle").unwrap();
+        assert_eq!(keys(&module), vec!["first", "middle", "third", "server"]);
+        let middle = module.find_child("middle").unwrap();
+        assert_eq!(middle.id, "middle");
+        assert_eq!(middle.get_value().unwrap().as_int(), Some(&2));
+        assert!(module.find_child("second").is_none());
+
+        // Blocks are keyed by their id and labels
+        let server = module.find_child_mut("server").unwrap();
+        server.rename_child("listener.http", "socket").unwrap();
+        assert_eq!(keys(server), vec!["socket.http"]);
+        assert!(server.find_block("socket", &["http"]).is_some());
+
+        // Renaming to the same key is a no-op
+        module.rename_child("first", "first").unwrap();
+        assert_eq!(keys(&module), vec!["first", "middle", "third", "server"]);
+
+        assert!(matches!(
+            module.rename_child("first", "third"),
+            Err(error::Error::Collision { .. })
+        ));
+        assert!(matches!(
+            module.rename_child("missing", "other"),
+            Err(error::Error::NoField { .. })
+        ));
+        assert_eq!(keys(&module), vec!["first", "middle", "third", "server"]);
+    }
+
+    #[test]
+    fn test_statement_insert_child() {
+        let mut module = crate::from_str("[server]\nhost = 'localhost'\n").unwrap();
+        let server = module.find_child_mut("server").unwrap();
+        let port = Statement::new_assign(
+            "port",
+            None,
+            Value::new_int(80, Metadata::default()),
+            Metadata::default(),
+        )
+        .unwrap();
+        server.insert_child(port.clone()).unwrap();
+        let listener = Statement::new_block(
+            "listener",
+            vec![Value::new_string("http".to_string(), Metadata::default())],
+            IndexMap::new(),
+            Metadata::default(),
+        );
+        server.insert_child(listener).unwrap();
+
+        let keys = server.children().map(|x| x.inject_id()).collect::<Vec<_>>();
+        assert_eq!(keys, ["host", "port", "listener.http"]);
+        assert_eq!(server.type_, server.to_schema());
+        assert_eq!(
+            module
+                .find_by_path("server.port")
+                .unwrap()
+                .get::<i64>()
+                .unwrap(),
+            80
+        );
+
+        // Keys are unique and assignments hold no children
+        let server = module.find_child_mut("server").unwrap();
+        assert!(matches!(
+            server.insert_child(port.clone()),
+            Err(error::Error::Collision { .. })
+        ));
+        let host = server.find_child_mut("host").unwrap();
+        assert!(matches!(
+            host.insert_child(port),
+            Err(error::Error::NotScope { .. })
+        ));
+    }
+
+    #[test]
+    fn test_statement_set_value() {
+        let mut module = crate::from_str("port: i64 = 80i64\n$env = 'dev'\n[server]\n").unwrap();
+        let port = module.find_child_mut("port").unwrap();
+        port.set_value(Value::new_string("http".to_string(), Metadata::default()))
+            .unwrap();
+        assert_eq!(port.type_, StatementType::Assignment(ValueType::String));
+        assert_eq!(port.to_string(), "port: string = 'http'");
+
+        let env = module.find_child_mut("env").unwrap();
+        env.set_value(Value::new_bool(true, Metadata::default()))
+            .unwrap();
+        assert_eq!(env.type_, StatementType::Control(ValueType::Bool));
+
+        let server = module.find_child_mut("server").unwrap();
+        assert!(matches!(
+            server.set_value(Value::new_null(Metadata::default())),
+            Err(error::Error::NoValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_statement_remove_by_path() {
+        let mut module = crate::from_str(
+            "name = 'app'\n\
+             [server]\n\
+             host = 'localhost'\n\
+             port = 8080\n\
+             workers = 4\n",
+        )
+        .unwrap();
+
+        let removed = module.remove_by_path("server.port").unwrap();
+        assert_eq!(removed.id, "port");
+        assert_eq!(removed.get_value().unwrap().as_int(), Some(&8080));
+        let server = module.find_by_path("server").unwrap();
+        assert_eq!(
+            server.get_grouped().unwrap().keys().collect::<Vec<_>>(),
+            vec!["host", "workers"]
+        );
+
+        assert!(module.remove_by_path("server.port").is_none());
+        assert!(module.remove_by_path("missing.port").is_none());
+        assert!(module.remove_by_path("name.inner").is_none());
+
+        assert!(module.remove_by_path("server").is_some());
+        assert!(module.find_by_path("server.host").is_none());
+        assert!(module.find_by_path("name").is_some());
+    }
+
+    #[test]
+    fn test_statement_comments() {
+        // Line comments are displayed as line comments again
+        let module = crate::from_str("# a\n# b\nx = 1\n").unwrap();
+        assert_eq!(module.comment(), Some("a\nb"));
+        assert_eq!(module.to_string(), "# a\n# b\nx: int = 1");
+
+        let mut module =
+            crate::from_str("w = 0\n# a\n# b\nx = 1\n/* c */\ny = 2\n# d\n/* e */\nz = 3\n")
+                .unwrap();
+        let x = module.find_by_path("x").unwrap();
+        assert_eq!(x.comment(), Some("a\nb"));
+        assert_eq!(x.to_string(), "# a\n# b\nx: int = 1");
+        let y = module.find_by_path("y").unwrap();
+        assert_eq!(y.comment(), Some("c"));
+        assert_eq!(y.to_string(), "/*\nc\n*/\ny: int = 2");
+        // Mixed comments fall back to a block comment
+        let z = module.find_by_path("z").unwrap();
+        assert_eq!(z.to_string(), "/*\nd\ne\n*/\nz: int = 3");
+
+        let displayed = module.to_string();
+        let reparsed = crate::from_str(&displayed).unwrap();
+        assert_eq!(reparsed.to_string(), displayed);
+
+        let y = module.find_child_mut("y").unwrap();
+        y.set_comment(Some("replaced\nagain".to_string()));
+        assert_eq!(y.to_string(), "# replaced\n# again\ny: int = 2");
+        y.set_comment(None);
+        assert_eq!(y.comment(), None);
+        assert_eq!(y.to_string(), "y: int = 2");
+    }
+
+    #[test]
+    fn test_statement_leaves() {
+        let module = crate::from_str(
+            "name = 'app'\n\
+             tags = ['web', 'api']\n\
+             empty = []\n\
+             listener 'http' 80 {\n  hosts = [{ name = 'a' }, { name = 'b' }]\n}\n\
+             [server]\n\
+             limits = { cpu = 2, memory = { max = '1G' } }\n\
+             region 'eu' {\n  zone = 'west'\n}\n\
+             [client]\n\
+             retries = 3\n",
+        )
+        .unwrap();
+        let leaves = module
+            .leaves()
+            .map(|(path, value)| (path, value.to_macro_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            leaves,
+            [
+                ("name", "app"),
+                ("tags.0", "web"),
+                ("tags.1", "api"),
+                ("empty", ""),
+                ("listener.http.80.hosts.0.name", "a"),
+                ("listener.http.80.hosts.1.name", "b"),
+                ("server.limits.cpu", "2"),
+                ("server.limits.memory.max", "1G"),
+                ("server.region.eu.zone", "west"),
+                ("client.retries", "3"),
+            ]
+            .map(|(path, value)| (path.to_string(), value.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_statement_symbols() {
+        let module = crate::from_str(
+            "level = :info\n\
+             listener 'http' {\n  mode = :tcp/stream\n}\n\
+             [server]\n\
+             flags = [:fast, { next = :server/level }]\n\
+             fallback = :info\n",
+        )
+        .unwrap();
+        let symbols = module
+            .symbols()
+            .into_iter()
+            .map(|(symbol, location)| (symbol, location.line, location.column))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            symbols,
+            [
+                ("info", 0, 8),
+                ("tcp/stream", 2, 9),
+                ("fast", 5, 9),
+                ("server/level", 5, 25),
+                ("info", 6, 11),
+            ]
+            .map(|(symbol, line, column)| (symbol.to_string(), line, column))
+        );
+        assert!(
+            crate::from_str("name = 'app'\n")
+                .unwrap()
+                .symbols()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_statement_get() {
+        let module =
+            crate::from_str("port = 8080u32\nname = 'app'\n[server]\nhost = 'a'\n").unwrap();
+        let get = |path: &str| module.find_by_path(path).unwrap();
+        assert_eq!(get("port").get::<u32>().unwrap(), 8080);
+        assert_eq!(get("name").get::<String>().unwrap(), "app");
+        assert!(matches!(
+            get("name").get::<u32>(),
+            Err(error::Error::ImplicitConvert { .. })
+        ));
+        assert!(matches!(
+            get("server").get::<String>(),
+            Err(error::Error::NoValue { field, .. }) if field == "server"
+        ));
+    }
+
+    #[test]
+    fn test_statement_to_tree() {
+        let module = crate::from_str(
+            "name = 'app'\n\
+             [server]\n\
+             port = 8080\n\
+             listener 'http' { tls = false }\n",
+        )
+        .unwrap();
+        let tree = module.to_tree();
+        assert_eq!(tree.kind, "Module");
+        assert_eq!(tree.value, None);
+
+        let shape = |node: &TreeNode| {
+            node.children
+                .iter()
+                .map(|x| (x.id.clone(), x.kind.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            shape(&tree),
+            vec![
+                ("name".to_string(), "Assignment".to_string()),
+                ("server".to_string(), "Section".to_string()),
+            ]
+        );
+        assert_eq!(tree.children[0].value.as_deref(), Some("app"));
+        assert_eq!(tree.children[0].value_type.as_deref(), Some("string"));
+        assert!(tree.children[0].children.is_empty());
+
+        let server = &tree.children[1];
+        assert_eq!(
+            shape(server),
+            vec![
+                ("port".to_string(), "Assignment".to_string()),
+                ("listener".to_string(), "Block".to_string()),
+            ]
+        );
+        let listener = &server.children[1];
+        assert_eq!(listener.value, None);
+        assert_eq!(
+            shape(listener),
+            vec![("tls".to_string(), "Assignment".to_string())]
+        );
+        assert_eq!(listener.children[0].value.as_deref(), Some("false"));
+    }
+
+    #[test]
+    fn test_statement_content_hash() {
+        let original = crate::from_str(
+            "name = 'app'\n\
+             [server]\n\
+             port = 8080\n\
+             hosts = ['alpha', 'beta']\n\
+             listener 'http' { tls = false }\n",
+        )
+        .unwrap();
+        let reformatted = crate::from_str(
+            "# The application name\n\
+             name    =   'app'\n\n\
+             [server]\n\
+             /* Port to listen on */\n\
+             port = 8080\n\
+             hosts = [\n  'alpha',\n  'beta',\n]\n\
+             listener 'http' {\n  tls = false\n}\n",
+        )
+        .unwrap();
+        assert_eq!(original.content_hash(), reformatted.content_hash());
+        assert_eq!(original.content_hash(), original.clone().content_hash());
+
+        // The fingerprint is persisted, so it must not change between platforms or releases
+        let pinned = crate::from_str("name = 'app'\nlistener 'http' { ports = [80] }\n").unwrap();
+        assert_eq!(pinned.content_hash(), 0x32aa_1017_0d34_91d9);
+
+        for changed in [
+            "name = 'app'\n[server]\nport = 8081\nhosts = ['alpha', 'beta']\nlistener 'http' { tls = false }\n",
+            "name = 'app'\n[server]\nport = 8080\nhosts = ['alpha']\nlistener 'http' { tls = false }\n",
+            "name = 'app'\n[server]\nport = 8080\nhosts = ['alpha', 'beta']\nlistener 'https' { tls = false }\n",
+            "name = 'app'\n[server]\nport: i64 = 8080\nhosts = ['alpha', 'beta']\nlistener 'http' { tls = false }\n",
+            "name = 'app'\n[server]\nport = '8080'\nhosts = ['alpha', 'beta']\nlistener 'http' { tls = false }\n",
+        ] {
+            let changed = crate::from_str(changed).unwrap();
+            assert_ne!(original.content_hash(), changed.content_hash());
+        }
+    }
+
+    #[test]
+    fn test_statement_strip_locations() {
+        fn value_metadata(value: &Value, all: &mut Vec<Metadata>) {
+            all.push(value.meta.clone());
+            match &value.data {
+                Data::Array(values) => values.iter().for_each(|x| value_metadata(x, all)),
+                Data::Table(values) => values.values().for_each(|x| value_metadata(x, all)),
+                _ => {}
+            }
+        }
+        fn metadata(statement: &Statement, all: &mut Vec<Metadata>) {
+            all.push(statement.meta.clone());
+            match &statement.data {
+                StatementData::Single(value) => value_metadata(value, all),
+                StatementData::Labeled(labels, children) => {
+                    labels.iter().for_each(|x| value_metadata(x, all));
+                    children.values().for_each(|x| metadata(x, all));
+                }
+                StatementData::Group(children) => children.values().for_each(|x| metadata(x, all)),
+            }
+        }
+        let collect = |statement: &Statement| {
+            let mut all = Vec::new();
+            metadata(statement, &mut all);
+            all
+        };
+
+        let mut compact = crate::from_str(
+            "name = 'app'\n\
+             # Listening port\n\
+             port = !Port 80\n\
+             limits = { cpu = 2, tags = ['a'] }\n\
+             server 'web' { tls = true }\n",
+        )
+        .unwrap();
+        let mut indented = crate::from_str(
+            "name='app'\n\n# Listening port\n\
+             port   =   !Port 80\n\n\
+             limits = {\n    cpu = 2,\n    tags = [ 'a' ]\n}\n\
+             server 'web' {\n    tls = true\n}\n",
+        )
+        .unwrap();
+        assert_eq!(compact, indented);
+        assert_ne!(collect(&compact), collect(&indented));
+
+        compact.strip_locations();
+        indented.strip_locations();
+        assert_eq!(collect(&compact), collect(&indented));
+        assert!(
+            collect(&compact)
+                .iter()
+                .all(|meta| meta.location == Location::default())
+        );
+        let port = compact.find_by_path("port").unwrap();
+        assert_eq!(port.meta.comment.as_deref(), Some("Listening port"));
+        assert_eq!(
+            port.get_value().unwrap().meta.label.as_deref(),
+            Some("Port")
+        );
+
+        // A differing comment is still noticed after stripping
+        let mut other = crate::from_str(
+            "name = 'app'\n# Public port\nport = !Port 80\nlimits = { cpu = 2, tags = ['a'] }\n\
+             server 'web' { tls = true }\n",
+        )
+        .unwrap();
+        other.strip_locations();
+        assert_ne!(collect(&compact), collect(&other));
+    }
+
     #[test]
     fn test_statement_creation() {
         let meta = Metadata::new(Location::new(0, 0));
@@ -524,6 +1717,84 @@ mod tests {
         assert!(root.find_by_path("nonexistent").is_none());
     }
 
+    #[test]
+    fn test_statement_quoted_keys() {
+        let module = crate::from_str(
+            "'_internal' = true\n\
+             [\"2fa\"]\n\
+             \"3rd-party\" = 'enabled'\n\
+             'a.b' = 1\n",
+        )
+        .unwrap();
+
+        let check = |module: &Statement| {
+            let internal = module.find_by_path("_internal").unwrap();
+            assert_eq!(internal.get_value().unwrap().as_bool(), Some(&true));
+            let party = module.find_by_path("2fa.3rd-party").unwrap();
+            assert_eq!(
+                party.get_value().unwrap().as_string(),
+                Some(&"enabled".to_string())
+            );
+            // Keys holding a dot are reached by quoting them in the path
+            assert!(module.find_by_path("2fa.a.b").is_none());
+            let dotted = module.find_by_path("2fa.'a.b'").unwrap();
+            assert_eq!(dotted.get_value().unwrap().as_int(), Some(&1));
+            assert!(module.find_by_path("\"2fa\".\"a.b\"").is_some());
+        };
+        check(&module);
+
+        let displayed = module.to_string();
+        let reparsed = crate::from_str(&displayed).unwrap();
+        check(&reparsed);
+        assert_eq!(reparsed, module, "{displayed}");
+    }
+
+    #[test]
+    fn test_statement_find_by_segments() {
+        let mut module = crate::from_str(
+            "server 'a.b' {\n  port = 80\n}\n\
+             listener 'http' 8080 {\n  tls = false\n}\n",
+        )
+        .unwrap();
+
+        // Labels holding a dot are only reachable without splitting them
+        let port = module.find_by_segments(&["server", "a.b", "port"]).unwrap();
+        assert_eq!(port.get_value().unwrap().as_int(), Some(&80));
+        assert!(module.find_by_path("server.a.b.port").is_none());
+        assert!(
+            module
+                .find_by_segments(&["server", "a", "b", "port"])
+                .is_none()
+        );
+        for path in [
+            "server['a.b'].port",
+            "server[\"a.b\"].port",
+            "'server.a.b'.port",
+        ] {
+            assert!(module.find_by_path(path).is_some(), "missing {path}");
+        }
+
+        // Labels without a dot can be written either way
+        for path in ["listener.http.8080.tls", "listener[http][8080].tls"] {
+            assert!(module.find_by_path(path).is_some(), "missing {path}");
+        }
+        assert!(module.find_by_path("listener.http.tls").is_none());
+
+        // Canonical paths resolve back to their statements
+        for child in module.children() {
+            let path = child.canonical_id_path();
+            assert_eq!(module.find_by_path(&path), Some(child), "{path}");
+        }
+        let server = module.find_by_segments(&["server", "a.b"]).unwrap();
+        assert_eq!(server.canonical_id_path(), "server['a.b']");
+        assert_eq!(port.canonical_id_path(), "port");
+
+        let removed = module.remove_by_path("server['a.b'].port").unwrap();
+        assert_eq!(removed.id, "port");
+        assert!(module.remove_by_path("listener[http][8080]").is_some());
+        assert_eq!(module.children().count(), 1);
+    }
+
     #[test]
     fn test_type_conversion() {
         let meta = Metadata::new(Location::new(0, 0));
@@ -537,4 +1808,128 @@ mod tests {
             assert_eq!(converted_value.as_u64(), Some(&42u64));
         }
     }
+
+    #[test]
+    fn test_statement_sections_and_blocks() {
+        let module = crate::from_str(
+            "name = 'app'\n\
+             service 'web' 80 {\n  replicas = 2\n}\n\
+             service 'worker' {\n  replicas = 1\n}\n\
+             [database]\nhost = 'localhost'\n\
+             [cache]\nsize = 64\n",
+        )
+        .unwrap();
+
+        let sections: Vec<&str> = module.sections().map(|x| x.id.as_str()).collect();
+        assert_eq!(sections, vec!["database", "cache"]);
+
+        let blocks: Vec<(&str, usize)> = module
+            .blocks()
+            .map(|(id, labels, _)| (id, labels.len()))
+            .collect();
+        assert_eq!(blocks, vec![("service", 2), ("service", 1)]);
+
+        let (_, labels, block) = module.blocks().next().unwrap();
+        assert_eq!(labels[0].as_string(), Some(&"web".to_string()));
+        assert!(block.find_child("replicas").is_some());
+
+        // Sections and assignments have no nested sections or blocks
+        let database = module.find_child("database").unwrap();
+        assert_eq!(database.sections().count(), 0);
+        assert_eq!(database.blocks().count(), 0);
+    }
+
+    #[test]
+    fn test_statement_find_block() {
+        let module = crate::from_str(
+            "server 'web' {\n  port = 80\n}\n\
+             server 'api' {\n  port = 8080\n}\n\
+             listener 'api' 443 {\n  tls = true\n}\n",
+        )
+        .unwrap();
+
+        let port = |block: &Statement| {
+            *block
+                .find_child("port")
+                .unwrap()
+                .get_value()
+                .unwrap()
+                .as_int()
+                .unwrap()
+        };
+        assert_eq!(port(module.find_block("server", &["web"]).unwrap()), 80);
+        assert_eq!(port(module.find_block("server", &["api"]).unwrap()), 8080);
+        assert!(module.find_block("server", &["db"]).is_none());
+        assert!(module.find_block("server", &[]).is_none());
+
+        let listener = module.find_block("listener", &["api", "443"]).unwrap();
+        assert!(listener.find_child("tls").is_some());
+        assert!(module.find_block("listener", &["api"]).is_none());
+    }
+
+    #[test]
+    fn test_statement_merge_groups() {
+        let mut base = crate::from_str("[server]\nhost = 'localhost'\n").unwrap();
+        let other = crate::from_str("[server]\nport = 8080\n[client]\nretries = 3\n").unwrap();
+
+        base.merge(&other, false).unwrap();
+
+        assert!(base.find_by_path("server.host").is_some());
+        assert!(base.find_by_path("server.port").is_some());
+        assert!(base.find_by_path("client.retries").is_some());
+    }
+
+    #[test]
+    fn test_statement_merge_override() {
+        let mut base = crate::from_str("[server]\nport = 80\n").unwrap();
+        let other = crate::from_str("[server]\nport = 8080\n").unwrap();
+
+        base.merge(&other, true).unwrap();
+
+        let port = base.find_by_path("server.port").unwrap();
+        assert_eq!(port.get_value().unwrap().as_int(), Some(&8080));
+    }
+
+    #[test]
+    fn test_statement_merge_collision() {
+        let mut base = crate::from_str("[server]\nport = 80\n").unwrap();
+        let other = crate::from_str("[server]\nport = 8080\n").unwrap();
+
+        let result = base.merge(&other, false);
+        assert!(matches!(result, Err(error::Error::Collision { .. })));
+    }
+
+    #[test]
+    fn test_statement_schema_paths() {
+        let module = crate::from_str(
+            "$schema = 1.0.0\n\
+             name = 'app'\n\
+             [server]\nhost = 'localhost'\nport = 8080u16\n\
+             [limits]\nratio = 0.5\nflags = [true, false]\n",
+        )
+        .unwrap();
+
+        let schema = module.to_schema();
+        assert!(matches!(schema, StatementType::Module(_)));
+
+        let paths = schema.flatten_paths();
+        let expected: Vec<(&str, ValueType)> = vec![
+            ("schema", ValueType::Version),
+            ("name", ValueType::String),
+            ("server.host", ValueType::String),
+            ("server.port", ValueType::U16),
+            ("limits.ratio", ValueType::Float),
+            (
+                "limits.flags",
+                ValueType::Array(vec![ValueType::Bool, ValueType::Bool]),
+            ),
+        ];
+        assert_eq!(
+            paths
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.clone()))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
 }
@@ -1,9 +1,16 @@
+use crate::syn::Token;
 use indexmap::IndexMap;
+use logos::Logos;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::Range;
 
 /// Defines the type of a given value
+///
+/// Some variants only exist with their feature enabled, so matches outside of this crate
+/// need a wildcard arm.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[non_exhaustive]
 pub enum ValueType {
     /// String values
     String,
@@ -52,6 +59,26 @@ pub enum ValueType {
     /// Semantic Version Requirement
     Require,
 
+    /// RFC 3339 date and time with a fixed offset
+    #[cfg(feature = "datetime")]
+    DateTime,
+
+    /// Exact fixed-point decimal number
+    #[cfg(feature = "decimal")]
+    Decimal,
+
+    /// IPv4 or IPv6 address
+    #[cfg(feature = "net")]
+    Ip,
+
+    /// Network in CIDR notation
+    #[cfg(feature = "net")]
+    Cidr,
+
+    /// Regular expression
+    #[cfg(feature = "regex")]
+    Regex,
+
     /// Macro string
     Macro,
 
@@ -64,11 +91,20 @@ pub enum ValueType {
     /// Null
     Null,
 
+    /// Any type, accepts values of every other type without conversion
+    Any,
+
     /// Array
     Array(Vec<Self>),
 
     /// Table
     Table(IndexMap<String, Self>),
+
+    /// One of a fixed set of symbols, written as `enum{:fast, :safe}`
+    Enum(Vec<String>),
+
+    /// Optional value of the inner type, written with a trailing `?`, accepts the inner type or null
+    Optional(Box<Self>),
 }
 
 impl ValueType {
@@ -92,6 +128,34 @@ impl ValueType {
             // Exact matches are always valid
             (left, right) if left == right => true,
 
+            // Any accepts every type as-is
+            (Any, _) => true,
+
+            // Optional types accept null or anything the inner type accepts
+            (Optional(_), Null) => true,
+            (Optional(left), Optional(right)) => left.can_assign(right),
+            (Optional(left), right) => left.can_assign(right),
+
+            // Empty literals carry no element or field types of their own, so
+            // they take on whatever the annotation declares
+            (Array(_), Array(right)) if right.is_empty() => true,
+            (Table(_), Table(right)) if right.is_empty() => true,
+
+            // A single any element type permits mixed arrays, otherwise
+            // arrays are checked element by element
+            (Array(left), Array(right)) => match left.as_slice() {
+                [Any] => true,
+                _ => {
+                    left.len() == right.len()
+                        && left.iter().zip(right).all(|(l, r)| l.can_assign(r))
+                }
+            },
+
+            // Enums accept symbols, the symbol itself is checked when a value is assigned,
+            // and narrower enums whose symbols are all allowed
+            (Enum(_), Symbol) => true,
+            (Enum(left), Enum(right)) => right.iter().all(|symbol| left.contains(symbol)),
+
             // String types are only compatible with other strings
             (String, String) => true,
 
@@ -114,6 +178,71 @@ impl ValueType {
         }
     }
 
+    /// Finds the narrowest type that every one of the given types fits in
+    ///
+    /// A type fits when it can be assigned with [`ValueType::can_assign`] or widened
+    /// without loss: sized integers widen to larger integers of the same signedness,
+    /// unsigned integers to larger signed integers and `f32` to `f64`. Null types make the
+    /// result optional. Returns `None` for an empty slice or when the types have no common
+    /// type other than `any`, unless `any` is one of them.
+    pub fn common_supertype(types: &[ValueType]) -> Option<ValueType> {
+        use ValueType::*;
+
+        let mut optional = false;
+        let mut inner = Vec::with_capacity(types.len());
+        for type_ in types {
+            match type_ {
+                Null => optional = true,
+                Optional(type_) => {
+                    optional = true;
+                    inner.push(type_.as_ref().clone());
+                }
+                other => inner.push(other.clone()),
+            }
+        }
+        if inner.is_empty() {
+            return (!types.is_empty()).then_some(Null);
+        }
+
+        let widened = [
+            I16, I32, I64, I128, Signed, U16, U32, U64, U128, Unsigned, F64, Float,
+        ];
+        let candidates: Vec<&ValueType> = inner
+            .iter()
+            .chain(widened.iter())
+            .filter(|candidate| inner.iter().all(|x| candidate.widens_from(x)))
+            .collect();
+        let narrowest = candidates
+            .iter()
+            .find(|candidate| candidates.iter().all(|x| x.widens_from(candidate)))?;
+
+        Some(match narrowest {
+            Any => Any,
+            narrowest if optional => Optional(Box::new((*narrowest).clone())),
+            narrowest => (*narrowest).clone(),
+        })
+    }
+
+    /// Checks if the right type can be assigned to the left or widened into it without loss
+    fn widens_from(&self, right: &Self) -> bool {
+        use ValueType::*;
+
+        self.can_assign(right)
+            || matches!(
+                (self, right),
+                (I16, I8 | U8)
+                    | (I32, I8 | I16 | U8 | U16)
+                    | (
+                        I128,
+                        Signed | I8 | I16 | I32 | I64 | Unsigned | U8 | U16 | U32 | U64
+                    )
+                    | (U16, U8)
+                    | (U32, U8 | U16)
+                    | (U128, Unsigned | U8 | U16 | U32 | U64)
+                    | (F64, F32)
+            )
+    }
+
     /// Determines if this type is a numeric type
     pub const fn is_numeric(&self) -> bool {
         matches!(
@@ -185,7 +314,39 @@ impl ValueType {
         }
     }
 
-    /// Returns the category of this type for grouping purposes
+    /// Writes this type in the annotation syntax accepted by the parser
+    ///
+    /// Unlike `Display`, arrays and tables are written with their keywords
+    /// (`array[string, int]`, `table{one: string}`) so the output can be used as a type
+    /// annotation. Table keys that are not plain identifiers are quoted. Macros have no
+    /// annotation keyword and are written as `any`, as a macro takes the type of the value
+    /// it references.
+    pub fn to_annotation(&self) -> String {
+        match self {
+            Self::Macro => "any".to_string(),
+            Self::Optional(inner) => format!("{}?", inner.to_annotation()),
+            Self::Array(children) => format!(
+                "array[{}]",
+                children
+                    .iter()
+                    .map(|x| x.to_annotation())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Table(children) => format!(
+                "table{{{}}}",
+                children
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", quote_key(k), v.to_annotation()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            other => other.to_string(),
+        }
+    }
+
+    /// Returns the category of this type for grouping purposes, optional types
+    /// share the category of their inner type
     pub const fn category(&self) -> TypeCategory {
         match self {
             Self::String => TypeCategory::Text,
@@ -199,17 +360,38 @@ impl ValueType {
             Self::Bytes => TypeCategory::Binary,
             Self::Bool => TypeCategory::Boolean,
             Self::Version | Self::Require => TypeCategory::Version,
+            #[cfg(feature = "datetime")]
+            Self::DateTime => TypeCategory::DateTime,
+            #[cfg(feature = "decimal")]
+            Self::Decimal => TypeCategory::Decimal,
+            #[cfg(feature = "net")]
+            Self::Ip | Self::Cidr => TypeCategory::Network,
+            #[cfg(feature = "regex")]
+            Self::Regex => TypeCategory::Pattern,
             Self::Macro => TypeCategory::Macro,
-            Self::Label | Self::Symbol => TypeCategory::Identifier,
+            Self::Label | Self::Symbol | Self::Enum(_) => TypeCategory::Identifier,
             Self::Null => TypeCategory::Null,
+            Self::Any => TypeCategory::Any,
             Self::Array(_) => TypeCategory::Collection,
             Self::Table(_) => TypeCategory::Collection,
+            Self::Optional(inner) => (**inner).category(),
         }
     }
 }
 
+/// Quotes a key unless it would be read back as the same plain identifier
+pub(crate) fn quote_key(key: &str) -> String {
+    let mut lexer = Token::lexer(key);
+    match (lexer.next(), lexer.next()) {
+        (Some(Ok(Token::Identifier((_, id)))), None) if id == key => key.to_string(),
+        _ if key.contains('\'') => format!("\"{key}\""),
+        _ => format!("'{key}'"),
+    }
+}
+
 /// Categories for grouping related types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum TypeCategory {
     Text,
     SignedInteger,
@@ -218,9 +400,18 @@ pub enum TypeCategory {
     Binary,
     Boolean,
     Version,
+    #[cfg(feature = "datetime")]
+    DateTime,
+    #[cfg(feature = "decimal")]
+    Decimal,
+    #[cfg(feature = "net")]
+    Network,
+    #[cfg(feature = "regex")]
+    Pattern,
     Macro,
     Identifier,
     Null,
+    Any,
     Collection,
 }
 
@@ -247,10 +438,31 @@ impl fmt::Display for ValueType {
             Self::Bool => f.write_str("bool"),
             Self::Version => f.write_str("version"),
             Self::Require => f.write_str("require"),
+            #[cfg(feature = "datetime")]
+            Self::DateTime => f.write_str("datetime"),
+            #[cfg(feature = "decimal")]
+            Self::Decimal => f.write_str("decimal"),
+            #[cfg(feature = "net")]
+            Self::Ip => f.write_str("ip"),
+            #[cfg(feature = "net")]
+            Self::Cidr => f.write_str("cidr"),
+            #[cfg(feature = "regex")]
+            Self::Regex => f.write_str("regex"),
             Self::Macro => f.write_str("macro"),
             Self::Label => f.write_str("label"),
             Self::Symbol => f.write_str("symbol"),
             Self::Null => f.write_str("null"),
+            Self::Any => f.write_str("any"),
+            Self::Optional(inner) => write!(f, "{inner}?"),
+            Self::Enum(symbols) => write!(
+                f,
+                "enum{{{}}}",
+                symbols
+                    .iter()
+                    .map(|symbol| format!(":{symbol}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Self::Array(children) => {
                 if children.is_empty() {
                     f.write_str("[]")
@@ -285,7 +497,21 @@ impl fmt::Display for ValueType {
     }
 }
 
+impl std::str::FromStr for ValueType {
+    type Err = crate::error::Error;
+
+    /// Parses a type annotation such as `array[string, int]` or `table{port: u16}?`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::syn::Parser::new(".", Token::lexer(s)).type_annotation()
+    }
+}
+
 /// Represents the type of a statement in the BarkML language
+///
+/// Statement types double as schemas of the statements they describe. Child types are
+/// compared like a map, so two schemas declaring the same ids with the same types are equal
+/// whatever order they declare them in, which also holds for the fields of table types.
+/// Block labels and array element types are compared in order.
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub enum StatementType {
     /// Control statement ($identifier = value)
@@ -296,6 +522,11 @@ pub enum StatementType {
     /// Stores the expected type of the value
     Assignment(ValueType),
 
+    /// Append assignment statement (identifier += value)
+    /// Stores the expected type of the value, the value is concatenated onto an existing
+    /// array with the same id when modules are merged
+    Append(ValueType),
+
     /// Block statement (identifier labels { statements })
     /// Stores the types of labels and contents
     Block {
@@ -325,13 +556,16 @@ impl StatementType {
 
     /// Returns true if this statement type represents a value assignment
     pub const fn is_assignment(&self) -> bool {
-        matches!(self, Self::Control(_) | Self::Assignment(_))
+        matches!(
+            self,
+            Self::Control(_) | Self::Assignment(_) | Self::Append(_)
+        )
     }
 
     /// Gets the value type for assignment statements, None for containers
     pub fn value_type(&self) -> Option<&ValueType> {
         match self {
-            Self::Control(vt) | Self::Assignment(vt) => Some(vt),
+            Self::Control(vt) | Self::Assignment(vt) | Self::Append(vt) => Some(vt),
             _ => None,
         }
     }
@@ -345,6 +579,36 @@ impl StatementType {
             _ => None,
         }
     }
+
+    /// Flattens the nested statement types into dotted paths of every leaf assignment
+    ///
+    /// Control and assignment statements become entries keyed by the path of their ids
+    /// joined with '.', containers contribute only the prefix of their children.
+    pub fn flatten_paths(&self) -> IndexMap<String, ValueType> {
+        let mut paths = IndexMap::new();
+        self.flatten_into("", &mut paths);
+        paths
+    }
+
+    fn flatten_into(&self, prefix: &str, paths: &mut IndexMap<String, ValueType>) {
+        match self {
+            Self::Control(vt) | Self::Assignment(vt) | Self::Append(vt) => {
+                if !prefix.is_empty() {
+                    paths.insert(prefix.to_string(), vt.clone());
+                }
+            }
+            Self::Block { contents, .. } | Self::Section(contents) | Self::Module(contents) => {
+                for (key, child) in contents {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    child.flatten_into(&path, paths);
+                }
+            }
+        }
+    }
 }
 
 /// Stores the metadata associated with a value or statement
@@ -362,6 +626,10 @@ pub struct Metadata {
 
     /// Optional label associated with the node (from !label syntax)
     pub label: Option<String>,
+
+    /// Whether the comment was written as `#` line comments and is displayed as such
+    #[serde(default)]
+    pub line_comment: bool,
 }
 
 impl Metadata {
@@ -371,6 +639,7 @@ impl Metadata {
             location,
             comment: None,
             label: None,
+            line_comment: false,
         }
     }
 
@@ -384,6 +653,7 @@ impl Metadata {
             location,
             comment,
             label,
+            line_comment: false,
         }
     }
 
@@ -401,6 +671,14 @@ impl Metadata {
     pub const fn has_annotations(&self) -> bool {
         self.has_comment() || self.has_label()
     }
+
+    /// Resets the location to the default, keeping comments and labels
+    ///
+    /// Normalizing locations lets tests compare metadata of trees parsed from sources that
+    /// only differ in layout.
+    pub fn strip_locations(&mut self) {
+        self.location = Location::default();
+    }
 }
 
 /// Represents a location in the source code
@@ -418,6 +696,9 @@ pub struct Location {
     pub length: usize,
     /// File path where the token was found
     pub file_path: Option<String>,
+    /// Absolute byte offset of the token in the source
+    #[serde(default)]
+    pub byte_start: usize,
 }
 
 impl Location {
@@ -430,6 +711,7 @@ impl Location {
             source_text: None,
             length: 0,
             file_path: None,
+            byte_start: 0,
         }
     }
 
@@ -449,6 +731,7 @@ impl Location {
             source_text,
             length,
             file_path,
+            byte_start: 0,
         }
     }
 
@@ -468,6 +751,19 @@ impl Location {
         self.file_path = Some(path.to_string());
     }
 
+    /// Absolute byte offset just past the end of the token
+    pub const fn byte_end(&self) -> usize {
+        self.byte_start + self.length
+    }
+
+    /// Byte range of the token in the source
+    ///
+    /// Slicing the parsed source with this range yields the token text, which makes
+    /// it suitable for editor integrations that address text by byte offset.
+    pub const fn span(&self) -> Range<usize> {
+        self.byte_start..self.byte_end()
+    }
+
     /// Get the human-readable position (1-based for display)
     pub const fn position(&self) -> (usize, usize) {
         (self.line + 1, self.column + 1)
@@ -541,8 +837,9 @@ impl Location {
             (std::cmp::max(self.column + self.length, other.column + other.length))
                 .saturating_sub(start_col)
         } else {
-            // Multi-line span - approximate
-            self.length + other.length
+            // Columns restart on every line, so multi-line spans are measured in bytes
+            std::cmp::max(self.byte_end(), other.byte_end())
+                .saturating_sub(std::cmp::min(self.byte_start, other.byte_start))
         };
 
         Location {
@@ -555,6 +852,7 @@ impl Location {
                 .or_else(|| other.source_text.clone()),
             length,
             file_path: self.file_path.clone().or_else(|| other.file_path.clone()),
+            byte_start: std::cmp::min(self.byte_start, other.byte_start),
         }
     }
 }
@@ -609,6 +907,181 @@ mod tests {
         assert!(!ValueType::U64.can_assign(&ValueType::I64));
     }
 
+    #[test]
+    fn test_value_type_common_supertype() {
+        use ValueType::*;
+
+        // Integers widen to the narrowest type holding every element
+        assert_eq!(ValueType::common_supertype(&[U8, U16, U32]), Some(U32));
+        assert_eq!(ValueType::common_supertype(&[I8, U8]), Some(I16));
+        assert_eq!(ValueType::common_supertype(&[I32, Signed]), Some(Signed));
+        assert_eq!(ValueType::common_supertype(&[U8, Unsigned]), Some(Unsigned));
+        assert_eq!(ValueType::common_supertype(&[U64, I8]), Some(I128));
+
+        // Floats widen to f64 or the generic float
+        assert_eq!(ValueType::common_supertype(&[F32, F64]), Some(F64));
+        assert_eq!(ValueType::common_supertype(&[F32, Float]), Some(Float));
+
+        // Nulls make the element type optional
+        assert_eq!(
+            ValueType::common_supertype(&[String, Null, Optional(Box::new(String))]),
+            Some(Optional(Box::new(String)))
+        );
+        assert_eq!(ValueType::common_supertype(&[Null]), Some(Null));
+        assert_eq!(ValueType::common_supertype(&[Any, Bool]), Some(Any));
+
+        // Heterogeneous types have no common type
+        assert_eq!(ValueType::common_supertype(&[String, Signed]), None);
+        assert_eq!(ValueType::common_supertype(&[U8, F32]), None);
+        assert_eq!(ValueType::common_supertype(&[U128, I8]), None);
+        assert_eq!(ValueType::common_supertype(&[]), None);
+    }
+
+    #[test]
+    fn test_value_type_any() {
+        assert!(ValueType::Any.can_assign(&ValueType::String));
+        assert!(ValueType::Any.can_assign(&ValueType::Macro));
+        assert!(ValueType::Any.can_assign(&ValueType::Array(vec![
+            ValueType::Signed,
+            ValueType::String
+        ])));
+        assert!(!ValueType::String.can_assign(&ValueType::Any));
+
+        // array[any] permits mixed elements of any length
+        let any_array = ValueType::Array(vec![ValueType::Any]);
+        assert!(any_array.can_assign(&ValueType::Array(vec![])));
+        assert!(ValueType::Array(vec![ValueType::String]).can_assign(&ValueType::Array(vec![])));
+        assert!(!ValueType::Array(vec![]).can_assign(&ValueType::Array(vec![ValueType::String])));
+        assert!(any_array.can_assign(&ValueType::Array(vec![
+            ValueType::Signed,
+            ValueType::String,
+            ValueType::Bool
+        ])));
+        assert!(!any_array.can_assign(&ValueType::String));
+
+        // Other array types are checked element by element
+        let pair = ValueType::Array(vec![ValueType::Any, ValueType::Signed]);
+        assert!(pair.can_assign(&ValueType::Array(vec![ValueType::Bool, ValueType::I8])));
+        assert!(!pair.can_assign(&ValueType::Array(vec![ValueType::Bool, ValueType::String])));
+        assert!(!pair.can_assign(&ValueType::Array(vec![ValueType::Bool])));
+    }
+
+    #[test]
+    fn test_value_type_optional() {
+        let optional = ValueType::Optional(Box::new(ValueType::String));
+        assert!(optional.can_assign(&ValueType::String));
+        assert!(optional.can_assign(&ValueType::Null));
+        assert!(optional.can_assign(&optional));
+        assert!(!optional.can_assign(&ValueType::Signed));
+        assert!(!ValueType::String.can_assign(&optional));
+
+        let optional_int = ValueType::Optional(Box::new(ValueType::Signed));
+        assert!(optional_int.can_assign(&ValueType::I8));
+        assert!(optional_int.can_assign(&ValueType::Optional(Box::new(ValueType::I8))));
+
+        assert_eq!(optional.to_string(), "string?");
+        assert_eq!(optional.category(), TypeCategory::Text);
+
+        // Categories can be computed in constant contexts
+        const CATEGORY: TypeCategory = ValueType::U16.category();
+        assert_eq!(CATEGORY, TypeCategory::UnsignedInteger);
+    }
+
+    #[test]
+    fn test_value_type_enum() {
+        let mode = ValueType::Enum(vec!["fast".to_string(), "safe".to_string()]);
+        assert!(mode.can_assign(&ValueType::Symbol));
+        assert!(mode.can_assign(&ValueType::Enum(vec!["safe".to_string()])));
+        assert!(!mode.can_assign(&ValueType::Enum(vec!["turbo".to_string()])));
+        assert!(!mode.can_assign(&ValueType::String));
+        assert!(ValueType::Symbol.can_assign(&ValueType::Symbol));
+
+        assert_eq!(mode.to_string(), "enum{:fast, :safe}");
+        assert_eq!(mode.category(), TypeCategory::Identifier);
+    }
+
+    #[test]
+    fn test_value_type_annotation() {
+        let type_ = ValueType::Table(IndexMap::from([
+            ("one".to_string(), ValueType::String),
+            (
+                "version".to_string(),
+                ValueType::Array(vec![ValueType::Signed, ValueType::Macro]),
+            ),
+            (
+                "with space".to_string(),
+                ValueType::Optional(Box::new(ValueType::Table(IndexMap::new()))),
+            ),
+        ]));
+        assert_eq!(
+            type_.to_annotation(),
+            "table{one: string, 'version': array[int, any], 'with space': table{}?}"
+        );
+    }
+
+    #[test]
+    fn test_value_type_from_str() {
+        for (annotation, expected) in [
+            ("string", ValueType::String),
+            ("int", ValueType::Signed),
+            ("i8", ValueType::I8),
+            ("i16", ValueType::I16),
+            ("i32", ValueType::I32),
+            ("i64", ValueType::I64),
+            ("i128", ValueType::I128),
+            ("uint", ValueType::Unsigned),
+            ("u8", ValueType::U8),
+            ("u16", ValueType::U16),
+            ("u32", ValueType::U32),
+            ("u64", ValueType::U64),
+            ("u128", ValueType::U128),
+            ("float", ValueType::Float),
+            ("f32", ValueType::F32),
+            ("f64", ValueType::F64),
+            ("bool", ValueType::Bool),
+            ("bytes", ValueType::Bytes),
+            ("version", ValueType::Version),
+            ("require", ValueType::Require),
+            ("label", ValueType::Label),
+            ("symbol", ValueType::Symbol),
+            ("null", ValueType::Null),
+            ("any", ValueType::Any),
+        ] {
+            assert_eq!(annotation.parse::<ValueType>().unwrap(), expected);
+        }
+
+        assert_eq!(
+            "array[string, array[int?]]".parse::<ValueType>().unwrap(),
+            ValueType::Array(vec![
+                ValueType::String,
+                ValueType::Array(vec![ValueType::Optional(Box::new(ValueType::Signed))]),
+            ])
+        );
+
+        let table = ValueType::Table(IndexMap::from([
+            ("port".to_string(), ValueType::U16),
+            (
+                "hosts".to_string(),
+                ValueType::Array(vec![ValueType::String]),
+            ),
+        ]));
+        assert_eq!(
+            "table{port: u16, hosts: array[string]}"
+                .parse::<ValueType>()
+                .unwrap(),
+            table
+        );
+        assert_eq!(table.to_annotation().parse::<ValueType>().unwrap(), table);
+
+        assert!("".parse::<ValueType>().is_err());
+        assert!("banana".parse::<ValueType>().is_err());
+        assert!("array[string".parse::<ValueType>().is_err());
+        assert!(matches!(
+            "string int".parse::<ValueType>(),
+            Err(crate::error::Error::Expected { .. })
+        ));
+    }
+
     #[test]
     fn test_value_type_categories() {
         assert!(ValueType::I32.is_numeric());
@@ -625,6 +1098,38 @@ mod tests {
         assert!(!ValueType::String.is_compound());
     }
 
+    #[test]
+    fn test_statement_type_ignores_order() {
+        let schema = |code: &str| crate::from_str(code).unwrap().type_;
+
+        let original = schema(
+            "name = 'app'\nlimits = { cpu = 2, memory = '1Gi' }\n\
+             [server]\nhost = 'localhost'\nport = 80u16\n",
+        );
+        let reordered = schema(
+            "limits = { memory = '2Gi', cpu = 4 }\nname = 'other'\n\
+             [server]\nport = 8080u16\nhost = 'example.com'\n",
+        );
+        assert_eq!(original, reordered);
+        assert_ne!(
+            original.child_types().unwrap().keys().collect::<Vec<_>>(),
+            reordered.child_types().unwrap().keys().collect::<Vec<_>>()
+        );
+
+        // Different types, missing ids and reordered labels are still told apart
+        for different in [
+            "name = 'app'\nlimits = { cpu = 2, memory = '1Gi' }\n[server]\nhost = 'localhost'\nport = 80\n",
+            "name = 'app'\nlimits = { cpu = 2 }\n[server]\nhost = 'localhost'\nport = 80u16\n",
+            "name = 'app'\nlimits = { cpu = 2, memory = '1Gi' }\n[server]\nhost = 'localhost'\n",
+        ] {
+            assert_ne!(original, schema(different), "{different}");
+        }
+        assert_ne!(
+            schema("listener 'http' 80 { tls = false }\n"),
+            schema("listener 80 'http' { tls = false }\n")
+        );
+    }
+
     #[test]
     fn test_location_span() {
         let loc1 = Location::new(0, 5);
@@ -633,5 +1138,16 @@ mod tests {
 
         assert_eq!(span.line, 0);
         assert_eq!(span.column, 5);
+
+        // Spans across lines are measured from the first to the last byte
+        let mut open = Location::new(1, 4);
+        open.byte_start = 12;
+        open.length = 1;
+        let mut close = Location::new(3, 0);
+        close.byte_start = 30;
+        close.length = 1;
+        let span = open.span_to(&close);
+        assert_eq!((span.line, span.column), (1, 4));
+        assert_eq!(span.span(), 12..31);
     }
 }
@@ -1,3 +1,5 @@
+use super::Statement;
+use super::statement::ContentHasher;
 use super::types::{Metadata, ValueType};
 use crate::error;
 use base64::Engine;
@@ -10,8 +12,10 @@ use uuid::Uuid;
 /// Stores the actual in-memory data for a value in BarkML
 ///
 /// This enum represents all possible data types that can be stored in a BarkML value.
-/// Each variant corresponds to a specific data type in the language.
+/// Each variant corresponds to a specific data type in the language. Some variants only
+/// exist with their feature enabled, so matches outside of this crate need a wildcard arm.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
 pub enum Data {
     /// String value ('text')
     String(String),
@@ -57,6 +61,21 @@ pub enum Data {
     Version(semver::Version),
     /// Version requirement (^1.2.3, ~2.0)
     Require(semver::VersionReq),
+    /// Date and time with a fixed offset (2024-01-02T03:04:05Z)
+    #[cfg(feature = "datetime")]
+    DateTime(chrono::DateTime<chrono::FixedOffset>),
+    /// Exact fixed-point decimal number (1.50d)
+    #[cfg(feature = "decimal")]
+    Decimal(rust_decimal::Decimal),
+    /// IPv4 or IPv6 address (10.0.0.1, fe80::1)
+    #[cfg(feature = "net")]
+    Ip(std::net::IpAddr),
+    /// Network in CIDR notation (10.0.0.0/8, fe80::/10)
+    #[cfg(feature = "net")]
+    Cidr(Cidr),
+    /// Regular expression compiled when read (r'^[a-z]+$')
+    #[cfg(feature = "regex")]
+    Regex(Pattern),
     /// Macro reference (m'name' or m!name)
     Macro(String),
     /// Symbol identifier (:symbol)
@@ -93,6 +112,16 @@ impl Data {
             Data::Bool(_) => ValueType::Bool,
             Data::Version(_) => ValueType::Version,
             Data::Require(_) => ValueType::Require,
+            #[cfg(feature = "datetime")]
+            Data::DateTime(_) => ValueType::DateTime,
+            #[cfg(feature = "decimal")]
+            Data::Decimal(_) => ValueType::Decimal,
+            #[cfg(feature = "net")]
+            Data::Ip(_) => ValueType::Ip,
+            #[cfg(feature = "net")]
+            Data::Cidr(_) => ValueType::Cidr,
+            #[cfg(feature = "regex")]
+            Data::Regex(_) => ValueType::Regex,
             Data::Macro(_) => ValueType::Macro,
             Data::Symbol(_) => ValueType::Symbol,
             Data::Null => ValueType::Null,
@@ -133,6 +162,33 @@ impl Data {
         matches!(self, Data::Array(_) | Data::Table(_))
     }
 
+    /// Widens any integer to a 128-bit signed integer, None if it does not fit or is not an integer
+    pub(crate) fn integer_value(&self) -> Option<i128> {
+        match self {
+            Data::Signed(v) | Data::I64(v) => Some(*v as i128),
+            Data::I8(v) => Some(*v as i128),
+            Data::I16(v) => Some(*v as i128),
+            Data::I32(v) => Some(*v as i128),
+            Data::I128(v) => Some(*v),
+            Data::Unsigned(v) | Data::U64(v) => Some(*v as i128),
+            Data::U8(v) => Some(*v as i128),
+            Data::U16(v) => Some(*v as i128),
+            Data::U32(v) => Some(*v as i128),
+            Data::U128(v) => i128::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    /// Converts any numeric value to a 64-bit float, None if it is not numeric
+    pub(crate) fn float_value(&self) -> Option<f64> {
+        match self {
+            Data::Float(v) | Data::F64(v) => Some(*v),
+            Data::F32(v) => Some(*v as f64),
+            Data::U128(v) => Some(*v as f64),
+            data => data.integer_value().map(|x| x as f64),
+        }
+    }
+
     /// Returns the approximate memory size of this data in bytes
     pub fn memory_size(&self) -> usize {
         match self {
@@ -156,6 +212,112 @@ impl Data {
     }
 }
 
+/// A network written in CIDR notation, an address followed by its prefix length
+#[cfg(feature = "net")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Cidr {
+    /// Address of the network, host bits are kept as written
+    pub address: std::net::IpAddr,
+    /// Number of leading bits identifying the network
+    pub prefix: u8,
+}
+
+#[cfg(feature = "net")]
+impl std::str::FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (address, prefix) = s
+            .split_once('/')
+            .ok_or_else(|| format!("'{s}' is missing a prefix length"))?;
+        let address = address
+            .parse::<std::net::IpAddr>()
+            .map_err(|e| e.to_string())?;
+        let prefix = prefix.parse::<u8>().map_err(|e| e.to_string())?;
+        let max = if address.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            return Err(format!("prefix length {prefix} is larger than {max}"));
+        }
+        Ok(Self { address, prefix })
+    }
+}
+
+#[cfg(feature = "net")]
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+/// A regular expression compiled when it is read, keeping the pattern as written
+///
+/// Patterns compare, hash and serialize by their source text.
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone)]
+pub struct Pattern(regex::Regex);
+
+#[cfg(feature = "regex")]
+impl Pattern {
+    /// Returns the pattern as written
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns the compiled regular expression
+    pub fn regex(&self) -> &regex::Regex {
+        &self.0
+    }
+}
+
+#[cfg(feature = "regex")]
+impl std::str::FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        regex::Regex::new(s).map(Self).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "regex")]
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Eq for Pattern {}
+
+#[cfg(feature = "regex")]
+impl std::hash::Hash for Pattern {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+#[cfg(feature = "regex")]
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Serialize for Pattern {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "regex")]
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 /// Represents an individual value in the BarkML language
 ///
 /// A Value is the fundamental unit of data in BarkML. It contains the actual data,
@@ -211,11 +373,325 @@ impl Value {
         self.data.is_collection()
     }
 
+    /// Returns any integer value widened to 128 bits
+    ///
+    /// Matches every integer variant regardless of its suffix, so `3`, `3u8` and `3i128`
+    /// all return `Some(3)`. Returns None for floats, non-numeric values and `u128` values
+    /// above `i128::MAX`.
+    pub fn as_integer(&self) -> Option<i128> {
+        self.data.integer_value()
+    }
+
+    /// Returns any integer or float value as a 64-bit float
+    ///
+    /// Returns None for non-numeric values and for 128-bit integers outside of the 64-bit
+    /// integer range.
+    pub fn as_number(&self) -> Option<f64> {
+        match &self.data {
+            Data::I128(_) | Data::U128(_) => {
+                let value = self.as_integer()?;
+                (i64::try_from(value).is_ok() || u64::try_from(value).is_ok())
+                    .then_some(value as f64)
+            }
+            data => data.float_value(),
+        }
+    }
+
+    /// Returns any integer or float value as a 64-bit signed integer
+    ///
+    /// Floats are truncated toward zero. Returns None for non-numeric values, non-finite
+    /// floats and values outside of the 64-bit signed range.
+    pub fn as_i64_lossy(&self) -> Option<i64> {
+        if let Some(value) = self.as_integer() {
+            return i64::try_from(value).ok();
+        }
+        let value = self.data.float_value()?.trunc();
+        let limit = 2f64.powi(63);
+        (value >= -limit && value < limit).then_some(value as i64)
+    }
+
+    /// Flattens nested tables and arrays into a map of paths to leaf values
+    ///
+    /// Table keys and array indices are joined with `separator`, so `{a = {b = 1}}` becomes
+    /// `a.b = 1` and `{a = [1, 2]}` becomes `a.0 = 1` and `a.1 = 2` with a `.` separator.
+    /// Empty tables and arrays are kept as leaves. A value that is not a collection is
+    /// returned as a single entry with an empty path.
+    pub fn flatten(&self, separator: &str) -> IndexMap<String, Value> {
+        let mut entries = Vec::new();
+        self.leaves_into(String::new(), separator, &mut entries);
+        entries
+            .into_iter()
+            .map(|(path, value)| (path, value.clone()))
+            .collect()
+    }
+
+    /// Collects the leaf values below this value with their paths, see [`Value::flatten`]
+    pub(crate) fn leaves_into<'a>(
+        &'a self,
+        path: String,
+        separator: &str,
+        entries: &mut Vec<(String, &'a Value)>,
+    ) {
+        let join = |key: &str| {
+            if path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}{}{}", path, separator, key)
+            }
+        };
+        match &self.data {
+            Data::Table(children) if !children.is_empty() => {
+                for (key, value) in children {
+                    value.leaves_into(join(key), separator, entries);
+                }
+            }
+            Data::Array(values) if !values.is_empty() => {
+                for (index, value) in values.iter().enumerate() {
+                    value.leaves_into(join(&index.to_string()), separator, entries);
+                }
+            }
+            _ => entries.push((path, self)),
+        }
+    }
+
+    /// Converts this value with its `TryFrom<&Value>` implementation, `value.get::<u32>()`
+    ///
+    /// Conversions are as strict as the implementations, so an `int` value does not
+    /// convert to a `u32`.
+    pub fn get<T>(&self) -> crate::Result<T>
+    where
+        T: for<'a> TryFrom<&'a Value, Error = error::Error>,
+    {
+        T::try_from(self)
+    }
+
+    /// Checks whether this version value satisfies a requirement value
+    ///
+    /// This value must hold a version and `req` a requirement, for example `1.4.2`
+    /// satisfies `^1.2`. Any other combination of types is an error located at this value.
+    pub fn satisfies(&self, req: &Value) -> crate::Result<bool> {
+        match (&self.data, &req.data) {
+            (Data::Version(version), Data::Require(require)) => Ok(require.matches(version)),
+            _ => error::SatisfiesSnafu {
+                location: self.meta.location.clone(),
+                version: self.type_of(),
+                require: req.type_of(),
+            }
+            .fail(),
+        }
+    }
+
+    /// Resets the locations of this value and every nested array element and table entry
+    ///
+    /// See [`Metadata::strip_locations`].
+    pub fn strip_locations(&mut self) {
+        self.meta.strip_locations();
+        match &mut self.data {
+            Data::Array(values) => values.iter_mut().for_each(Value::strip_locations),
+            Data::Table(values) => values.values_mut().for_each(Value::strip_locations),
+            _ => {}
+        }
+    }
+
+    /// Merges the entries of another table value into this table
+    ///
+    /// Keys from `other` are inserted in order, overwriting existing keys. When `deep` is
+    /// true and both the existing and incoming values of a key are tables, they are merged
+    /// recursively instead. Both values must be tables, otherwise an assignment error
+    /// located at `other` is returned and this value is left unchanged.
+    pub fn merge_table(&mut self, other: &Value, deep: bool) -> crate::Result<()> {
+        let (Data::Table(children), Data::Table(incoming)) = (&mut self.data, &other.data) else {
+            return error::AssignSnafu {
+                location: other.meta.location.clone(),
+                left: self.type_of(),
+                right: other.type_of(),
+            }
+            .fail();
+        };
+        for (key, value) in incoming {
+            match children.get_mut(key) {
+                Some(existing)
+                    if deep
+                        && matches!(
+                            (&existing.data, &value.data),
+                            (Data::Table(_), Data::Table(_))
+                        ) =>
+                {
+                    existing.merge_table(value, deep)?;
+                }
+                _ => {
+                    children.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Feeds the type, label and contents of this value to a hasher, ignoring the uid,
+    /// location and comment
+    pub(crate) fn hash_content(&self, state: &mut ContentHasher) {
+        state.write_option_str(self.meta.label.as_deref());
+        match &self.data {
+            Data::Array(values) => {
+                state.write_str("array");
+                state.write_u64(values.len() as u64);
+                for value in values {
+                    value.hash_content(state);
+                }
+            }
+            Data::Table(children) => {
+                state.write_str("table");
+                state.write_u64(children.len() as u64);
+                for (key, value) in children {
+                    state.write_str(key);
+                    value.hash_content(state);
+                }
+            }
+            data => {
+                state.write_str(&data.type_of().to_string());
+                state.write_str(&self.to_macro_string());
+            }
+        }
+    }
+
     /// Returns the approximate memory size of this value
     pub fn memory_size(&self) -> usize {
         self.data.memory_size() + std::mem::size_of::<Uuid>() + std::mem::size_of::<Metadata>()
     }
 
+    /// Converts this value to the given type, allowing explicit conversions
+    ///
+    /// Besides every implicit conversion accepted by `ValueType::can_assign`, this supports
+    /// range checked conversions between integer types, integers to floats, conversions
+    /// between float precisions, parsing strings as versions or requirements, and
+    /// converting between strings and UTF-8 bytes. Arrays are converted element by element.
+    pub fn coerce_to(&self, ty: &ValueType) -> crate::Result<Value> {
+        let found = self.type_of();
+        if ty == &found {
+            return Ok(self.clone());
+        }
+        if ty.can_assign(&found) {
+            return Statement::convert_value(ty, self);
+        }
+
+        let coerce_error = |reason: String| error::Error::Coerce {
+            left: ty.clone(),
+            right: found.clone(),
+            reason,
+        };
+
+        let data = match (ty, &self.data) {
+            (ValueType::Optional(inner), _) => return self.coerce_to(inner),
+            (ValueType::Array(types), Data::Array(values)) if types.len() == values.len() => {
+                Data::Array(
+                    types
+                        .iter()
+                        .zip(values)
+                        .map(|(ty, value)| value.coerce_to(ty))
+                        .collect::<crate::Result<_>>()?,
+                )
+            }
+            (_, data) if ty.is_integer() && found.is_integer() => {
+                let value = data.integer_value().ok_or_else(|| {
+                    coerce_error("value does not fit in a 128-bit signed integer".to_string())
+                })?;
+                let out_of_range = |_| coerce_error(format!("{} is out of range", value));
+                match ty {
+                    ValueType::Signed => Data::Signed(value.try_into().map_err(out_of_range)?),
+                    ValueType::I8 => Data::I8(value.try_into().map_err(out_of_range)?),
+                    ValueType::I16 => Data::I16(value.try_into().map_err(out_of_range)?),
+                    ValueType::I32 => Data::I32(value.try_into().map_err(out_of_range)?),
+                    ValueType::I64 => Data::I64(value.try_into().map_err(out_of_range)?),
+                    ValueType::I128 => Data::I128(value),
+                    ValueType::Unsigned => Data::Unsigned(value.try_into().map_err(out_of_range)?),
+                    ValueType::U8 => Data::U8(value.try_into().map_err(out_of_range)?),
+                    ValueType::U16 => Data::U16(value.try_into().map_err(out_of_range)?),
+                    ValueType::U32 => Data::U32(value.try_into().map_err(out_of_range)?),
+                    ValueType::U64 => Data::U64(value.try_into().map_err(out_of_range)?),
+                    _ => Data::U128(value.try_into().map_err(out_of_range)?),
+                }
+            }
+            (ValueType::Float | ValueType::F32 | ValueType::F64, data) if found.is_numeric() => {
+                let value = data.float_value().unwrap_or_default();
+                match ty {
+                    ValueType::Float => Data::Float(value),
+                    ValueType::F64 => Data::F64(value),
+                    _ => {
+                        if value.is_finite() && value.abs() > f32::MAX as f64 {
+                            return Err(coerce_error(format!(
+                                "{} is out of range for a 32-bit float",
+                                value
+                            )));
+                        }
+                        Data::F32(value as f32)
+                    }
+                }
+            }
+            (ValueType::Version, Data::String(value)) => Data::Version(
+                semver::Version::parse(value).map_err(|e| coerce_error(e.to_string()))?,
+            ),
+            (ValueType::Require, Data::String(value)) => Data::Require(
+                semver::VersionReq::parse(value).map_err(|e| coerce_error(e.to_string()))?,
+            ),
+            (ValueType::Bytes, Data::String(value)) => Data::Bytes(value.as_bytes().to_vec()),
+            (ValueType::String, Data::Bytes(value)) => Data::String(
+                String::from_utf8(value.clone()).map_err(|e| coerce_error(e.to_string()))?,
+            ),
+            (ValueType::String, Data::Version(value)) => Data::String(value.to_string()),
+            (ValueType::String, Data::Require(value)) => Data::String(value.to_string()),
+            #[cfg(feature = "decimal")]
+            (ValueType::Decimal, Data::String(value)) => Data::Decimal(
+                value
+                    .parse()
+                    .map_err(|e: rust_decimal::Error| coerce_error(e.to_string()))?,
+            ),
+            #[cfg(feature = "decimal")]
+            (ValueType::String, Data::Decimal(value)) => Data::String(value.to_string()),
+            #[cfg(feature = "net")]
+            (ValueType::Ip, Data::String(value)) => Data::Ip(
+                value
+                    .parse()
+                    .map_err(|e: std::net::AddrParseError| coerce_error(e.to_string()))?,
+            ),
+            #[cfg(feature = "net")]
+            (ValueType::String, Data::Ip(value)) => Data::String(value.to_string()),
+            #[cfg(feature = "net")]
+            (ValueType::Cidr, Data::String(value)) => {
+                Data::Cidr(value.parse().map_err(coerce_error)?)
+            }
+            #[cfg(feature = "net")]
+            (ValueType::String, Data::Cidr(value)) => Data::String(value.to_string()),
+            #[cfg(feature = "regex")]
+            (ValueType::Regex, Data::String(value)) => {
+                Data::Regex(value.parse().map_err(coerce_error)?)
+            }
+            #[cfg(feature = "regex")]
+            (ValueType::String, Data::Regex(value)) => Data::String(value.to_string()),
+            _ => {
+                return error::ImplicitConvertSnafu {
+                    left: ty.clone(),
+                    right: found,
+                }
+                .fail();
+            }
+        };
+
+        Ok(Value {
+            uid: self.uid,
+            data,
+            meta: self.meta.clone(),
+        })
+    }
+
+    /// Formats byte data as lowercase hexadecimal, the form accepted by `x'...'` literals
+    ///
+    /// Returns None if this value does not hold bytes. Byte data is still displayed as
+    /// base64 by default.
+    pub fn to_hex_string(&self) -> Option<String> {
+        self.as_bytes()
+            .map(|bytes| bytes.iter().map(|x| format!("{x:02x}")).collect())
+    }
+
     /// Converts this value to a macro string representation
     pub fn to_macro_string(&self) -> String {
         match &self.data {
@@ -252,6 +728,16 @@ impl Value {
             Data::Null => "null".to_string(),
             Data::Version(value) => value.to_string(),
             Data::Require(value) => value.to_string(),
+            #[cfg(feature = "datetime")]
+            Data::DateTime(value) => value.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+            #[cfg(feature = "decimal")]
+            Data::Decimal(value) => value.to_string(),
+            #[cfg(feature = "net")]
+            Data::Ip(value) => value.to_string(),
+            #[cfg(feature = "net")]
+            Data::Cidr(value) => value.to_string(),
+            #[cfg(feature = "regex")]
+            Data::Regex(value) => value.to_string(),
         }
     }
 }
@@ -320,6 +806,28 @@ value_methods!(
     semver::VersionReq,
     Require
 );
+#[cfg(feature = "datetime")]
+value_methods!(
+    new_datetime,
+    as_datetime,
+    as_datetime_mut,
+    chrono::DateTime<chrono::FixedOffset>,
+    DateTime
+);
+#[cfg(feature = "decimal")]
+value_methods!(
+    new_decimal,
+    as_decimal,
+    as_decimal_mut,
+    rust_decimal::Decimal,
+    Decimal
+);
+#[cfg(feature = "net")]
+value_methods!(new_ip, as_ip, as_ip_mut, std::net::IpAddr, Ip);
+#[cfg(feature = "net")]
+value_methods!(new_cidr, as_cidr, as_cidr_mut, Cidr, Cidr);
+#[cfg(feature = "regex")]
+value_methods!(new_regex, as_regex, as_regex_mut, Pattern, Regex);
 value_methods!(new_macro, as_macro, as_macro_mut, String, Macro);
 value_methods!(new_symbol, as_symbol, as_symbol_mut, String, Symbol);
 value_methods!(new_array, as_array, as_array_mut, Vec<Value>, Array);
@@ -364,6 +872,20 @@ try_from_value!(Vec<u8>, as_bytes, ValueType::Bytes);
 try_from_value!(bool, as_bool, ValueType::Bool);
 try_from_value!(semver::Version, as_version, ValueType::Version);
 try_from_value!(semver::VersionReq, as_require, ValueType::Require);
+#[cfg(feature = "datetime")]
+try_from_value!(
+    chrono::DateTime<chrono::FixedOffset>,
+    as_datetime,
+    ValueType::DateTime
+);
+#[cfg(feature = "decimal")]
+try_from_value!(rust_decimal::Decimal, as_decimal, ValueType::Decimal);
+#[cfg(feature = "net")]
+try_from_value!(std::net::IpAddr, as_ip, ValueType::Ip);
+#[cfg(feature = "net")]
+try_from_value!(Cidr, as_cidr, ValueType::Cidr);
+#[cfg(feature = "regex")]
+try_from_value!(Pattern, as_regex, ValueType::Regex);
 
 // Special TryFrom implementations with fallback logic
 impl<'a> TryFrom<&'a Value> for String {
@@ -457,6 +979,36 @@ impl<'a> TryFrom<&'a Value> for IndexMap<String, Value> {
     }
 }
 
+/// Writes a floating point number in a form the lexer reads back to the same value
+///
+/// Finite values use the shortest digits that parse back to the exact same number and
+/// always hold a decimal point, so `3.0` is not read back as an integer and `1e300` is
+/// written as `1.0e300`. Special values are written without a precision suffix as the
+/// `inf`, `-inf` and `nan` keywords only exist in their generic form.
+pub(crate) fn write_float<F>(f: &mut fmt::Formatter<'_>, value: F, suffix: &str) -> fmt::Result
+where
+    F: Into<f64> + fmt::Debug + Copy,
+{
+    let wide: f64 = value.into();
+    if wide.is_nan() {
+        f.write_str("nan")
+    } else if wide.is_infinite() {
+        f.write_str(if wide.is_sign_negative() {
+            "-inf"
+        } else {
+            "inf"
+        })
+    } else {
+        let digits = format!("{:?}", value);
+        match digits.split_once('e') {
+            Some((mantissa, exponent)) if !mantissa.contains('.') => {
+                write!(f, "{}.0e{}{}", mantissa, exponent, suffix)
+            }
+            _ => write!(f, "{}{}", digits, suffix),
+        }
+    }
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Write comment if present
@@ -484,9 +1036,9 @@ impl fmt::Display for Value {
             Data::U32(value) => write!(f, "{}u32", value),
             Data::U64(value) => write!(f, "{}u64", value),
             Data::U128(value) => write!(f, "{}u128", value),
-            Data::Float(value) => write!(f, "{}", value),
-            Data::F32(value) => write!(f, "{}f32", value),
-            Data::F64(value) => write!(f, "{}f64", value),
+            Data::Float(value) => write_float(f, *value, ""),
+            Data::F32(value) => write_float(f, *value, "f32"),
+            Data::F64(value) => write_float(f, *value, "f64"),
             Data::Bool(value) => write!(f, "{}", if *value { "true" } else { "false" }),
             Data::Bytes(value) => write!(
                 f,
@@ -498,6 +1050,23 @@ impl fmt::Display for Value {
             Data::Null => write!(f, "null"),
             Data::Version(value) => write!(f, "{}", value),
             Data::Require(value) => write!(f, "{}", value),
+            #[cfg(feature = "datetime")]
+            Data::DateTime(value) => f.write_str(
+                value
+                    .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+                    .as_str(),
+            ),
+            #[cfg(feature = "decimal")]
+            Data::Decimal(value) => write!(f, "{}d", value),
+            #[cfg(feature = "net")]
+            Data::Ip(value) => write!(f, "{}", value),
+            #[cfg(feature = "net")]
+            Data::Cidr(value) => write!(f, "{}", value),
+            // Raw patterns have no escapes, a pattern holding a single quote is double quoted
+            #[cfg(feature = "regex")]
+            Data::Regex(value) if value.as_str().contains('\'') => write!(f, "r\"{}\"", value),
+            #[cfg(feature = "regex")]
+            Data::Regex(value) => write!(f, "r'{}'", value),
             Data::Array(values) => {
                 write!(
                     f,
@@ -524,6 +1093,73 @@ impl fmt::Display for Value {
     }
 }
 
+/// Builder for table values with a fluent interface
+///
+/// Values are created with default metadata, so tools assembling documents can build
+/// nested structures without spelling out every `IndexMap` and `Metadata`. A builder can
+/// be passed anywhere a value is expected through its `Into<Value>` conversion, nested
+/// arrays are built with [`ArrayBuilder`].
+#[derive(Debug, Clone, Default)]
+pub struct TableBuilder {
+    fields: IndexMap<String, Value>,
+}
+
+impl TableBuilder {
+    /// Starts an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a field of the table, replacing an earlier field with the same key
+    pub fn field(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.fields.insert(key.into(), value.into());
+        self
+    }
+
+    /// Finishes the builder into a table value with default metadata
+    pub fn build(self) -> Value {
+        Value::new_table(self.fields, Metadata::default())
+    }
+}
+
+impl From<TableBuilder> for Value {
+    fn from(builder: TableBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builder for array values with a fluent interface
+///
+/// The array counterpart of [`TableBuilder`], elements are appended in order.
+#[derive(Debug, Clone, Default)]
+pub struct ArrayBuilder {
+    values: Vec<Value>,
+}
+
+impl ArrayBuilder {
+    /// Starts an empty array
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an element to the array
+    pub fn push(mut self, value: impl Into<Value>) -> Self {
+        self.values.push(value.into());
+        self
+    }
+
+    /// Finishes the builder into an array value with default metadata
+    pub fn build(self) -> Value {
+        Value::new_array(self.values, Metadata::default())
+    }
+}
+
+impl From<ArrayBuilder> for Value {
+    fn from(builder: ArrayBuilder) -> Self {
+        builder.build()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -558,4 +1194,489 @@ mod tests {
         assert!(converted.is_ok());
         assert_eq!(converted.unwrap(), "test");
     }
+
+    #[test]
+    fn test_float_round_trip() {
+        for value in [
+            3.0,
+            0.1,
+            -2.5,
+            0.30000000000000004,
+            1.2345678901234567,
+            1e300,
+            -1.5e-7,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+        ] {
+            let written = Value::new_float(value, Metadata::default()).to_string();
+            assert!(written.contains('.'), "{written}");
+            let module = crate::from_str(&format!("value = {written}\n")).unwrap();
+            let read = module.find_by_path("value").unwrap().get_value().unwrap();
+            assert_eq!(read.as_float(), Some(&value), "{written}");
+        }
+        assert_eq!(
+            Value::new_float(3.0, Metadata::default()).to_string(),
+            "3.0"
+        );
+        assert_eq!(
+            Value::new_float(1e300, Metadata::default()).to_string(),
+            "1.0e300"
+        );
+
+        for value in [3.0f32, 0.1, 16_777_217.0, 1e-30] {
+            let written = Value::new_f32(value, Metadata::default()).to_string();
+            let module = crate::from_str(&format!("value = {written}\n")).unwrap();
+            let read = module.find_by_path("value").unwrap().get_value().unwrap();
+            assert_eq!(read.as_f32(), Some(&value), "{written}");
+        }
+        assert_eq!(
+            Value::new_f64(3.0, Metadata::default()).to_string(),
+            "3.0f64"
+        );
+    }
+
+    #[test]
+    fn test_special_float_round_trip() {
+        let module =
+            crate::from_str("a = inf\nb = -inf\nc = nan\nd = -0.0\ne = -0.0f32\n").unwrap();
+
+        let displayed: Vec<String> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|id| {
+                module
+                    .find_by_path(id)
+                    .unwrap()
+                    .get_value()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(displayed, vec!["inf", "-inf", "nan", "-0.0", "-0.0f32"]);
+
+        let reparsed = crate::from_str(
+            &["a", "b", "c", "d"]
+                .iter()
+                .zip(&displayed)
+                .map(|(id, value)| format!("{id} = {value}\n"))
+                .collect::<String>(),
+        )
+        .unwrap();
+        let float = |id: &str| {
+            *reparsed
+                .find_by_path(id)
+                .unwrap()
+                .get_value()
+                .unwrap()
+                .as_float()
+                .unwrap()
+        };
+        assert_eq!(float("a"), f64::INFINITY);
+        assert_eq!(float("b"), f64::NEG_INFINITY);
+        assert!(float("c").is_nan());
+        assert!(float("d") == 0.0 && float("d").is_sign_negative());
+    }
+
+    #[test]
+    fn test_numeric_accessors() {
+        let meta = Metadata::default();
+        for value in [
+            Value::new_int(-3, meta.clone()),
+            Value::new_i8(-3, meta.clone()),
+            Value::new_i16(-3, meta.clone()),
+            Value::new_i32(-3, meta.clone()),
+            Value::new_i64(-3, meta.clone()),
+            Value::new_i128(-3, meta.clone()),
+        ] {
+            assert_eq!(value.as_integer(), Some(-3), "{value}");
+            assert_eq!(value.as_number(), Some(-3.0), "{value}");
+            assert_eq!(value.as_i64_lossy(), Some(-3), "{value}");
+        }
+        for value in [
+            Value::new_uint(3, meta.clone()),
+            Value::new_u8(3, meta.clone()),
+            Value::new_u16(3, meta.clone()),
+            Value::new_u32(3, meta.clone()),
+            Value::new_u64(3, meta.clone()),
+            Value::new_u128(3, meta.clone()),
+        ] {
+            assert_eq!(value.as_integer(), Some(3), "{value}");
+            assert_eq!(value.as_number(), Some(3.0), "{value}");
+            assert_eq!(value.as_i64_lossy(), Some(3), "{value}");
+        }
+        for value in [
+            Value::new_float(-2.75, meta.clone()),
+            Value::new_f32(-2.75, meta.clone()),
+            Value::new_f64(-2.75, meta.clone()),
+        ] {
+            assert_eq!(value.as_integer(), None, "{value}");
+            assert_eq!(value.as_number(), Some(-2.75), "{value}");
+            assert_eq!(value.as_i64_lossy(), Some(-2), "{value}");
+        }
+
+        // Unsigned values above the signed range still widen
+        let large = Value::new_u64(u64::MAX, meta.clone());
+        assert_eq!(large.as_integer(), Some(u64::MAX as i128));
+        assert_eq!(large.as_number(), Some(u64::MAX as f64));
+        assert_eq!(large.as_i64_lossy(), None);
+
+        // 128-bit values outside of the 64-bit range
+        let huge = Value::new_i128(i128::MIN, meta.clone());
+        assert_eq!(huge.as_integer(), Some(i128::MIN));
+        assert_eq!(huge.as_number(), None);
+        let huge = Value::new_u128(u128::MAX, meta.clone());
+        assert_eq!(huge.as_integer(), None);
+        assert_eq!(huge.as_number(), None);
+
+        assert_eq!(
+            Value::new_float(f64::NAN, meta.clone()).as_i64_lossy(),
+            None
+        );
+        assert_eq!(Value::new_float(1e19, meta.clone()).as_i64_lossy(), None);
+        for value in [
+            Value::new_string("3".to_string(), meta.clone()),
+            Value::new_bool(true, meta.clone()),
+            Value::new_null(meta),
+        ] {
+            assert_eq!(value.as_integer(), None);
+            assert_eq!(value.as_number(), None);
+            assert_eq!(value.as_i64_lossy(), None);
+        }
+    }
+
+    #[test]
+    fn test_to_hex_string() {
+        let value = Value::new_bytes(b"Hello\x00\xff".to_vec(), Metadata::default());
+        assert_eq!(value.to_hex_string(), Some("48656c6c6f00ff".to_string()));
+        assert_eq!(value.to_string(), "b'SGVsbG8A/w=='");
+        assert_eq!(
+            Value::new_bytes(Vec::new(), Metadata::default()).to_hex_string(),
+            Some(String::new())
+        );
+        assert_eq!(
+            Value::new_string("48".into(), Metadata::default()).to_hex_string(),
+            None
+        );
+    }
+
+    /// Parses the value of a `value = <code>` assignment
+    fn value(code: &str) -> Value {
+        let module = crate::from_str(&format!("value = {}\n", code)).unwrap();
+        module
+            .find_by_path("value")
+            .unwrap()
+            .get_value()
+            .unwrap()
+            .clone()
+    }
+
+    /// Flattens a value into its paths and leaves written as macro strings
+    fn flat_entries(value: &Value, separator: &str) -> Vec<(String, String)> {
+        value
+            .flatten(separator)
+            .into_iter()
+            .map(|(k, v)| (k, v.to_macro_string()))
+            .collect()
+    }
+
+    fn entry(k: &str, v: &str) -> (String, String) {
+        (k.to_string(), v.to_string())
+    }
+
+    #[test]
+    fn test_flatten() {
+        let flat = |code: &str, separator: &str| flat_entries(&value(code), separator);
+
+        assert_eq!(flat("{a = {b = 1}}", "."), vec![entry("a.b", "1")]);
+        assert_eq!(
+            flat("[1, [2, 3]]", "."),
+            vec![entry("0", "1"), entry("1.0", "2"), entry("1.1", "3")]
+        );
+        assert_eq!(
+            flat(
+                "{server = {host = 'web', ports = [80, 443]}, tags = [{name = 'a'}], empty = {}}",
+                "."
+            ),
+            vec![
+                entry("server.host", "web"),
+                entry("server.ports.0", "80"),
+                entry("server.ports.1", "443"),
+                entry("tags.0.name", "a"),
+                entry("empty", ""),
+            ]
+        );
+        assert_eq!(
+            flat("{a = {b = 1, c = [true]}}", "__"),
+            vec![entry("a__b", "1"), entry("a__c__0", "true")]
+        );
+        assert_eq!(flat("'plain'", "."), vec![entry("", "plain")]);
+
+        // Leaves keep their original data
+        let flattened = value("{a = {b = 1u8}}").flatten(".");
+        assert_eq!(flattened["a.b"].as_u8(), Some(&1));
+    }
+
+    #[test]
+    fn test_get() {
+        let port = Value::new_u32(8080, Metadata::default());
+        assert_eq!(port.get::<u32>().unwrap(), 8080);
+        let name = Value::new_string("app".into(), Metadata::default());
+        assert_eq!(name.get::<String>().unwrap(), "app");
+        let symbol = Value::new_symbol("ready".into(), Metadata::default());
+        assert_eq!(symbol.get::<String>().unwrap(), "ready");
+
+        assert!(matches!(
+            name.get::<u32>(),
+            Err(error::Error::ImplicitConvert {
+                left: ValueType::U32,
+                right: ValueType::String
+            })
+        ));
+        let int = Value::new_int(8080, Metadata::default());
+        assert!(int.get::<u32>().is_err());
+        assert_eq!(int.get::<i64>().unwrap(), 8080);
+    }
+
+    #[test]
+    fn test_satisfies() {
+        let meta = Metadata::new(Location::new(3, 4));
+        let version =
+            |x: &str| Value::new_version(semver::Version::parse(x).unwrap(), meta.clone());
+        let require =
+            |x: &str| Value::new_require(semver::VersionReq::parse(x).unwrap(), meta.clone());
+
+        assert!(version("1.4.2").satisfies(&require("^1.2")).unwrap());
+        assert!(!version("2.0.0").satisfies(&require("^1.2")).unwrap());
+        assert!(
+            !version("1.3.0-beta.1")
+                .satisfies(&require(">=1.2"))
+                .unwrap()
+        );
+
+        let string = Value::new_string("1.4.2".to_string(), meta.clone());
+        assert_eq!(
+            string.satisfies(&require("^1.2")),
+            Err(error::Error::Satisfies {
+                location: Location::new(3, 4),
+                version: ValueType::String,
+                require: ValueType::Require,
+            })
+        );
+        assert!(matches!(
+            version("1.4.2").satisfies(&version("1.4.2")),
+            Err(error::Error::Satisfies {
+                version: ValueType::Version,
+                require: ValueType::Version,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_value_builder() {
+        let meta = Metadata::default;
+        let built = TableBuilder::new()
+            .field("name", Value::new_string("app".to_string(), meta()))
+            .field(
+                "hosts",
+                ArrayBuilder::new()
+                    .push(Value::new_string("a".to_string(), meta()))
+                    .push(TableBuilder::new().field("port", Value::new_u16(80, meta()))),
+            )
+            .build();
+
+        let expected = Value::new_table(
+            IndexMap::from([
+                (
+                    "name".to_string(),
+                    Value::new_string("app".to_string(), meta()),
+                ),
+                (
+                    "hosts".to_string(),
+                    Value::new_array(
+                        vec![
+                            Value::new_string("a".to_string(), meta()),
+                            Value::new_table(
+                                IndexMap::from([("port".to_string(), Value::new_u16(80, meta()))]),
+                                meta(),
+                            ),
+                        ],
+                        meta(),
+                    ),
+                ),
+            ]),
+            meta(),
+        );
+        assert_eq!(built, expected);
+        assert_eq!(
+            built.type_of(),
+            crate::from_str("value = { name = 'app', hosts = ['a', { port = 80u16 }] }\n")
+                .unwrap()
+                .find_by_path("value")
+                .unwrap()
+                .get_value()
+                .unwrap()
+                .type_of()
+        );
+
+        // Later fields replace earlier ones and keep their position
+        let built: Value = TableBuilder::new()
+            .field("a", Value::new_int(1, meta()))
+            .field("b", Value::new_int(2, meta()))
+            .field("a", Value::new_int(3, meta()))
+            .into();
+        let table = built.as_table().unwrap();
+        assert_eq!(table.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(table["a"].as_int(), Some(&3));
+        assert_eq!(ArrayBuilder::new().build().as_array(), Some(&vec![]));
+    }
+
+    #[test]
+    fn test_merge_table() {
+        let merged = |left: &str, right: &str, deep: bool| {
+            let mut base = value(left);
+            base.merge_table(&value(right), deep).unwrap();
+            flat_entries(&base, ".")
+        };
+
+        // Shallow merges overwrite keys, replacing nested tables wholesale
+        assert_eq!(
+            merged(
+                "{host = 'web', db = {user = 'app', port = 5432}}",
+                "{port = 80, db = {port = 6432}}",
+                false
+            ),
+            vec![
+                entry("host", "web"),
+                entry("db.port", "6432"),
+                entry("port", "80"),
+            ]
+        );
+
+        // Deep merges recurse into tables present on both sides
+        assert_eq!(
+            merged(
+                "{host = 'web', db = {user = 'app', port = 5432, pool = {size = 4}}}",
+                "{db = {port = 6432, pool = {idle = 1}}, tags = ['a']}",
+                true
+            ),
+            vec![
+                entry("host", "web"),
+                entry("db.user", "app"),
+                entry("db.port", "6432"),
+                entry("db.pool.size", "4"),
+                entry("db.pool.idle", "1"),
+                entry("tags.0", "a"),
+            ]
+        );
+
+        // A table replaces a scalar even when merging deeply
+        assert_eq!(
+            merged("{db = 'none'}", "{db = {port = 1}}", true),
+            vec![entry("db.port", "1")]
+        );
+
+        let mut table = value("{a = 1}");
+        assert!(matches!(
+            table.merge_table(&value("[1]"), true),
+            Err(error::Error::Assign { .. })
+        ));
+        let mut scalar = value("1");
+        assert!(matches!(
+            scalar.merge_table(&value("{a = 1}"), false),
+            Err(error::Error::Assign { .. })
+        ));
+        assert_eq!(table.as_table().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_value_coerce_to() {
+        let meta = Metadata::new(Location::new(0, 0));
+        let coerce = |data: Data, ty: ValueType| {
+            Value::new(data, meta.clone())
+                .coerce_to(&ty)
+                .map(|x| x.data)
+        };
+
+        // Implicit conversions are still supported
+        assert_eq!(coerce(Data::I8(4), ValueType::I64).unwrap(), Data::I64(4));
+        // Integers convert between any precision within range
+        assert_eq!(
+            coerce(Data::Signed(-7), ValueType::I32).unwrap(),
+            Data::I32(-7)
+        );
+        assert_eq!(
+            coerce(Data::U64(200), ValueType::U8).unwrap(),
+            Data::U8(200)
+        );
+        assert_eq!(
+            coerce(Data::I16(9), ValueType::U128).unwrap(),
+            Data::U128(9)
+        );
+        // Integers and floats convert to every float precision
+        assert_eq!(
+            coerce(Data::Signed(3), ValueType::F64).unwrap(),
+            Data::F64(3.0)
+        );
+        assert_eq!(
+            coerce(Data::F64(1.5), ValueType::F32).unwrap(),
+            Data::F32(1.5)
+        );
+        assert_eq!(
+            coerce(Data::F32(2.5), ValueType::Float).unwrap(),
+            Data::Float(2.5)
+        );
+        // Strings parse into versions and requirements
+        assert_eq!(
+            coerce(Data::String("1.2.3".into()), ValueType::Version).unwrap(),
+            Data::Version(semver::Version::new(1, 2, 3))
+        );
+        assert_eq!(
+            coerce(Data::String("^1.2".into()), ValueType::Require).unwrap(),
+            Data::Require(semver::VersionReq::parse("^1.2").unwrap())
+        );
+        // Strings and bytes convert through UTF-8
+        assert_eq!(
+            coerce(Data::String("hi".into()), ValueType::Bytes).unwrap(),
+            Data::Bytes(b"hi".to_vec())
+        );
+        assert_eq!(
+            coerce(Data::Bytes(b"hi".to_vec()), ValueType::String).unwrap(),
+            Data::String("hi".into())
+        );
+        // Arrays convert element by element
+        assert_eq!(
+            coerce(
+                Data::Array(vec![Value::new_int(1, meta.clone())]),
+                ValueType::Array(vec![ValueType::U16])
+            )
+            .unwrap(),
+            Data::Array(vec![Value::new_u16(1, meta.clone())])
+        );
+
+        // Failures
+        assert!(matches!(
+            coerce(Data::Signed(300), ValueType::U8),
+            Err(error::Error::Coerce { .. })
+        ));
+        assert!(matches!(
+            coerce(Data::Signed(-1), ValueType::Unsigned),
+            Err(error::Error::Coerce { .. })
+        ));
+        assert!(matches!(
+            coerce(Data::F64(1e300), ValueType::F32),
+            Err(error::Error::Coerce { .. })
+        ));
+        assert!(matches!(
+            coerce(Data::String("one".into()), ValueType::Version),
+            Err(error::Error::Coerce { .. })
+        ));
+        assert!(matches!(
+            coerce(Data::Bytes(vec![0xff, 0xfe]), ValueType::String),
+            Err(error::Error::Coerce { .. })
+        ));
+        assert!(matches!(
+            coerce(Data::Bool(true), ValueType::I32),
+            Err(error::Error::ImplicitConvert { .. })
+        ));
+    }
 }
@@ -0,0 +1,210 @@
+use super::{Data, Statement, StatementData, StatementType, Value};
+
+/// A pass over a statement tree that may modify statements and values in place
+///
+/// Both methods walk into the children of the node they are given by default, so an
+/// implementation only overrides the nodes it is interested in. An override that should
+/// still reach the children of its node calls [`walk_statement`] or [`walk_value`].
+///
+/// Changing the type of a value held by an assignment does not update the type recorded
+/// on the statement, and changing an id or block label does not update the key the
+/// statement is stored under in its parent. Passes that do either should re-validate or
+/// rebuild the tree afterwards.
+pub trait StatementVisitor {
+    /// Visits a statement, walking its labels, value and children by default
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement);
+    }
+
+    /// Visits a value, walking the members of arrays and tables by default
+    fn visit_value(&mut self, value: &mut Value) {
+        walk_value(self, value);
+    }
+}
+
+/// Visits the labels, value and children of a statement in declaration order
+pub fn walk_statement<V: StatementVisitor + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match &mut statement.data {
+        StatementData::Single(value) => visitor.visit_value(value),
+        StatementData::Labeled(labels, children) => {
+            for label in labels.iter_mut() {
+                visitor.visit_value(label);
+            }
+            for child in children.values_mut() {
+                visitor.visit_statement(child);
+            }
+        }
+        StatementData::Group(children) => {
+            for child in children.values_mut() {
+                visitor.visit_statement(child);
+            }
+        }
+    }
+}
+
+/// Visits the members of an array or table value in order
+pub fn walk_value<V: StatementVisitor + ?Sized>(visitor: &mut V, value: &mut Value) {
+    match &mut value.data {
+        Data::Array(children) => {
+            for child in children.iter_mut() {
+                visitor.visit_value(child);
+            }
+        }
+        Data::Table(children) => {
+            for child in children.values_mut() {
+                visitor.visit_value(child);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Statement {
+    /// Runs a visitor over this statement and everything below it
+    pub fn accept<V: StatementVisitor>(&mut self, visitor: &mut V) {
+        visitor.visit_statement(self);
+    }
+
+    /// Applies a closure to every value held by this statement and the statements below it
+    ///
+    /// Each value is passed to the closure before the members of its arrays and tables, so
+    /// a closure that replaces a collection continues into the replacement. Block labels
+    /// are not visited, and the types recorded on statements are not updated.
+    pub fn map_values<F: FnMut(&mut Value)>(&mut self, mut f: F) {
+        self.map_values_with_path(|_, value| f(value));
+    }
+
+    /// Applies a closure to every value along with its path
+    ///
+    /// Paths are built like the paths of [`Statement::leaves`], from inject ids followed by
+    /// table keys and array indices joined with '.'.
+    pub fn map_values_with_path<F: FnMut(&str, &mut Value)>(&mut self, mut f: F) {
+        self.map_values_into(String::new(), &mut f);
+    }
+
+    fn map_values_into<F: FnMut(&str, &mut Value)>(&mut self, path: String, f: &mut F) {
+        let path = match self.type_ {
+            StatementType::Module(_) => path,
+            _ => Self::child_path(&path, &self.inject_id()),
+        };
+        match &mut self.data {
+            StatementData::Single(value) => map_value(value, path, f),
+            StatementData::Group(children) | StatementData::Labeled(_, children) => {
+                for child in children.values_mut() {
+                    child.map_values_into(path.clone(), f);
+                }
+            }
+        }
+    }
+}
+
+fn map_value<F: FnMut(&str, &mut Value)>(value: &mut Value, path: String, f: &mut F) {
+    f(&path, value);
+    match &mut value.data {
+        Data::Array(children) => {
+            for (index, child) in children.iter_mut().enumerate() {
+                map_value(child, Statement::child_path(&path, &index.to_string()), f);
+            }
+        }
+        Data::Table(children) => {
+            for (key, child) in children.iter_mut() {
+                map_value(child, Statement::child_path(&path, key), f);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Uppercase;
+
+    impl StatementVisitor for Uppercase {
+        fn visit_value(&mut self, value: &mut Value) {
+            if let Data::String(text) = &mut value.data {
+                *text = text.to_uppercase();
+            }
+            walk_value(self, value);
+        }
+    }
+
+    #[test]
+    fn test_statement_visitor() {
+        let mut module = crate::from_str(
+            "name = 'app'\n\
+             port = 80\n\
+             tags = ['web', ['nested']]\n\
+             owner = { team = 'ops', contact = { email = 'ops@example.com' } }\n\
+             listener 'http' {\n  scheme = 'tcp'\n}\n\
+             [server]\n\
+             host = 'localhost'\n",
+        )
+        .unwrap();
+        module.accept(&mut Uppercase);
+
+        let expected = crate::from_str(
+            "name = 'APP'\n\
+             port = 80\n\
+             tags = ['WEB', ['NESTED']]\n\
+             owner = { team = 'OPS', contact = { email = 'OPS@EXAMPLE.COM' } }\n\
+             listener 'HTTP' {\n  scheme = 'TCP'\n}\n\
+             [server]\n\
+             host = 'LOCALHOST'\n",
+        )
+        .unwrap();
+        assert_eq!(module.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_map_values() {
+        let source = "password = 'hunter2'\n\
+                      db = { user = 'admin', admin_password = 'secret', port = 5432 }\n\
+                      tokens = [' a ', 'b ']\n\
+                      [auth]\n\
+                      password = 'swordfish'\n\
+                      password_rounds = 12\n";
+        let mut module = crate::from_str(source).unwrap();
+
+        let mut paths = Vec::new();
+        module.map_values_with_path(|path, value| {
+            paths.push(path.to_string());
+            if path.contains("password") && matches!(value.data, Data::String(_)) {
+                value.data = Data::String("***".to_string());
+            }
+        });
+        assert_eq!(
+            paths,
+            vec![
+                "password",
+                "db",
+                "db.user",
+                "db.admin_password",
+                "db.port",
+                "tokens",
+                "tokens.0",
+                "tokens.1",
+                "auth.password",
+                "auth.password_rounds",
+            ]
+        );
+
+        module.map_values(|value| {
+            if let Data::String(text) = &mut value.data {
+                *text = text.trim().to_string();
+            }
+        });
+
+        let expected = crate::from_str(
+            "password = '***'\n\
+             db = { user = 'admin', admin_password = '***', port = 5432 }\n\
+             tokens = ['a', 'b']\n\
+             [auth]\n\
+             password = '***'\n\
+             password_rounds = 12\n",
+        )
+        .unwrap();
+        assert_eq!(module.to_string(), expected.to_string());
+    }
+}
@@ -8,14 +8,16 @@
 //! - `error`: Custom error types for deserialization failures
 //! - `statement`: Deserializer implementation for `Statement` types
 //! - `value`: Deserializer implementation for `Value` types
+//! - `semver`: Field helpers keeping semantic versions and requirements as BarkML types
 
 // External crates
-use serde::de::Deserialize;
+use serde::de::{Deserialize, DeserializeSeed};
 
 // Local crate
 use crate::{Result, Statement, Value};
 
 pub(crate) mod error;
+pub mod semver;
 mod statement;
 mod value;
 
@@ -27,6 +29,18 @@ pub use value::*;
 /// This is the main entry point for deserializing Rust data structures from BarkML statements.
 /// It supports deserializing from module, section, block, and assignment statements.
 ///
+/// Self-describing targets such as `serde_json::Value` or `IndexMap<String, serde_json::Value>`
+/// receive a module as a map in document order. Assignments become their scalar, array or
+/// table value, sections become nested maps and blocks become nested maps keyed by their
+/// injection id (the block id followed by its labels, such as `server.web`). Control
+/// statements are included under their id without the leading `$`.
+///
+/// Struct fields are matched against child ids. A field with no child of the same id
+/// collects every labeled block with that id, in document order, so blocks such as
+/// `server 'a' {}` and `server 'b' {}` fill a `server: Vec<Server>` field or a map keyed
+/// by their labels. A block read as a struct exposes its first label under a `label` field
+/// and all of its labels under a `labels` field, unless it has children with those ids.
+///
 /// # Errors
 ///
 /// Returns an error if the statement structure doesn't match the expected type `T` or if
@@ -58,6 +72,39 @@ where
     Ok(result)
 }
 
+/// Deserialize a BarkML `Statement` using a `DeserializeSeed`.
+///
+/// This is useful when the target type is only known at runtime or when deserialization
+/// needs access to external state, such as a registry of types.
+///
+/// # Errors
+///
+/// Returns an error if the seed rejects the structure or values of the statement.
+pub fn from_statement_seed<'de, S>(statement: &Statement, seed: S) -> Result<S::Value>
+where
+    S: DeserializeSeed<'de>,
+{
+    let deserializer = StatementDeserializer::new(statement);
+    let result = seed.deserialize(deserializer)?;
+    Ok(result)
+}
+
+/// Deserialize a BarkML `Value` using a `DeserializeSeed`.
+///
+/// This is the value counterpart to `from_statement_seed`.
+///
+/// # Errors
+///
+/// Returns an error if the seed rejects the type or contents of the value.
+pub fn from_value_seed<'de, S>(value: &Value, seed: S) -> Result<S::Value>
+where
+    S: DeserializeSeed<'de>,
+{
+    let deserializer = ValueDeserializer::new(value);
+    let result = seed.deserialize(deserializer)?;
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     // External crates
@@ -210,6 +257,337 @@ mod tests {
         assert_eq!(result.age, 30);
     }
 
+    #[test]
+    fn deserialize_any_typed_statements_works_correctly() {
+        // Arrange
+        let barkml = r#"
+        timeout: any = 30
+        values: array[any] = [1, "two", true]
+        "#;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct DynamicConfig {
+            timeout: f64,
+            values: serde_json::Value,
+        }
+
+        // Act
+        let statement = from_str(barkml).expect("should parse BarkML");
+        let config: DynamicConfig = from_statement(&statement).expect("should deserialize config");
+
+        // Assert
+        assert_eq!(config.timeout, 30.0);
+        assert_eq!(config.values, serde_json::json!([1, "two", true]));
+    }
+
+    #[test]
+    fn deserialize_optional_typed_statements_works_correctly() {
+        // Arrange
+        let barkml = r#"
+        host: string? = null
+        user: string? = "admin"
+        "#;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct OptionalConfig {
+            host: Option<String>,
+            user: Option<String>,
+        }
+
+        // Act
+        let statement = from_str(barkml).expect("should parse BarkML");
+        let config: OptionalConfig = from_statement(&statement).expect("should deserialize config");
+
+        // Assert
+        assert_eq!(
+            config,
+            OptionalConfig {
+                host: None,
+                user: Some("admin".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_integers_into_floats_works_correctly() {
+        // Arrange
+        let barkml = r#"
+        ratio = 1
+        small = -3i8
+        large = 18446744073709551615u64
+        single = 2
+        exact = 0.5
+        "#;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct FloatConfig {
+            ratio: f64,
+            small: f64,
+            large: f64,
+            single: f32,
+            exact: f64,
+        }
+
+        // Act
+        let statement = from_str(barkml).expect("should parse BarkML");
+        let config: FloatConfig = from_statement(&statement).expect("should deserialize config");
+
+        // Assert
+        assert_eq!(
+            config,
+            FloatConfig {
+                ratio: 1.0,
+                small: -3.0,
+                large: u64::MAX as f64,
+                single: 2.0,
+                exact: 0.5,
+            }
+        );
+
+        let statement = from_str("ratio = 'one'").expect("should parse BarkML");
+        assert!(from_statement::<std::collections::HashMap<String, f64>>(&statement).is_err());
+    }
+
+    #[test]
+    fn deserialize_unsuffixed_integers_into_unsigned_works_correctly() {
+        // Arrange
+        let barkml = r#"
+        count = 5
+        port = 8080
+        flags = 255
+        total = 9223372036854775807
+        "#;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct UnsignedConfig {
+            count: u32,
+            port: u16,
+            flags: u8,
+            total: u64,
+        }
+
+        // Act
+        let statement = from_str(barkml).expect("should parse BarkML");
+        let config: UnsignedConfig = from_statement(&statement).expect("should deserialize config");
+
+        // Assert
+        assert_eq!(
+            config,
+            UnsignedConfig {
+                count: 5,
+                port: 8080,
+                flags: 255,
+                total: i64::MAX as u64,
+            }
+        );
+
+        let meta = Metadata::new(Location::new(0, 0));
+        let negative = Value::new_int(-1, meta.clone());
+        let error = from_value::<u32>(&negative).expect_err("negative values should not fit");
+        assert_eq!(error.to_string(), "invalid value '-1' for type u32");
+        let overflow = Value::new_int(256, meta);
+        assert!(from_value::<u8>(&overflow).is_err());
+        assert_eq!(
+            from_value::<u128>(&Value::new_int(7, Metadata::default())).unwrap(),
+            7
+        );
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn deserialize_decimal_statements_works_correctly() {
+        // Arrange
+        let barkml = r#"
+        price = 0.1d
+        total: decimal = 1.50d
+        "#;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct DecimalConfig {
+            price: rust_decimal::Decimal,
+            total: String,
+        }
+
+        // Act
+        let statement = from_str(barkml).expect("should parse BarkML");
+        let config: DecimalConfig = from_statement(&statement).expect("should deserialize config");
+
+        // Assert
+        assert_eq!(
+            config,
+            DecimalConfig {
+                price: "0.1".parse().unwrap(),
+                total: "1.50".to_string(),
+            }
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn deserialize_network_statements_works_correctly() {
+        // Arrange
+        let barkml = r#"
+        address = 10.0.0.1
+        gateway = fe80::1
+        subnet = 10.0.0.0/8
+        "#;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct NetworkConfig {
+            address: std::net::IpAddr,
+            gateway: std::net::Ipv6Addr,
+            subnet: String,
+        }
+
+        // Act
+        let statement = from_str(barkml).expect("should parse BarkML");
+        let config: NetworkConfig = from_statement(&statement).expect("should deserialize config");
+
+        // Assert
+        assert_eq!(
+            config,
+            NetworkConfig {
+                address: std::net::IpAddr::from([10, 0, 0, 1]),
+                gateway: "fe80::1".parse().unwrap(),
+                subnet: "10.0.0.0/8".to_string(),
+            }
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn deserialize_regex_statements_works_correctly() {
+        // Arrange
+        let barkml = r#"
+        sources = r'^src/.*\.rs$'
+        ignored = [r"^target/", r'\.bak$']
+        "#;
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct MatchConfig {
+            sources: String,
+            ignored: Vec<String>,
+        }
+
+        // Act
+        let statement = from_str(barkml).expect("should parse BarkML");
+        let config: MatchConfig = from_statement(&statement).expect("should deserialize config");
+
+        // Assert
+        assert_eq!(
+            config,
+            MatchConfig {
+                sources: r"^src/.*\.rs$".to_string(),
+                ignored: vec!["^target/".to_string(), r"\.bak$".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_module_into_generic_map_works_correctly() {
+        // Arrange
+        use crate::Loader;
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/simple.bml");
+        let module = crate::StandardLoader::default()
+            .add_file(&path)
+            .expect("should read example")
+            .load()
+            .expect("should load example");
+        let blocks = from_str("listener 'http' 80 { tls = false }\n[server]\nworkers = 4\n")
+            .expect("should parse BarkML");
+
+        // Act
+        let config: IndexMap<String, serde_json::Value> =
+            from_statement(&module).expect("should deserialize module");
+        let generic: serde_json::Value =
+            from_statement(&blocks).expect("should deserialize blocks");
+
+        // Assert
+        assert_eq!(config.keys().collect::<Vec<_>>(), vec!["tire", "section-1"]);
+        assert_eq!(config["tire"], serde_json::json!("1.0.0"));
+        let section = config["section-1"]
+            .as_object()
+            .expect("section should be a map");
+        assert_eq!(section["number"], serde_json::json!(4));
+        assert_eq!(section["floating"], serde_json::json!(3.14f32 as f64));
+        assert_eq!(section["versioning"], serde_json::json!("1.2.3-beta.6"));
+        assert_eq!(section["requires"], serde_json::json!("^1.3.3"));
+        assert_eq!(section["strings"], serde_json::json!("hello world"));
+
+        assert_eq!(
+            generic,
+            serde_json::json!({
+                "listener.http.80": { "tls": false },
+                "server": { "workers": 4 }
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_blocks_by_label_works_correctly() {
+        // Arrange
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Server {
+            #[serde(rename = "label")]
+            name: String,
+            port: i64,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Listener {
+            labels: Vec<String>,
+            tls: bool,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Named {
+            server: IndexMap<String, Server>,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Config {
+            server: Vec<Server>,
+            listener: Listener,
+            workers: i64,
+        }
+
+        let barkml = r#"
+        server "a" { port = 80 }
+        workers = 4
+        server "b" { port = 8080 }
+        listener "http" "public" { tls = false }
+        "#;
+
+        // Act
+        let statement = from_str(barkml).expect("should parse BarkML");
+        let config: Config = from_statement(&statement).expect("should deserialize config");
+        let named: Named = from_statement(&statement).expect("should deserialize named servers");
+        let by_label: IndexMap<String, IndexMap<String, Server>> =
+            from_statement(&from_str("[servers]\nserver 'a' { port = 80 }\n").unwrap())
+                .expect("should deserialize map of servers");
+
+        // Assert
+        assert_eq!(
+            config.server,
+            vec![
+                Server {
+                    name: "a".to_string(),
+                    port: 80
+                },
+                Server {
+                    name: "b".to_string(),
+                    port: 8080
+                }
+            ]
+        );
+        assert_eq!(config.listener.labels, vec!["http", "public"]);
+        assert!(!config.listener.tls);
+        assert_eq!(config.workers, 4);
+        assert_eq!(named.server.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(named.server["b"].port, 8080);
+        assert_eq!(by_label["servers"]["server.a"].name, "a");
+    }
+
     #[test]
     fn error_handling_works_correctly() {
         // Arrange
@@ -222,4 +600,64 @@ mod tests {
         // Assert
         assert!(result.is_err());
     }
+
+    /// Seed that appends every string of a list to a shared buffer and returns the count
+    struct CollectSeed<'a>(&'a mut Vec<String>);
+
+    impl<'de> serde::de::DeserializeSeed<'de> for CollectSeed<'_> {
+        type Value = usize;
+
+        fn deserialize<D>(self, deserializer: D) -> std::result::Result<usize, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_seq(self)
+        }
+    }
+
+    impl<'de> serde::de::Visitor<'de> for CollectSeed<'_> {
+        type Value = usize;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a list of strings")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> std::result::Result<usize, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            let mut count = 0;
+            while let Some(item) = seq.next_element::<String>()? {
+                self.0.push(item);
+                count += 1;
+            }
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn deserialize_with_seed_works_correctly() {
+        // Arrange
+        let statement =
+            from_str("hosts = ['alpha', 'beta']\nports = [80]\n").expect("should parse BarkML");
+        let hosts = statement.find_child("hosts").expect("should find hosts");
+        let mut collected = vec!["existing".to_string()];
+
+        // Act
+        let count = from_statement_seed(hosts, CollectSeed(&mut collected))
+            .expect("should deserialize with seed");
+        let value_count = from_value_seed(hosts.get_value().unwrap(), CollectSeed(&mut collected))
+            .expect("should deserialize value with seed");
+        let ports = statement.find_child("ports").expect("should find ports");
+        let result = from_statement_seed(ports, CollectSeed(&mut collected));
+
+        // Assert
+        assert_eq!(count, 2);
+        assert_eq!(value_count, 2);
+        assert!(result.is_err());
+        assert_eq!(
+            collected,
+            vec!["existing", "alpha", "beta", "alpha", "beta"]
+        );
+    }
 }
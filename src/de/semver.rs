@@ -0,0 +1,182 @@
+//! Helpers for semantic version fields.
+//!
+//! Fields of type `semver::Version` and `semver::VersionReq` deserialize from BarkML
+//! versions and requirements without any attributes, as both are read as their string form.
+//! They serialize as plain strings though, so a document written from them holds
+//! `'1.2.3'` instead of `1.2.3`. Annotating the field with [`serialize_with`] and
+//! [`deserialize_with`] keeps the BarkML types in both directions while other data formats
+//! still see a string:
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Package {
+//!     #[serde(
+//!         serialize_with = "barkml::de::semver::serialize_with",
+//!         deserialize_with = "barkml::de::semver::deserialize_with"
+//!     )]
+//!     release: semver::Version,
+//!     #[serde(
+//!         serialize_with = "barkml::de::semver::serialize_with",
+//!         deserialize_with = "barkml::de::semver::deserialize_with"
+//!     )]
+//!     compatible: semver::VersionReq,
+//! }
+//! ```
+
+// Standard library
+use std::fmt;
+use std::marker::PhantomData;
+
+// External crates
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::Serializer;
+
+// Local crate
+use crate::interop::semver::{REQUIRE_MARKER, VERSION_MARKER};
+
+/// Semantic version types supported by [`serialize_with`] and [`deserialize_with`]
+pub trait SemverType: Sized + fmt::Display {
+    /// Newtype name used to pass the type through to the BarkML serializer
+    const MARKER: &'static str;
+
+    /// Parses the type from its string form
+    fn parse(input: &str) -> Result<Self, String>;
+}
+
+impl SemverType for ::semver::Version {
+    const MARKER: &'static str = VERSION_MARKER;
+
+    fn parse(input: &str) -> Result<Self, String> {
+        Self::parse(input).map_err(|e| e.to_string())
+    }
+}
+
+impl SemverType for ::semver::VersionReq {
+    const MARKER: &'static str = REQUIRE_MARKER;
+
+    fn parse(input: &str) -> Result<Self, String> {
+        Self::parse(input).map_err(|e| e.to_string())
+    }
+}
+
+/// Serializes a version or requirement as the matching BarkML type
+///
+/// # Errors
+///
+/// Returns an error if the serializer fails.
+pub fn serialize_with<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: SemverType,
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(T::MARKER, &value.to_string())
+}
+
+/// Deserializes a version or requirement from a BarkML version, requirement or string
+///
+/// # Errors
+///
+/// Returns an error if the input is not a string or does not parse as the target type.
+pub fn deserialize_with<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: SemverType,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(T::MARKER, SemverVisitor(PhantomData))
+}
+
+struct SemverVisitor<T>(PhantomData<T>);
+
+impl<'de, T: SemverType> Visitor<'de> for SemverVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a semantic version or version requirement")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        T::parse(v).map_err(E::custom)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::de::from_statement;
+    use crate::ser::to_statement;
+    use crate::{Data, from_str};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Plain {
+        release: ::semver::Version,
+        compatible: ::semver::VersionReq,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Package {
+        #[serde(
+            serialize_with = "crate::de::semver::serialize_with",
+            deserialize_with = "crate::de::semver::deserialize_with"
+        )]
+        release: ::semver::Version,
+        #[serde(
+            serialize_with = "crate::de::semver::serialize_with",
+            deserialize_with = "crate::de::semver::deserialize_with"
+        )]
+        compatible: ::semver::VersionReq,
+    }
+
+    #[test]
+    fn test_semver_fields() {
+        let module = from_str("release = 1.2.3\ncompatible = ^1.2\n").unwrap();
+        let version = ::semver::Version::new(1, 2, 3);
+        let compatible = ::semver::VersionReq::parse("^1.2").unwrap();
+
+        // Both forms read versions and requirements
+        let plain: Plain = from_statement(&module).unwrap();
+        assert_eq!(plain.release, version);
+        assert_eq!(plain.compatible, compatible);
+        let package: Package = from_statement(&module).unwrap();
+        assert_eq!(package.release, version);
+        assert_eq!(package.compatible, compatible);
+
+        // Only the helper writes them back as versions and requirements
+        let statement = to_statement(&plain).unwrap();
+        let data = |path: &str| {
+            statement
+                .find_by_path(path)
+                .and_then(|x| x.get_value())
+                .map(|x| x.data.clone())
+        };
+        assert!(matches!(data("release"), Some(Data::String(_))));
+        assert_eq!(from_statement::<Plain>(&statement).unwrap(), plain);
+
+        let statement = to_statement(&package).unwrap();
+        let data = |path: &str| {
+            statement
+                .find_by_path(path)
+                .and_then(|x| x.get_value())
+                .map(|x| x.data.clone())
+        };
+        assert_eq!(data("release"), Some(Data::Version(version)));
+        assert_eq!(data("compatible"), Some(Data::Require(compatible)));
+        assert_eq!(from_statement::<Package>(&statement).unwrap(), package);
+
+        // The printed document reads back into the same package
+        let reparsed = from_str(&statement.to_string()).unwrap();
+        assert_eq!(from_statement::<Package>(&reparsed).unwrap(), package);
+
+        // Strings are still accepted and must parse
+        let module = from_str("release = '2.0.0'\ncompatible = '>=1'\n").unwrap();
+        let package: Package = from_statement(&module).unwrap();
+        assert_eq!(package.release, ::semver::Version::new(2, 0, 0));
+        let module = from_str("release = 'latest'\ncompatible = '>=1'\n").unwrap();
+        assert!(from_statement::<Package>(&module).is_err());
+    }
+}
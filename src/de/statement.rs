@@ -9,7 +9,7 @@ use super::ValueDeserializer;
 use super::error::{self, Result};
 
 // Local crate
-use crate::{Statement, StatementData};
+use crate::{Statement, StatementData, Value, ValueType};
 
 /// Deserializer for BarkML statements.
 pub struct StatementDeserializer<'a> {
@@ -21,6 +21,27 @@ impl<'a> StatementDeserializer<'a> {
     pub fn new(statement: &'a Statement) -> Self {
         Self { statement }
     }
+
+    /// Whether the statement was declared with the `any` type, in which case
+    /// its value is deserialized from its data rather than the requested type
+    fn is_any(&self) -> bool {
+        matches!(self.statement.type_.value_type(), Some(ValueType::Any))
+    }
+
+    /// Deserializes the value of a single statement with the given method, or with
+    /// `deserialize_any` when the statement was declared with the `any` type
+    fn forward<'de, V, F>(&self, value: &'a Value, visitor: V, deserialize: F) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+        F: FnOnce(ValueDeserializer<'a>, V) -> Result<V::Value>,
+    {
+        let value_deserializer = ValueDeserializer::new(value);
+        if self.is_any() {
+            value_deserializer.deserialize_any(visitor)
+        } else {
+            deserialize(value_deserializer, visitor)
+        }
+    }
 }
 
 impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
@@ -48,8 +69,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_bool(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_bool)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "bool",
@@ -65,8 +85,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_i8(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_i8)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "i8",
@@ -82,8 +101,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_i16(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_i16)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "i16",
@@ -99,8 +117,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_i32(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_i32)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "i32",
@@ -116,8 +133,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_i64(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_i64)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "i64",
@@ -133,8 +149,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_i128(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_i128)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "i128",
@@ -150,8 +165,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_u8(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_u8)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "u8",
@@ -167,8 +181,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_u16(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_u16)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "u16",
@@ -184,8 +197,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_u32(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_u32)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "u32",
@@ -201,8 +213,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_u64(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_u64)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "u64",
@@ -218,8 +229,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_u128(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_u128)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "u128",
@@ -235,8 +245,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_f32(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_f32)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "f32",
@@ -252,8 +261,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_f64(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_f64)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "f64",
@@ -286,8 +294,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_str(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_str)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "str",
@@ -310,8 +317,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_bytes(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_bytes)
             }
             _ => error::TypeMismatchSnafu {
                 expected: "bytes",
@@ -378,8 +384,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_seq(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_seq)
             }
             StatementData::Group(children) | StatementData::Labeled(_, children) => {
                 let seq = StatementSeqAccess::new(children);
@@ -413,8 +418,7 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     {
         match &self.statement.data {
             StatementData::Single(value) => {
-                let value_deserializer = ValueDeserializer::new(value);
-                value_deserializer.deserialize_map(visitor)
+                self.forward(value, visitor, ValueDeserializer::deserialize_map)
             }
             StatementData::Group(children) | StatementData::Labeled(_, children) => {
                 let map = StatementMapAccess::new(children);
@@ -426,13 +430,21 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_map(visitor)
+        match &self.statement.data {
+            StatementData::Group(children) => {
+                visitor.visit_map(StatementMapAccess::for_struct(children, &[], fields))
+            }
+            StatementData::Labeled(labels, children) => {
+                visitor.visit_map(StatementMapAccess::for_struct(children, labels, fields))
+            }
+            StatementData::Single(_) => self.deserialize_map(visitor),
+        }
     }
 
     fn deserialize_enum<V>(
@@ -479,16 +491,89 @@ impl<'de, 'a> Deserializer<'de> for StatementDeserializer<'a> {
     }
 }
 
+/// Field name under which a block deserialized as a struct exposes its first label
+const LABEL_FIELD: &str = "label";
+
+/// Field name under which a block deserialized as a struct exposes all of its labels
+const LABELS_FIELD: &str = "labels";
+
+/// Entry presented by a statement map access
+enum MapEntry<'a> {
+    /// A child statement under its injection id
+    Statement(&'a Statement),
+    /// Blocks sharing an id, presented under that id
+    Blocks(Vec<&'a Statement>),
+    /// The first label of the block being deserialized
+    Label(&'a Value),
+    /// All labels of the block being deserialized
+    Labels(&'a [Value]),
+}
+
 /// Map access for statement groups
 struct StatementMapAccess<'a> {
-    iter: indexmap::map::Iter<'a, String, Statement>,
-    value: Option<&'a Statement>,
+    iter: std::vec::IntoIter<(&'a str, MapEntry<'a>)>,
+    value: Option<MapEntry<'a>>,
 }
 
 impl<'a> StatementMapAccess<'a> {
     fn new(children: &'a indexmap::IndexMap<String, Statement>) -> Self {
+        let entries = children
+            .iter()
+            .map(|(key, child)| (key.as_str(), MapEntry::Statement(child)))
+            .collect::<Vec<_>>();
+        Self {
+            iter: entries.into_iter(),
+            value: None,
+        }
+    }
+
+    /// Creates a map access for deserializing a struct with the given fields
+    ///
+    /// A field without a child of the same key collects every labeled block whose id is the
+    /// field name, so `server 'a' {}` and `server 'b' {}` can fill a `server: Vec<_>` field.
+    /// Blocks also expose their labels under the `label` and `labels` fields when no child
+    /// uses those keys.
+    fn for_struct(
+        children: &'a indexmap::IndexMap<String, Statement>,
+        labels: &'a [Value],
+        fields: &'static [&'static str],
+    ) -> Self {
+        let mut entries: Vec<(&'a str, MapEntry<'a>)> = Vec::with_capacity(children.len());
+        for (key, child) in children {
+            let field = fields.iter().find(|field| {
+                **field == child.id
+                    && !children.contains_key(**field)
+                    && child
+                        .get_labeled()
+                        .is_some_and(|(labels, _)| !labels.is_empty())
+            });
+            let Some(field) = field else {
+                entries.push((key.as_str(), MapEntry::Statement(child)));
+                continue;
+            };
+
+            // Blocks are grouped at the position of the first block with their id
+            let group = entries.iter_mut().find_map(|(id, entry)| match entry {
+                MapEntry::Blocks(blocks) if id == field => Some(blocks),
+                _ => None,
+            });
+            match group {
+                Some(blocks) => blocks.push(child),
+                None => entries.push((*field, MapEntry::Blocks(vec![child]))),
+            }
+        }
+
+        if !labels.is_empty() {
+            if fields.contains(&LABEL_FIELD) && !children.contains_key(LABEL_FIELD) {
+                entries.push((LABEL_FIELD, MapEntry::Label(&labels[0])));
+            }
+            if fields.contains(&LABELS_FIELD) && !children.contains_key(LABELS_FIELD) {
+                entries.push((LABELS_FIELD, MapEntry::Labels(labels)));
+            }
+        }
+
         Self {
-            iter: children.iter(),
+            iter: entries.into_iter(),
             value: None,
         }
     }
@@ -502,9 +587,9 @@ impl<'de, 'a> MapAccess<'de> for StatementMapAccess<'a> {
         K: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some((key, statement)) => {
-                self.value = Some(statement);
-                seed.deserialize(key.as_str().into_deserializer()).map(Some)
+            Some((key, entry)) => {
+                self.value = Some(entry);
+                seed.deserialize(key.into_deserializer()).map(Some)
             }
             None => Ok(None),
         }
@@ -515,9 +600,14 @@ impl<'de, 'a> MapAccess<'de> for StatementMapAccess<'a> {
         V: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(statement) => {
-                let deserializer = StatementDeserializer::new(statement);
-                seed.deserialize(deserializer)
+            Some(MapEntry::Statement(statement)) => {
+                seed.deserialize(StatementDeserializer::new(statement))
+            }
+            Some(MapEntry::Blocks(blocks)) => seed.deserialize(BlocksDeserializer::new(blocks)),
+            Some(MapEntry::Label(label)) => seed.deserialize(ValueDeserializer::new(label)),
+            Some(MapEntry::Labels(labels)) => {
+                let labels = Value::new_array(labels.to_vec(), Default::default());
+                seed.deserialize(ValueDeserializer::new(&labels))
             }
             None => error::MessageSnafu {
                 message: "value is missing".to_string(),
@@ -557,6 +647,146 @@ impl<'de, 'a> SeqAccess<'de> for StatementSeqAccess<'a> {
     }
 }
 
+/// Deserializer for several blocks sharing an id
+///
+/// The blocks are read as a sequence in document order, or as a map keyed by their labels
+/// joined with '.'. A single block can also be read directly as a struct.
+struct BlocksDeserializer<'a> {
+    blocks: Vec<&'a Statement>,
+}
+
+impl<'a> BlocksDeserializer<'a> {
+    fn new(blocks: Vec<&'a Statement>) -> Self {
+        Self { blocks }
+    }
+}
+
+impl<'de, 'a> Deserializer<'de> for BlocksDeserializer<'a> {
+    type Error = error::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(BlocksAccess::new(self.blocks))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(BlocksAccess::new(self.blocks))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.blocks.as_slice() {
+            [block] => StatementDeserializer::new(block).deserialize_struct(name, fields, visitor),
+            blocks => error::TypeMismatchSnafu {
+                expected: "a single block",
+                found: format!("{} blocks", blocks.len()),
+            }
+            .fail(),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct enum identifier ignored_any
+    }
+}
+
+/// Sequence and map access over blocks sharing an id
+struct BlocksAccess<'a> {
+    iter: std::vec::IntoIter<&'a Statement>,
+    value: Option<&'a Statement>,
+}
+
+impl<'a> BlocksAccess<'a> {
+    fn new(blocks: Vec<&'a Statement>) -> Self {
+        Self {
+            iter: blocks.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de, 'a> SeqAccess<'de> for BlocksAccess<'a> {
+    type Error = error::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(block) => seed
+                .deserialize(StatementDeserializer::new(block))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+impl<'de, 'a> MapAccess<'de> for BlocksAccess<'a> {
+    type Error = error::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(block) => {
+                self.value = Some(block);
+                let key = block.inject_id();
+                let key = key
+                    .strip_prefix(&format!("{}.", block.id))
+                    .unwrap_or(&key)
+                    .to_string();
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(block) => seed.deserialize(StatementDeserializer::new(block)),
+            None => error::MessageSnafu {
+                message: "value is missing".to_string(),
+            }
+            .fail(),
+        }
+    }
+}
+
 /// Enum access for statement-based enums
 struct StatementEnumAccess<'a> {
     variant: &'a str,
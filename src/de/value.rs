@@ -3,6 +3,7 @@
 // External crates
 use serde::Deserializer;
 use serde::de::{self, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use snafu::ensure;
 
 // Parent module
 use super::error::{self, Result};
@@ -20,6 +21,34 @@ impl<'a> ValueDeserializer<'a> {
     pub fn new(value: &'a Value) -> Self {
         Self { value }
     }
+
+    /// Widens an integer value to a float for the float type named by `expected`
+    ///
+    /// Integers are widened since configs commonly write `1` for a float, any other
+    /// value is a type mismatch.
+    fn widen_integer(&self, expected: &'static str) -> Result<f64> {
+        ensure!(
+            self.value.type_of().is_integer(),
+            error::TypeMismatchSnafu {
+                expected,
+                found: self.value.type_of().to_string(),
+            }
+        );
+        Ok(self.value.data.float_value().unwrap_or_default())
+    }
+}
+
+/// Converts an integer written without a suffix to an unsigned integer type
+///
+/// Integers without a suffix are signed, they fit when not negative and in range.
+fn unsigned<T: TryFrom<i64>>(n: i64, expected_type: &str) -> Result<T> {
+    T::try_from(n).map_err(|_| {
+        error::InvalidValueSnafu {
+            value: n.to_string(),
+            expected_type,
+        }
+        .build()
+    })
 }
 
 impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
@@ -59,6 +88,14 @@ impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
             Data::Bytes(bytes) => visitor.visit_bytes(bytes),
             Data::Version(version) => visitor.visit_str(&version.to_string()),
             Data::Require(req) => visitor.visit_str(&req.to_string()),
+            #[cfg(feature = "datetime")]
+            Data::DateTime(_) => visitor.visit_str(&self.value.to_macro_string()),
+            #[cfg(feature = "decimal")]
+            Data::Decimal(_) => visitor.visit_str(&self.value.to_macro_string()),
+            #[cfg(feature = "net")]
+            Data::Ip(_) | Data::Cidr(_) => visitor.visit_str(&self.value.to_macro_string()),
+            #[cfg(feature = "regex")]
+            Data::Regex(pattern) => visitor.visit_str(pattern.as_str()),
             Data::Macro(macro_ref) => visitor.visit_str(macro_ref),
             Data::Symbol(symbol) => visitor.visit_str(symbol),
         }
@@ -210,6 +247,7 @@ impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
                     .fail()
                 }
             }
+            Data::Signed(n) => visitor.visit_u8(unsigned(*n, "u8")?),
             _ => error::TypeMismatchSnafu {
                 expected: "u8",
                 found: self.value.type_of().to_string(),
@@ -236,6 +274,7 @@ impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
                     .fail()
                 }
             }
+            Data::Signed(n) => visitor.visit_u16(unsigned(*n, "u16")?),
             _ => error::TypeMismatchSnafu {
                 expected: "u16",
                 found: self.value.type_of().to_string(),
@@ -263,6 +302,7 @@ impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
                     .fail()
                 }
             }
+            Data::Signed(n) => visitor.visit_u32(unsigned(*n, "u32")?),
             _ => error::TypeMismatchSnafu {
                 expected: "u32",
                 found: self.value.type_of().to_string(),
@@ -281,6 +321,7 @@ impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
             Data::U16(n) => visitor.visit_u64(u64::from(*n)),
             Data::U32(n) => visitor.visit_u64(u64::from(*n)),
             Data::Unsigned(n) => visitor.visit_u64(*n),
+            Data::Signed(n) => visitor.visit_u64(unsigned(*n, "u64")?),
             _ => error::TypeMismatchSnafu {
                 expected: "u64",
                 found: self.value.type_of().to_string(),
@@ -300,6 +341,7 @@ impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
             Data::U32(n) => visitor.visit_u128(u128::from(*n)),
             Data::U64(n) => visitor.visit_u128(u128::from(*n)),
             Data::Unsigned(n) => visitor.visit_u128(u128::from(*n)),
+            Data::Signed(n) => visitor.visit_u128(unsigned(*n, "u128")?),
             _ => error::TypeMismatchSnafu {
                 expected: "u128",
                 found: self.value.type_of().to_string(),
@@ -315,11 +357,7 @@ impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
         match &self.value.data {
             Data::F32(n) => visitor.visit_f32(*n),
             Data::Float(n) => visitor.visit_f32(*n as f32),
-            _ => error::TypeMismatchSnafu {
-                expected: "f32",
-                found: self.value.type_of().to_string(),
-            }
-            .fail(),
+            _ => visitor.visit_f32(self.widen_integer("f32")? as f32),
         }
     }
 
@@ -331,11 +369,7 @@ impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
             Data::F64(n) => visitor.visit_f64(*n),
             Data::F32(n) => visitor.visit_f64(f64::from(*n)),
             Data::Float(n) => visitor.visit_f64(*n),
-            _ => error::TypeMismatchSnafu {
-                expected: "f64",
-                found: self.value.type_of().to_string(),
-            }
-            .fail(),
+            _ => visitor.visit_f64(self.widen_integer("f64")?),
         }
     }
 
@@ -371,6 +405,14 @@ impl<'de, 'a> Deserializer<'de> for ValueDeserializer<'a> {
             Data::String(s) => visitor.visit_str(s),
             Data::Version(v) => visitor.visit_str(&v.to_string()),
             Data::Require(v) => visitor.visit_str(&v.to_string()),
+            #[cfg(feature = "datetime")]
+            Data::DateTime(_) => visitor.visit_str(&self.value.to_macro_string()),
+            #[cfg(feature = "decimal")]
+            Data::Decimal(_) => visitor.visit_str(&self.value.to_macro_string()),
+            #[cfg(feature = "net")]
+            Data::Ip(_) | Data::Cidr(_) => visitor.visit_str(&self.value.to_macro_string()),
+            #[cfg(feature = "regex")]
+            Data::Regex(pattern) => visitor.visit_str(pattern.as_str()),
             Data::Macro(m) => visitor.visit_str(m),
             Data::Symbol(s) => visitor.visit_str(s),
             _ => error::TypeMismatchSnafu {
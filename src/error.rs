@@ -15,7 +15,7 @@ use snafu::Snafu;
 // Local crate imports
 use crate::{
     Token,
-    ast::{Location, ValueType},
+    ast::{Collision, Location, ValueType},
 };
 
 /// Comprehensive error type for all BarkML operations.
@@ -41,8 +41,10 @@ pub enum Error {
         location: Location,
         source: base64::DecodeError,
     },
+    #[snafu(display("binary (de)serialization failed: {reason}"))]
+    Bincode { reason: String },
     #[snafu(display(
-        "name collision between {left_id} ({left_location}) and {right_id} ({right_location})"
+        "name collision: {left_id} defined at {left_location}, {right_id} redefined at {right_location}"
     ))]
     Collision {
         left_id: String,
@@ -50,10 +52,55 @@ pub enum Error {
         right_id: String,
         right_location: Location,
     },
+    #[snafu(display("type error: cannot coerce a value of type '{right}' to '{left}': {reason}"))]
+    Coerce {
+        left: ValueType,
+        right: ValueType,
+        reason: String,
+    },
+    #[snafu(display(
+        "found {} name collisions: {}",
+        collisions.len(),
+        collisions
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))]
+    Collisions { collisions: Vec<Collision> },
+    #[snafu(display("{location} - invalid datetime: {reason}"))]
+    DateTime { location: Location, reason: String },
+    #[snafu(display("{location} - invalid decimal: {reason}"))]
+    Decimal { location: Location, reason: String },
     #[snafu(transparent)]
     Deserialize { source: crate::de::error::Error },
+    #[snafu(display(
+        "{location} - duplicate block '{id}' with the same labels as an earlier block"
+    ))]
+    DuplicateBlock { id: String, location: Location },
+    #[snafu(display(
+        "{second_location} - duplicate key '{key}', first declared at {first_location}"
+    ))]
+    DuplicateKey {
+        key: String,
+        first_location: Location,
+        second_location: Location,
+    },
+    #[snafu(display(
+        "{location} - misplaced document separator: '---' must be on a line of its own"
+    ))]
+    DocumentSeparator { location: Location },
     #[snafu(display("{location} - unexpected end of file"))]
     Eof { location: Location },
+    #[snafu(display(
+        "{location} - symbol ':{symbol}' is not one of the allowed symbols: {}",
+        allowed.iter().map(|x| format!(":{x}")).collect::<Vec<_>>().join(", ")
+    ))]
+    EnumSymbol {
+        location: Location,
+        symbol: String,
+        allowed: Vec<String>,
+    },
     #[snafu(display("{location} - syntax error: expected {expected}, found {got}\n{context}"))]
     Expected {
         location: Location,
@@ -66,8 +113,17 @@ pub enum Error {
         location: Location,
         source: ParseFloatError,
     },
+    #[snafu(display("{location} - invalid hex encoding: {reason}"))]
+    Hex { location: Location, reason: String },
     #[snafu(display("type error: implicit conversion from '{left}' to '{right}' is not allowed"))]
     ImplicitConvert { left: ValueType, right: ValueType },
+    #[snafu(display("{location} - invalid network address: {reason}"))]
+    Ip { location: Location, reason: String },
+    #[snafu(display(
+        "{location} - include cycle detected: '{}' is already being loaded",
+        path.display()
+    ))]
+    IncludeCycle { location: Location, path: PathBuf },
     #[snafu(display("{location} - invalid integer: {source}"))]
     Integer {
         location: Location,
@@ -75,8 +131,34 @@ pub enum Error {
     },
     #[snafu(display("i/o error occurred during loading: {reason}"))]
     Io { reason: String },
+    #[snafu(display(
+        "{location} - invalid line continuation: '\\' must be followed by a line break"
+    ))]
+    LineContinuation { location: Location },
     #[snafu(display("{location} - infinite loop detected during macro resolution"))]
     Loop { location: Location },
+    #[snafu(display(
+        "invalid macro delimiters: opening '{open}' and closing '{close}' must not be empty"
+    ))]
+    MacroDelimiters { open: String, close: String },
+    #[snafu(display("{location} - invalid arithmetic '{expression}' in macro: {reason}"))]
+    MacroArithmetic {
+        location: Location,
+        expression: String,
+        reason: String,
+    },
+    #[snafu(display(
+        "{location} - macro resolution failed: condition '{condition}' must be a bool, found '{found}'"
+    ))]
+    MacroCondition {
+        location: Location,
+        condition: String,
+        found: ValueType,
+    },
+    #[snafu(display("{location} - macros are forbidden in this configuration"))]
+    MacrosForbidden { location: Location },
+    #[snafu(display("{location} - nesting limit exceeded: maximum depth of {limit} reached"))]
+    NestingLimit { location: Location, limit: usize },
     #[snafu(display("{location} - array index out of bounds: no element at index {index}"))]
     NoElement { location: Location, index: usize },
     #[snafu(display("{location} - field not found: '{field}'"))]
@@ -87,18 +169,43 @@ pub enum Error {
         "{location} - macro resolution failed: could not locate value at path '{path}'"
     ))]
     NoMacro { location: Location, path: String },
+    #[snafu(display(
+        "{location} - macro resolution failed: could not resolve '{{{segment}}}' in '{input}'"
+    ))]
+    NoMacroSegment {
+        location: Location,
+        segment: String,
+        input: String,
+    },
     #[snafu(display(
         "missing main module: the standard loader requires at least one main module to load"
     ))]
     NoMain,
+    #[snafu(display("{location} - symbol ':{symbol}' does not reference an existing path"))]
+    NoSymbol { location: Location, symbol: String },
     #[snafu(display("file not found: '{}'", path.display()))]
     NotFound { path: PathBuf },
     #[snafu(display("{location} - not a scope with fields"))]
     NotScope { location: Location },
+    #[snafu(display("failed to parse '{filename}': {source}"))]
+    Parse {
+        filename: String,
+        source: Box<Error>,
+    },
     #[snafu(display("{location} - recursion limit exceeded: maximum depth of {limit} reached"))]
     RecursionLimit { location: Location, limit: usize },
+    #[snafu(display("{location} - invalid regular expression: {reason}"))]
+    Regex { location: Location, reason: String },
     #[snafu(display("{location} - invalid semantic version requirement: {reason}"))]
     Require { location: Location, reason: String },
+    #[snafu(display(
+        "{location} - type error: cannot check whether a '{version}' satisfies a '{require}', expected a version and a requirement"
+    ))]
+    Satisfies {
+        location: Location,
+        version: ValueType,
+        require: ValueType,
+    },
     #[snafu(display("module not found: could not find file named {name}.bml or directory named {name}.d in any of these paths:\n{}", search_paths.iter().map(|x| x.to_string_lossy().to_string()).collect::<Vec<_>>().join("\n")))]
     Search {
         name: String,
@@ -106,9 +213,227 @@ pub enum Error {
     },
     #[snafu(transparent)]
     Serialize { source: crate::ser::error::Error },
+    #[snafu(display("{location} - cannot convert to toml: {reason}"))]
+    Toml { location: Location, reason: String },
+    #[snafu(display("{location} - unexpected end of file, expected {expected}"))]
+    UnexpectedEof {
+        location: Location,
+        expected: String,
+    },
     #[snafu(display("unknown error occurred"))]
     #[default]
     Unknown,
     #[snafu(display("{location} - invalid semantic version: {reason}"))]
     Version { location: Location, reason: String },
 }
+
+/// Broad classification of an [`Error`], see [`Error::category`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Malformed input such as unexpected tokens or invalid literals
+    Syntax,
+    /// A value does not have or cannot be converted to the expected type
+    Type,
+    /// A macro could not be resolved
+    Macro,
+    /// Files or modules could not be found or read
+    Io,
+    /// Statements collided while being merged or declared
+    Merge,
+    /// A field or element looked up in a loaded document does not exist
+    Lookup,
+    /// A nesting or recursion limit was exceeded
+    Limit,
+    /// Conversion between BarkML and Rust data structures failed
+    Serde,
+    /// Anything else
+    Other,
+}
+
+impl Error {
+    /// Classifies this error, parse errors are classified by the error that caused them
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::Base64 { .. }
+            | Self::DateTime { .. }
+            | Self::Decimal { .. }
+            | Self::DocumentSeparator { .. }
+            | Self::Eof { .. }
+            | Self::Expected { .. }
+            | Self::Float { .. }
+            | Self::Hex { .. }
+            | Self::Integer { .. }
+            | Self::Ip { .. }
+            | Self::LineContinuation { .. }
+            | Self::Regex { .. }
+            | Self::Require { .. }
+            | Self::UnexpectedEof { .. }
+            | Self::Version { .. } => ErrorCategory::Syntax,
+            Self::Assign { .. }
+            | Self::Coerce { .. }
+            | Self::EnumSymbol { .. }
+            | Self::ImplicitConvert { .. }
+            | Self::Satisfies { .. }
+            | Self::Toml { .. } => ErrorCategory::Type,
+            Self::Loop { .. }
+            | Self::MacroArithmetic { .. }
+            | Self::MacroCondition { .. }
+            | Self::MacroDelimiters { .. }
+            | Self::MacrosForbidden { .. }
+            | Self::NoMacro { .. }
+            | Self::NoMacroSegment { .. } => ErrorCategory::Macro,
+            Self::Basename
+            | Self::IncludeCycle { .. }
+            | Self::Io { .. }
+            | Self::NoMain
+            | Self::NotFound { .. }
+            | Self::Search { .. } => ErrorCategory::Io,
+            Self::Collision { .. }
+            | Self::Collisions { .. }
+            | Self::DuplicateBlock { .. }
+            | Self::DuplicateKey { .. } => ErrorCategory::Merge,
+            Self::NoElement { .. }
+            | Self::NoField { .. }
+            | Self::NoSymbol { .. }
+            | Self::NoValue { .. }
+            | Self::NotScope { .. } => ErrorCategory::Lookup,
+            Self::NestingLimit { .. } | Self::RecursionLimit { .. } => ErrorCategory::Limit,
+            Self::Bincode { .. } | Self::Deserialize { .. } | Self::Serialize { .. } => {
+                ErrorCategory::Serde
+            }
+            Self::Parse { source, .. } => source.category(),
+            Self::Unknown => ErrorCategory::Other,
+        }
+    }
+
+    /// Location the error is reported at, if it carries a single location
+    pub(crate) fn location_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            Self::Assign { location, .. }
+            | Self::Base64 { location, .. }
+            | Self::DateTime { location, .. }
+            | Self::Decimal { location, .. }
+            | Self::DocumentSeparator { location }
+            | Self::DuplicateBlock { location, .. }
+            | Self::Eof { location }
+            | Self::EnumSymbol { location, .. }
+            | Self::Expected { location, .. }
+            | Self::Float { location, .. }
+            | Self::Hex { location, .. }
+            | Self::Ip { location, .. }
+            | Self::IncludeCycle { location, .. }
+            | Self::Integer { location, .. }
+            | Self::LineContinuation { location }
+            | Self::Loop { location }
+            | Self::MacroArithmetic { location, .. }
+            | Self::MacroCondition { location, .. }
+            | Self::MacrosForbidden { location }
+            | Self::NestingLimit { location, .. }
+            | Self::NoElement { location, .. }
+            | Self::NoField { location, .. }
+            | Self::NoValue { location, .. }
+            | Self::NoMacro { location, .. }
+            | Self::NoMacroSegment { location, .. }
+            | Self::NoSymbol { location, .. }
+            | Self::NotScope { location }
+            | Self::RecursionLimit { location, .. }
+            | Self::Regex { location, .. }
+            | Self::Require { location, .. }
+            | Self::Satisfies { location, .. }
+            | Self::Toml { location, .. }
+            | Self::UnexpectedEof { location, .. }
+            | Self::Version { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the error only concerns a single value or statement
+    ///
+    /// Type, macro, merge and lookup errors leave the rest of the document usable, so a
+    /// tool can report them and keep going. Syntax, i/o and limit errors stop a document
+    /// from being read at all.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self.category(),
+            ErrorCategory::Type
+                | ErrorCategory::Macro
+                | ErrorCategory::Merge
+                | ErrorCategory::Lookup
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    #[test]
+    fn test_error_category() {
+        let location = Location::new(1, 2);
+        for (error, category, recoverable) in [
+            (
+                Error::Eof {
+                    location: location.clone(),
+                },
+                ErrorCategory::Syntax,
+                false,
+            ),
+            (
+                Error::ImplicitConvert {
+                    left: ValueType::String,
+                    right: ValueType::Bool,
+                },
+                ErrorCategory::Type,
+                true,
+            ),
+            (
+                Error::Loop {
+                    location: location.clone(),
+                },
+                ErrorCategory::Macro,
+                true,
+            ),
+            (
+                Error::Io {
+                    reason: "denied".to_string(),
+                },
+                ErrorCategory::Io,
+                false,
+            ),
+            (
+                Error::Collisions { collisions: vec![] },
+                ErrorCategory::Merge,
+                true,
+            ),
+            (
+                Error::NoField {
+                    location: location.clone(),
+                    field: "port".to_string(),
+                },
+                ErrorCategory::Lookup,
+                true,
+            ),
+            (
+                Error::RecursionLimit {
+                    location,
+                    limit: 100,
+                },
+                ErrorCategory::Limit,
+                false,
+            ),
+            (Error::Unknown, ErrorCategory::Other, false),
+        ] {
+            assert_eq!(error.category(), category, "{error}");
+            assert_eq!(error.is_recoverable(), recoverable, "{error}");
+        }
+
+        // Parse errors take the category of their cause
+        let error = from_str("name = ").unwrap_err();
+        assert!(matches!(error, Error::Parse { .. }));
+        assert_eq!(error.category(), ErrorCategory::Syntax);
+        let error = from_str("value = m!missing").unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::Macro);
+        assert!(error.is_recoverable());
+    }
+}
@@ -0,0 +1,478 @@
+//! Canonical formatting of BarkML source
+//!
+//! The formatter parses a source without resolving macros or includes and writes it back
+//! in a stable layout. Comments, labels, include directives and explicit type annotations
+//! are kept, while spacing, indentation and quoting are normalized.
+
+// External crates
+use logos::Logos;
+
+// Local modules
+use crate::ast::{Data, Metadata, Statement, StatementData, StatementType, Value, quote_key};
+use crate::syn::{Parser, Token};
+use crate::{Error, Result};
+
+/// Options controlling the layout written by [`format`]
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// Number of spaces used for each level of block and collection nesting
+    pub indent: usize,
+    /// Whether the `=` of consecutive assignments is aligned into one column
+    pub align_assignments: bool,
+    /// Whether every assignment is written with a type annotation, not only those
+    /// annotated in the source
+    pub explicit_types: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            align_assignments: true,
+            explicit_types: false,
+        }
+    }
+}
+
+/// Formats a BarkML source into its canonical layout.
+///
+/// Macros are written as found and includes are not read, so a source can be formatted
+/// on its own. Top level assignments are written ahead of sections, every section and
+/// block is set apart by a blank line and `#` comments stay line comments.
+///
+/// # Examples
+///
+/// ```rust
+/// use barkml::{FormatOptions, format};
+///
+/// let formatted = format("[server]\nhost='localhost'\n# The port\nport  =  80", FormatOptions::default())
+///     .expect("Failed to format BarkML");
+/// assert_eq!(formatted, "[server]\nhost = 'localhost'\n# The port\nport = 80\n");
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error if the input contains invalid BarkML syntax.
+pub fn format(input: &str, options: FormatOptions) -> Result<String> {
+    let mut parser = Parser::new("main", Token::lexer(input));
+    let module = parser.parse().map_err(|e| Error::Parse {
+        filename: "main".to_string(),
+        source: Box::new(e),
+    })?;
+
+    let mut writer = Writer {
+        input,
+        options: &options,
+        output: String::new(),
+    };
+    writer.module(&module, parser.includes(), parser.trailing_metadata());
+    Ok(writer.output)
+}
+
+struct Writer<'a> {
+    input: &'a str,
+    options: &'a FormatOptions,
+    output: String,
+}
+
+impl Writer<'_> {
+    fn module(
+        &mut self,
+        module: &Statement,
+        includes: &[(String, crate::Location)],
+        trailing: Option<&Metadata>,
+    ) {
+        self.comment(&module.meta, 0);
+        for (path, _) in includes {
+            self.output.push_str("include ");
+            self.output.push_str(&quote_string(path));
+            self.output.push('\n');
+        }
+
+        let (sections, statements): (Vec<_>, Vec<_>) = module
+            .children()
+            .partition(|x| matches!(x.type_, StatementType::Section(_)));
+        self.separate();
+        self.statements(&statements, 0);
+        for section in sections {
+            self.separate();
+            self.comment(&section.meta, 0);
+            self.label(&section.meta, 0);
+            self.output.push('[');
+            self.output.push_str(&quote_key(&section.id));
+            self.output.push_str("]\n");
+            self.statements(&section.children().collect::<Vec<_>>(), 0);
+        }
+
+        if let Some(meta) = trailing {
+            self.separate();
+            self.comment(meta, 0);
+        }
+    }
+
+    /// Starts a blank line unless the output is empty or already ends with one
+    fn separate(&mut self) {
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+
+    fn statements(&mut self, statements: &[&Statement], depth: usize) {
+        // Runs of assignments between blocks share their `=` column
+        let mut width = 0;
+        for (i, statement) in statements.iter().enumerate() {
+            let is_block = matches!(statement.type_, StatementType::Block { .. });
+            if i > 0 && (is_block || matches!(statements[i - 1].type_, StatementType::Block { .. }))
+            {
+                self.separate();
+            }
+            if is_block {
+                self.block(statement, depth);
+                continue;
+            }
+            if i == 0 || matches!(statements[i - 1].type_, StatementType::Block { .. }) {
+                width = statements[i..]
+                    .iter()
+                    .take_while(|x| !matches!(x.type_, StatementType::Block { .. }))
+                    .map(|x| self.target(x).chars().count())
+                    .max()
+                    .unwrap_or_default();
+            }
+            self.assignment(statement, width, depth);
+        }
+    }
+
+    /// Returns the left hand side of an assignment, including its type annotation
+    fn target(&self, statement: &Statement) -> String {
+        let (prefix, type_) = match &statement.type_ {
+            StatementType::Control(type_) => ("$", type_),
+            StatementType::Assignment(type_) | StatementType::Append(type_) => ("", type_),
+            _ => return String::new(),
+        };
+        let mut target = format!("{}{}", prefix, quote_key(&statement.id));
+        let annotated = self.options.explicit_types
+            || statement.get_value().is_some_and(|x| x.type_of() != *type_)
+            || self
+                .input
+                .get(statement.meta.location.byte_start + statement.meta.location.length..)
+                .is_some_and(|x| x.trim_start().starts_with(':'));
+        if annotated {
+            target.push_str(": ");
+            target.push_str(&type_.to_annotation());
+        }
+        target
+    }
+
+    fn assignment(&mut self, statement: &Statement, width: usize, depth: usize) {
+        self.comment(&statement.meta, depth);
+        self.label(&statement.meta, depth);
+        let target = self.target(statement);
+        self.indent(depth);
+        self.output.push_str(&target);
+        if self.options.align_assignments {
+            let padding = width.saturating_sub(target.chars().count());
+            self.output.push_str(&" ".repeat(padding));
+        }
+        if matches!(statement.type_, StatementType::Append(_)) {
+            self.output.push_str(" += ");
+        } else {
+            self.output.push_str(" = ");
+        }
+        if let Some(value) = statement.get_value() {
+            let value = self.value(value, depth);
+            self.output.push_str(&value);
+        }
+        self.output.push('\n');
+    }
+
+    fn block(&mut self, statement: &Statement, depth: usize) {
+        let StatementData::Labeled(labels, children) = &statement.data else {
+            return;
+        };
+        self.comment(&statement.meta, depth);
+        self.label(&statement.meta, depth);
+        self.indent(depth);
+        self.output.push_str(&quote_key(&statement.id));
+        for label in labels {
+            let label = self.value(label, depth);
+            self.output.push(' ');
+            self.output.push_str(&label);
+        }
+        if children.is_empty() {
+            self.output.push_str(" {}\n");
+            return;
+        }
+        self.output.push_str(" {\n");
+        self.statements(&children.values().collect::<Vec<_>>(), depth + 1);
+        self.indent(depth);
+        self.output.push_str("}\n");
+    }
+
+    fn indent(&mut self, depth: usize) {
+        self.output
+            .push_str(&" ".repeat(depth * self.options.indent));
+    }
+
+    fn comment(&mut self, meta: &Metadata, depth: usize) {
+        let Some(comment) = meta.comment.as_ref() else {
+            return;
+        };
+        if meta.line_comment {
+            for line in comment.lines() {
+                self.indent(depth);
+                self.output.push('#');
+                if !line.is_empty() {
+                    self.output.push(' ');
+                    self.output.push_str(line);
+                }
+                self.output.push('\n');
+            }
+        } else {
+            self.indent(depth);
+            self.output.push_str("/*\n");
+            self.output.push_str(comment);
+            self.output.push('\n');
+            self.indent(depth);
+            self.output.push_str("*/\n");
+        }
+    }
+
+    fn label(&mut self, meta: &Metadata, depth: usize) {
+        if let Some(label) = meta.label.as_ref() {
+            self.indent(depth);
+            self.output.push('!');
+            self.output.push_str(label);
+            self.output.push('\n');
+        }
+    }
+
+    /// Renders a value with its label, comments of values nested in collections are
+    /// written on their own lines
+    fn value(&self, value: &Value, depth: usize) -> String {
+        let mut text = String::new();
+        if let Some(comment) = value.meta.comment.as_ref() {
+            text.push_str(&format!("/* {} */ ", comment));
+        }
+        text.push_str(&self.element(value, depth));
+        text
+    }
+
+    fn element(&self, value: &Value, depth: usize) -> String {
+        let mut text = String::new();
+        if let Some(label) = value.meta.label.as_ref() {
+            text.push('!');
+            text.push_str(label);
+            text.push(' ');
+        }
+        let inner = " ".repeat((depth + 1) * self.options.indent);
+        let outer = " ".repeat(depth * self.options.indent);
+        match &value.data {
+            Data::String(value) => text.push_str(&quote_string(value)),
+            Data::Macro(value) if is_macro_path(value) => {
+                text.push_str("m!");
+                text.push_str(value);
+            }
+            Data::Macro(value) => {
+                text.push('m');
+                text.push_str(&quote_string(value));
+            }
+            Data::Array(values) if values.is_empty() => text.push_str("[]"),
+            Data::Array(values) if !values.iter().any(is_multiline) => {
+                let values = values
+                    .iter()
+                    .map(|x| self.element(x, depth))
+                    .collect::<Vec<_>>();
+                text.push_str(&format!("[{}]", values.join(", ")));
+            }
+            Data::Array(values) => {
+                text.push_str("[\n");
+                for value in values {
+                    text.push_str(&self.nested_comment(&value.meta, &inner));
+                    text.push_str(&inner);
+                    text.push_str(&self.element(value, depth + 1));
+                    text.push_str(",\n");
+                }
+                text.push_str(&outer);
+                text.push(']');
+            }
+            Data::Table(values) if values.is_empty() => text.push_str("{}"),
+            Data::Table(values) => {
+                let width = values
+                    .keys()
+                    .map(|x| quote_key(x).chars().count())
+                    .max()
+                    .unwrap_or_default();
+                text.push_str("{\n");
+                for (key, value) in values {
+                    let key = quote_key(key);
+                    text.push_str(&self.nested_comment(&value.meta, &inner));
+                    text.push_str(&inner);
+                    text.push_str(&key);
+                    if self.options.align_assignments {
+                        text.push_str(&" ".repeat(width - key.chars().count()));
+                    }
+                    text.push_str(" = ");
+                    text.push_str(&self.element(value, depth + 1));
+                    text.push('\n');
+                }
+                text.push_str(&outer);
+                text.push('}');
+            }
+            _ => {
+                let scalar = Value {
+                    uid: value.uid,
+                    data: value.data.clone(),
+                    meta: Metadata::default(),
+                };
+                text.push_str(&scalar.to_string());
+            }
+        }
+        text
+    }
+
+    fn nested_comment(&self, meta: &Metadata, indent: &str) -> String {
+        let Some(comment) = meta.comment.as_ref() else {
+            return String::new();
+        };
+        if meta.line_comment {
+            comment
+                .lines()
+                .map(|x| format!("{indent}# {x}\n").replace("# \n", "#\n"))
+                .collect()
+        } else {
+            format!("{indent}/*\n{comment}\n{indent}*/\n")
+        }
+    }
+}
+
+/// Returns true if a collection member forces its collection onto several lines
+fn is_multiline(value: &Value) -> bool {
+    value.meta.has_comment()
+        || match &value.data {
+            Data::Array(values) => !values.is_empty(),
+            Data::Table(values) => !values.is_empty(),
+            _ => false,
+        }
+}
+
+/// Returns true if a macro can be written in its unquoted `m!path` form
+fn is_macro_path(value: &str) -> bool {
+    let path = value.strip_prefix('@').unwrap_or(value);
+    let mut chars = path.chars();
+    chars.next().is_some_and(|x| x.is_ascii_alphabetic())
+        && chars.all(|x| x.is_ascii_alphanumeric() || matches!(x, '_' | '-' | '.'))
+        && !path.ends_with('.')
+}
+
+/// Quotes a string, preferring single quotes unless the string contains one
+fn quote_string(value: &str) -> String {
+    if value.contains('\'') {
+        format!("\"{value}\"")
+    } else {
+        format!("'{value}'")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_simple() {
+        let input = include_str!("../examples/simple.bml");
+        let expected = include_str!("../examples/simple.formatted.bml");
+        let formatted = format(input, FormatOptions::default()).unwrap();
+        assert_eq!(formatted, expected);
+
+        // Formatting is stable and keeps the data
+        assert_eq!(
+            format(expected, FormatOptions::default()).unwrap(),
+            expected
+        );
+        let original = crate::from_str(input).unwrap();
+        let reparsed = crate::from_str(&formatted).unwrap();
+        assert_eq!(reparsed.to_string(), original.to_string());
+    }
+
+    #[test]
+    fn test_format_layout() {
+        let input = "/* Service settings */\n\
+                     include 'base.bml'\n\
+                     [server]\n\
+                     host='localhost'\n\
+                     # Listening port\n\
+                     port: int = 8080\n\
+                     tags=[ 'web',\n'api' ]\n\
+                     # The listener\n\
+                     !Primary\n\
+                     listener \"http\" { scheme = 'tcp'\n nested 1 {} }\n\
+                     limits = { max = 10, 'rate limit' = m!server.port }\n\
+                     # about the next section\n\
+                     [cache]\n\
+                     hosts = [\n# first\n'a',\n[1, 2]\n]\n\
+                     # trailing\n";
+        let expected = "/*\nService settings\n*/\n\
+                        include 'base.bml'\n\
+                        \n\
+                        [server]\n\
+                        host      = 'localhost'\n\
+                        # Listening port\n\
+                        port: int = 8080\n\
+                        tags      = ['web', 'api']\n\
+                        \n\
+                        # The listener\n\
+                        !Primary\n\
+                        listener 'http' {\n\
+                        \x20 scheme = 'tcp'\n\
+                        \n\
+                        \x20 nested 1 {}\n\
+                        }\n\
+                        \n\
+                        limits = {\n\
+                        \x20 max          = 10\n\
+                        \x20 'rate limit' = m!server.port\n\
+                        }\n\
+                        \n\
+                        # about the next section\n\
+                        [cache]\n\
+                        hosts = [\n\
+                        \x20 # first\n\
+                        \x20 'a',\n\
+                        \x20 [1, 2],\n\
+                        ]\n\
+                        \n\
+                        # trailing\n";
+        let formatted = format(input, FormatOptions::default()).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(
+            format(expected, FormatOptions::default()).unwrap(),
+            expected
+        );
+
+        // Annotations can be forced and alignment turned off
+        let options = FormatOptions {
+            indent: 4,
+            align_assignments: false,
+            explicit_types: true,
+        };
+        let formatted = format("a = 1\nlong_name = 'x'\nb 'c' { d = true }", options).unwrap();
+        assert_eq!(
+            formatted,
+            "a: int = 1\nlong_name: string = 'x'\n\nb 'c' {\n    d: bool = true\n}\n"
+        );
+
+        // Appends keep their operator
+        assert_eq!(
+            format("tags  +=[ 'web' ]", FormatOptions::default()).unwrap(),
+            "tags += ['web']\n"
+        );
+    }
+
+    #[test]
+    fn test_format_invalid() {
+        assert!(matches!(
+            format("a = ", FormatOptions::default()),
+            Err(Error::Parse { .. })
+        ));
+    }
+}
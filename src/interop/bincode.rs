@@ -0,0 +1,79 @@
+use crate::ast::Statement;
+use crate::{Result, error};
+
+impl Statement {
+    /// Encodes this statement and its children into a compact binary form
+    ///
+    /// The encoding keeps the uid, type and metadata of every statement and value, which
+    /// makes it suitable for caching parsed documents. Bytes can only be decoded by a build
+    /// of this crate with the same value features enabled.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        ::bincode::serde::encode_to_vec(self, ::bincode::config::standard()).map_err(|e| {
+            error::Error::Bincode {
+                reason: e.to_string(),
+            }
+        })
+    }
+
+    /// Decodes a statement previously encoded with [`Statement::to_bytes`]
+    ///
+    /// Fails when the bytes are not a complete encoding of a single statement.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Statement> {
+        let (statement, read) =
+            ::bincode::serde::decode_from_slice(bytes, ::bincode::config::standard()).map_err(
+                |e| error::Error::Bincode {
+                    reason: e.to_string(),
+                },
+            )?;
+        if read != bytes.len() {
+            return error::BincodeSnafu {
+                reason: format!("{} trailing bytes", bytes.len() - read),
+            }
+            .fail();
+        }
+        Ok(statement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn round_trip() {
+        let module = crate::from_str(include_str!("../../examples/simple.bml")).unwrap();
+        let bytes = module.to_bytes().unwrap();
+        let decoded = Statement::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded, module);
+        assert_eq!(decoded.uid, module.uid);
+        assert_eq!(decoded.type_, module.type_);
+        assert_eq!(decoded.meta, module.meta);
+        let uids = |statement: &Statement| statement.children().map(|x| x.uid).collect::<Vec<_>>();
+        assert_eq!(uids(&decoded), uids(&module));
+        assert_eq!(decoded.to_string(), module.to_string());
+    }
+
+    #[cfg(all(feature = "datetime", feature = "decimal"))]
+    #[test]
+    fn round_trip_text_values() {
+        let module = crate::from_str("price = 1.50d\nreleased = 2024-01-02T03:04:05Z\n").unwrap();
+        let decoded = Statement::from_bytes(&module.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, module);
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let mut bytes = crate::from_str("a = 1").unwrap().to_bytes().unwrap();
+        bytes.push(0);
+        assert_matches!(
+            Statement::from_bytes(&bytes),
+            Err(error::Error::Bincode { .. })
+        );
+        assert_matches!(
+            Statement::from_bytes(&bytes[..bytes.len() / 2]),
+            Err(error::Error::Bincode { .. })
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::ast::{Data, Location, Metadata, Statement, StatementData, StatementType, Value};
+use serde_json::Number;
+
+impl Value {
+    /// Builds a value from a JSON value
+    ///
+    /// Objects become tables and arrays become arrays. Numbers are read as `int` when they
+    /// fit in a signed 64-bit integer, as `uint` when they only fit in an unsigned 64-bit
+    /// integer and as `float` otherwise.
+    pub fn from_json(value: serde_json::Value) -> Value {
+        let data = match value {
+            serde_json::Value::Null => Data::Null,
+            serde_json::Value::Bool(value) => Data::Bool(value),
+            serde_json::Value::Number(number) => {
+                if let Some(value) = number.as_i64() {
+                    Data::Signed(value)
+                } else if let Some(value) = number.as_u64() {
+                    Data::Unsigned(value)
+                } else {
+                    Data::Float(number.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            serde_json::Value::String(value) => Data::String(value),
+            serde_json::Value::Array(values) => {
+                Data::Array(values.into_iter().map(Value::from_json).collect())
+            }
+            serde_json::Value::Object(children) => Data::Table(
+                children
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from_json(value)))
+                    .collect(),
+            ),
+        };
+        Value::new(data, Metadata::new(Location::new(0, 0)))
+    }
+
+    /// Converts this value into a JSON value
+    ///
+    /// Values without a JSON equivalent are written as strings: bytes as standard base64,
+    /// versions, requirements, datetimes, symbols and macros in their textual form. 128-bit
+    /// integers outside of the 64-bit range are written as strings as well, and non-finite
+    /// floats become null.
+    pub fn to_json(&self) -> serde_json::Value {
+        let integer = |value: i128| {
+            i64::try_from(value)
+                .map(Number::from)
+                .or_else(|_| u64::try_from(value).map(Number::from))
+                .map(serde_json::Value::Number)
+                .unwrap_or_else(|_| serde_json::Value::String(value.to_string()))
+        };
+        let float = |value: f64| {
+            Number::from_f64(value)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null)
+        };
+
+        match &self.data {
+            Data::Null => serde_json::Value::Null,
+            Data::Bool(value) => serde_json::Value::Bool(*value),
+            Data::Signed(value) | Data::I64(value) => serde_json::Value::from(*value),
+            Data::I8(value) => serde_json::Value::from(*value),
+            Data::I16(value) => serde_json::Value::from(*value),
+            Data::I32(value) => serde_json::Value::from(*value),
+            Data::I128(value) => integer(*value),
+            Data::Unsigned(value) | Data::U64(value) => serde_json::Value::from(*value),
+            Data::U8(value) => serde_json::Value::from(*value),
+            Data::U16(value) => serde_json::Value::from(*value),
+            Data::U32(value) => serde_json::Value::from(*value),
+            Data::U128(value) => match i128::try_from(*value) {
+                Ok(value) => integer(value),
+                Err(_) => serde_json::Value::String(value.to_string()),
+            },
+            Data::Float(value) | Data::F64(value) => float(*value),
+            Data::F32(value) => float(f64::from(*value)),
+            Data::Array(values) => {
+                serde_json::Value::Array(values.iter().map(Value::to_json).collect())
+            }
+            Data::Table(children) => serde_json::Value::Object(
+                children
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_json()))
+                    .collect(),
+            ),
+            _ => serde_json::Value::String(self.to_macro_string()),
+        }
+    }
+}
+
+impl Statement {
+    /// Converts this statement and its children into JSON nodes carrying their source spans
+    ///
+    /// Unlike the `Serialize` implementation, the shape is meant for tools such as editors and
+    /// stays stable. Every node is an object with:
+    ///
+    /// - `id`: the statement id
+    /// - `type`: one of `module`, `section`, `block`, `assignment`, `append` or `control`
+    /// - `span`: an object with the 0-based `line` and `column`, the `length` in bytes and the
+    ///   `file` path the statement was read from, or null for statements not read from a file
+    ///
+    /// Assignments, appends and controls add `value_type`, the declared type written like a
+    /// type annotation, and `value`, the value converted with [`Value::to_json`]. Blocks add
+    /// `labels` holding their label values, and modules, sections and blocks add `children`
+    /// holding their child nodes in document order.
+    pub fn to_json_with_spans(&self) -> serde_json::Value {
+        let location = &self.meta.location;
+        let kind = match &self.type_ {
+            StatementType::Module(_) => "module",
+            StatementType::Section(_) => "section",
+            StatementType::Block { .. } => "block",
+            StatementType::Assignment(_) => "assignment",
+            StatementType::Append(_) => "append",
+            StatementType::Control(_) => "control",
+        };
+        let mut node = serde_json::Map::new();
+        node.insert("id".to_string(), self.id.clone().into());
+        node.insert("type".to_string(), kind.into());
+        node.insert(
+            "span".to_string(),
+            serde_json::json!({
+                "line": location.line,
+                "column": location.column,
+                "length": location.length,
+                "file": location.file_path,
+            }),
+        );
+        if let Some(value_type) = self.type_.value_type() {
+            node.insert("value_type".to_string(), value_type.to_annotation().into());
+        }
+        let children = match &self.data {
+            StatementData::Single(value) => {
+                node.insert("value".to_string(), value.to_json());
+                None
+            }
+            StatementData::Labeled(labels, children) => {
+                node.insert(
+                    "labels".to_string(),
+                    labels.iter().map(Value::to_json).collect(),
+                );
+                Some(children)
+            }
+            StatementData::Group(children) => Some(children),
+        };
+        if let Some(children) = children {
+            node.insert(
+                "children".to_string(),
+                children
+                    .values()
+                    .map(Statement::to_json_with_spans)
+                    .collect(),
+            );
+        }
+        serde_json::Value::Object(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn json_with_spans() {
+        let source = "name = 'app'\n[server]\nport: u16 = 8080u16\nlistener 'http' {\n  tls = false\n}\ntags: array[string] = ['web']\n";
+        let module = crate::from_str(source).unwrap();
+        let nodes = module.to_json_with_spans();
+        assert_eq!(nodes["type"], json!("module"));
+        assert_eq!(nodes["children"][0]["id"], json!("name"));
+
+        let server = &nodes["children"][1];
+        assert_eq!(server["type"], json!("section"));
+        assert_eq!(
+            server["children"][0],
+            json!({
+                "id": "port",
+                "type": "assignment",
+                "span": { "line": 2, "column": 0, "length": 4, "file": null },
+                "value_type": "u16",
+                "value": 8080,
+            })
+        );
+        let listener = &server["children"][1];
+        assert_eq!(listener["type"], json!("block"));
+        assert_eq!(listener["labels"], json!(["http"]));
+        assert_eq!(
+            listener["span"],
+            json!({ "line": 3, "column": 0, "length": 33, "file": null })
+        );
+        assert_eq!(listener["children"][0]["value"], json!(false));
+        assert_eq!(listener["children"][0]["span"]["line"], json!(4));
+        assert_eq!(server["children"][2]["value_type"], json!("array[string]"));
+
+        let parsed =
+            crate::Parser::with_file_path("main", "config/app.bml", logos::Logos::lexer(source))
+                .parse()
+                .unwrap();
+        let nodes = parsed.to_json_with_spans();
+        assert_eq!(
+            nodes["children"][0]["span"]["file"],
+            json!("config/app.bml")
+        );
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let document = json!({
+            "name": "app",
+            "replicas": 3,
+            "ratio": 0.25,
+            "limit": u64::MAX,
+            "debug": false,
+            "owner": null,
+            "servers": [
+                { "host": "alpha", "ports": [80, 443] },
+                { "host": "beta", "ports": [] }
+            ],
+            "nested": { "inner": { "values": [1, "two", [3.5]] } }
+        });
+
+        let value = Value::from_json(document.clone());
+        let table = value.as_table().unwrap();
+        assert_eq!(table["replicas"].as_int(), Some(&3));
+        assert_eq!(table["limit"].as_uint(), Some(&u64::MAX));
+        assert_eq!(table["ratio"].as_float(), Some(&0.25));
+        assert!(table["owner"].is_null());
+        let servers = table["servers"].as_array().unwrap();
+        assert_eq!(
+            servers[0].as_table().unwrap()["host"].as_string(),
+            Some(&"alpha".to_string())
+        );
+
+        assert_eq!(value.to_json(), document);
+    }
+
+    #[test]
+    fn json_strings_for_other_values() {
+        let meta = Metadata::new(Location::new(0, 0));
+        let bytes = Value::new_bytes(vec![0, 159, 146, 150], meta.clone());
+        assert_eq!(bytes.to_json(), json!("AJ+Slg=="));
+
+        let version = Value::new_version(semver::Version::new(1, 2, 3), meta.clone());
+        assert_eq!(version.to_json(), json!("1.2.3"));
+
+        let large = Value::new_u128(u128::MAX, meta.clone());
+        assert_eq!(large.to_json(), json!(u128::MAX.to_string()));
+
+        let infinite = Value::new_float(f64::INFINITY, meta);
+        assert_eq!(infinite.to_json(), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn json_feeds_deserialization() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Server {
+            host: String,
+            ports: Vec<i64>,
+        }
+
+        let value = Value::from_json(json!({ "host": "alpha", "ports": [80, 443] }));
+        let server: Server = crate::de::from_value(&value).unwrap();
+        assert_eq!(
+            server,
+            Server {
+                host: "alpha".to_string(),
+                ports: vec![80, 443],
+            }
+        );
+    }
+}
@@ -0,0 +1,15 @@
+//! Conversions between BarkML statements, values and other configuration formats
+//!
+//! Each format lives behind a feature of the same name:
+//!
+//! - **bincode**: `Statement::to_bytes` and `Statement::from_bytes`
+//! - **json**: `Value::from_json`, `Value::to_json` and `Statement::to_json_with_spans`
+//! - **toml**: `Statement::from_toml` and `Statement::to_toml`
+
+#[cfg(feature = "bincode")]
+mod bincode;
+#[cfg(feature = "json")]
+mod json;
+pub(crate) mod semver;
+#[cfg(feature = "toml")]
+mod toml;
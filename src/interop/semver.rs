@@ -0,0 +1,7 @@
+//! Markers shared by the serializer and the semantic version field helpers
+
+/// Newtype name marking a string the serializer should read as a version
+pub(crate) const VERSION_MARKER: &str = "$barkml::private::Version";
+
+/// Newtype name marking a string the serializer should read as a version requirement
+pub(crate) const REQUIRE_MARKER: &str = "$barkml::private::Require";
@@ -0,0 +1,248 @@
+use crate::ast::{Data, Location, Metadata, Statement, StatementData, StatementType, Value};
+use crate::{Result, error};
+use indexmap::IndexMap;
+
+impl Statement {
+    /// Builds a module statement from a TOML document
+    ///
+    /// Top level tables become sections and every other top level entry becomes an
+    /// assignment, nested tables are kept as table values. Integers are read as `int`,
+    /// datetimes with an offset are read as `datetime` values when the `datetime` feature
+    /// is enabled and as strings otherwise. A document that is not a table is assigned
+    /// to `value`.
+    pub fn from_toml(document: ::toml::Value) -> Statement {
+        let meta = Metadata::new(Location::new(0, 0));
+        let entries = match document {
+            ::toml::Value::Table(table) => table,
+            other => ::toml::Table::from_iter([("value".to_string(), other)]),
+        };
+
+        let mut children = IndexMap::new();
+        for (key, entry) in entries {
+            let child = match entry {
+                ::toml::Value::Table(table) => {
+                    let statements = table
+                        .into_iter()
+                        .map(|(key, entry)| {
+                            let child = assignment(&key, value_from_toml(entry));
+                            (child.inject_id(), child)
+                        })
+                        .collect();
+                    Statement::new_section(&key, statements, meta.clone())
+                }
+                other => assignment(&key, value_from_toml(other)),
+            };
+            children.insert(child.inject_id(), child);
+        }
+        Statement::new_module(".", children, meta)
+    }
+
+    /// Converts this statement into a TOML value
+    ///
+    /// Modules, sections and blocks become tables keyed by their statement keys, block
+    /// labels are not kept. Versions, requirements, symbols, macros and bytes are written
+    /// as strings. Null values and integers that do not fit in a signed 64-bit number
+    /// cannot be represented in TOML and are an error.
+    pub fn to_toml(&self) -> Result<::toml::Value> {
+        match &self.data {
+            StatementData::Single(value) => value_to_toml(value),
+            StatementData::Group(children) | StatementData::Labeled(_, children) => children
+                .iter()
+                .map(|(key, child)| Ok((key.clone(), child.to_toml()?)))
+                .collect::<Result<::toml::Table>>()
+                .map(::toml::Value::Table),
+        }
+    }
+}
+
+fn assignment(id: &str, value: Value) -> Statement {
+    let meta = value.meta.clone();
+    Statement::new(
+        id,
+        StatementType::Assignment(value.type_of()),
+        StatementData::Single(value),
+        meta,
+    )
+}
+
+fn value_from_toml(value: ::toml::Value) -> Value {
+    let data = match value {
+        ::toml::Value::String(value) => Data::String(value),
+        ::toml::Value::Integer(value) => Data::Signed(value),
+        ::toml::Value::Float(value) => Data::Float(value),
+        ::toml::Value::Boolean(value) => Data::Bool(value),
+        ::toml::Value::Datetime(value) => datetime_from_toml(value),
+        ::toml::Value::Array(values) => {
+            Data::Array(values.into_iter().map(value_from_toml).collect())
+        }
+        ::toml::Value::Table(table) => Data::Table(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, value_from_toml(value)))
+                .collect(),
+        ),
+    };
+    Value::new(data, Metadata::new(Location::new(0, 0)))
+}
+
+#[cfg(feature = "datetime")]
+fn datetime_from_toml(value: ::toml::value::Datetime) -> Data {
+    // Local dates and times have no offset and are kept as strings
+    let value = value.to_string();
+    match chrono::DateTime::parse_from_rfc3339(&value) {
+        Ok(datetime) => Data::DateTime(datetime),
+        Err(_) => Data::String(value),
+    }
+}
+
+#[cfg(not(feature = "datetime"))]
+fn datetime_from_toml(value: ::toml::value::Datetime) -> Data {
+    Data::String(value.to_string())
+}
+
+fn value_to_toml(value: &Value) -> Result<::toml::Value> {
+    let toml_error = |reason: String| error::Error::Toml {
+        location: value.meta.location.clone(),
+        reason,
+    };
+    let integer = |number: i128| {
+        i64::try_from(number)
+            .map(::toml::Value::Integer)
+            .map_err(|_| toml_error(format!("integer {number} does not fit in 64 bits")))
+    };
+
+    Ok(match &value.data {
+        Data::String(value) | Data::Macro(value) | Data::Symbol(value) => {
+            ::toml::Value::String(value.clone())
+        }
+        Data::Signed(value) | Data::I64(value) => ::toml::Value::Integer(*value),
+        Data::I8(value) => ::toml::Value::Integer(i64::from(*value)),
+        Data::I16(value) => ::toml::Value::Integer(i64::from(*value)),
+        Data::I32(value) => ::toml::Value::Integer(i64::from(*value)),
+        Data::U8(value) => ::toml::Value::Integer(i64::from(*value)),
+        Data::U16(value) => ::toml::Value::Integer(i64::from(*value)),
+        Data::U32(value) => ::toml::Value::Integer(i64::from(*value)),
+        Data::Unsigned(value) | Data::U64(value) => integer(i128::from(*value))?,
+        Data::I128(value) => integer(*value)?,
+        Data::U128(value) => i64::try_from(*value)
+            .map(::toml::Value::Integer)
+            .map_err(|_| toml_error(format!("integer {value} does not fit in 64 bits")))?,
+        Data::Float(value) | Data::F64(value) => ::toml::Value::Float(*value),
+        Data::F32(value) => ::toml::Value::Float(f64::from(*value)),
+        Data::Bool(value) => ::toml::Value::Boolean(*value),
+        Data::Bytes(_) | Data::Version(_) | Data::Require(_) => {
+            ::toml::Value::String(value.to_macro_string())
+        }
+        #[cfg(feature = "datetime")]
+        Data::DateTime(_) => ::toml::Value::Datetime(
+            value
+                .to_macro_string()
+                .parse()
+                .map_err(|e: ::toml::value::DatetimeParseError| toml_error(e.to_string()))?,
+        ),
+        // Decimals are kept as strings as a float would lose their exact value
+        #[cfg(feature = "decimal")]
+        Data::Decimal(_) => ::toml::Value::String(value.to_macro_string()),
+        #[cfg(feature = "net")]
+        Data::Ip(_) | Data::Cidr(_) => ::toml::Value::String(value.to_macro_string()),
+        #[cfg(feature = "regex")]
+        Data::Regex(_) => ::toml::Value::String(value.to_macro_string()),
+        Data::Null => return Err(toml_error("toml has no null value".to_string())),
+        Data::Array(values) => {
+            ::toml::Value::Array(values.iter().map(value_to_toml).collect::<Result<_>>()?)
+        }
+        Data::Table(children) => ::toml::Value::Table(
+            children
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), value_to_toml(value)?)))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    const DOCUMENT: &str = r#"
+title = "example"
+ports = [8000, 8001]
+
+[owner]
+name = "Tom"
+dob = 1979-05-27T07:32:00-08:00
+
+[database]
+enabled = true
+ratio = 0.5
+limits = { cpu = 2, memory = "4G" }
+"#;
+
+    #[test]
+    fn toml_roundtrip() {
+        let document: ::toml::Value = ::toml::from_str(DOCUMENT).unwrap();
+        let statement = Statement::from_toml(document.clone());
+
+        let title = statement.find_by_path("title").unwrap();
+        assert_eq!(
+            title.get_value().unwrap().as_string(),
+            Some(&"example".to_string())
+        );
+        let owner = statement.find_by_path("owner").unwrap();
+        assert!(matches!(owner.type_, StatementType::Section(_)));
+        let limits = statement.find_by_path("database.limits").unwrap();
+        assert_eq!(
+            limits.get_value().unwrap().as_table().unwrap()["cpu"].as_int(),
+            Some(&2)
+        );
+
+        // Datetimes only survive the round trip as datetimes with the feature enabled
+        let dob = statement.find_by_path("owner.dob").unwrap();
+        #[cfg(feature = "datetime")]
+        {
+            assert!(dob.get_value().unwrap().as_datetime().is_some());
+            assert_eq!(statement.to_toml().unwrap(), document);
+        }
+        #[cfg(not(feature = "datetime"))]
+        assert_eq!(
+            dob.get_value().unwrap().as_string(),
+            Some(&"1979-05-27T07:32:00-08:00".to_string())
+        );
+    }
+
+    #[test]
+    fn toml_from_barkml() {
+        let statement = crate::from_str("name = 'app'\n[server]\nmissing = null\n").unwrap();
+        let Err(error) = statement.to_toml() else {
+            panic!("null values should not convert");
+        };
+        assert_matches!(error, error::Error::Toml { .. });
+
+        let statement =
+            crate::from_str("name = 'app'\nrelease = 1.2.3\n[server]\nport = 8080u16\n").unwrap();
+        let ::toml::Value::Table(table) = statement.to_toml().unwrap() else {
+            panic!("expected a table");
+        };
+        assert_eq!(
+            table.get("release"),
+            Some(&::toml::Value::String("1.2.3".to_string()))
+        );
+        let Some(::toml::Value::Table(server)) = table.get("server") else {
+            panic!("expected a server table");
+        };
+        assert_eq!(server.get("port"), Some(&::toml::Value::Integer(8080)));
+
+        // Out of range integers are reported with their own value
+        let huge = u128::MAX;
+        let statement = crate::from_str(&format!("huge = {huge}u128\n")).unwrap();
+        let Err(error) = statement.to_toml() else {
+            panic!("{huge} should not convert");
+        };
+        assert!(
+            error
+                .to_string()
+                .contains(&format!("integer {huge} does not"))
+        );
+    }
+}
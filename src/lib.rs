@@ -31,12 +31,11 @@
 #![allow(clippy::from_str_radix_10)]
 #![allow(clippy::result_large_err)]
 
-// Standard library imports
-use std::io::Cursor;
-
 // Local crate modules
 mod ast;
 mod error;
+mod format;
+mod interop;
 mod load;
 mod syn;
 
@@ -46,9 +45,14 @@ pub mod de;
 // Serde serialization support
 pub mod ser;
 
+// Assertions for crates testing against BarkML
+#[cfg(any(test, feature = "testing"))]
+pub mod test_util;
+
 // Re-exports
 pub use ast::*;
-pub use error::Error;
+pub use error::{Error, ErrorCategory};
+pub use format::{FormatOptions, format};
 pub use load::*;
 pub use syn::*;
 
@@ -93,8 +97,211 @@ pub(crate) type Result<T> = std::result::Result<T, Error>;
 /// - Macro resolution fails
 /// - The parser encounters unexpected tokens
 pub fn from_str(input: &str) -> Result<Statement> {
-    let mut cursor = Cursor::new(input.as_bytes());
     StandardLoader::default()
-        .add_module("main", &mut cursor, None)?
+        .add_str("main", input, None)?
         .load()
 }
+
+/// Parses a BarkML string and deserializes the root statement into `T`.
+///
+/// This combines [`from_str`] and [`de::from_statement`], so macros are resolved before
+/// the module is deserialized.
+///
+/// # Examples
+///
+/// ```rust
+/// use barkml::from_str_as;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Database {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     database: Database,
+/// }
+///
+/// let config: Config = from_str_as("[database]\nhost = \"localhost\"\nport = 5432\n")
+///     .expect("Failed to read BarkML");
+/// assert_eq!(config.database.port, 5432);
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error if the input fails to parse, its macros can not be
+/// resolved or the module does not match the structure of `T`.
+pub fn from_str_as<T>(input: &str) -> Result<T>
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    de::from_statement(&from_str(input)?)
+}
+
+/// Reads BarkML from a reader and deserializes the root statement into `T`.
+///
+/// The reader is loaded with the standard loader like [`from_str`], macros are resolved
+/// and the module is deserialized with [`de::from_statement`].
+///
+/// # Errors
+///
+/// This function will return an error if the reader can not be read, the content fails
+/// to parse, its macros can not be resolved or the module does not match the structure
+/// of `T`.
+pub fn from_reader_as<T, R>(mut reader: R) -> Result<T>
+where
+    T: for<'de> serde::Deserialize<'de>,
+    R: std::io::Read + std::io::Seek,
+{
+    let module = StandardLoader::default()
+        .add_module("main", &mut reader, None)?
+        .load()?;
+    de::from_statement(&module)
+}
+
+/// Parses a BarkML string holding several documents separated by `---` lines.
+///
+/// Each separator must be on a line of its own, every document is parsed as an
+/// independent module and has its macros resolved on its own, so a document can not
+/// reference values declared in another one. A separator at the very start of the
+/// input does not start an empty document. `include` directives are not resolved.
+///
+/// # Examples
+///
+/// ```rust
+/// use barkml::from_str_multi;
+///
+/// let bundle = r#"
+/// kind = "Service"
+/// ---
+/// kind = "Deployment"
+/// "#;
+///
+/// let documents = from_str_multi(bundle).expect("Failed to parse BarkML");
+/// assert_eq!(documents.len(), 2);
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error if any document contains invalid BarkML syntax
+/// or its macros can not be resolved.
+pub fn from_str_multi(input: &str) -> Result<Vec<Statement>> {
+    let documents = Parser::new("main", logos::Logos::lexer(input))
+        .parse_documents()
+        .map_err(|e| Error::Parse {
+            filename: "main".to_string(),
+            source: Box::new(e),
+        })?;
+    documents
+        .iter()
+        .map(|document| Scope::new(document).apply())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Simple {
+        tire: String,
+        #[serde(rename = "section-1")]
+        section: SimpleSection,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct SimpleSection {
+        number: u32,
+        floating: f32,
+        versioning: String,
+        requires: String,
+        strings: String,
+    }
+
+    #[test]
+    fn test_from_reader_as() {
+        let expected = Simple {
+            tire: "1.0.0".to_string(),
+            section: SimpleSection {
+                number: 4,
+                floating: 3.14,
+                versioning: "1.2.3-beta.6".to_string(),
+                requires: "^1.3.3".to_string(),
+                strings: "hello world".to_string(),
+            },
+        };
+
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/simple.bml");
+        let file = std::fs::File::open(path).unwrap();
+        let config: Simple = from_reader_as(file).unwrap();
+        assert_eq!(config, expected);
+
+        let config: Simple = from_str_as(include_str!("../examples/simple.bml")).unwrap();
+        assert_eq!(config, expected);
+
+        // Macros are resolved before deserializing
+        let config: SimpleSection = from_str_as(
+            "number = 4\nfloating = 3.14f32\nversioning = '1.0.0'\n\
+             requires = m'^{versioning}'\nstrings = m'v{versioning}'\n",
+        )
+        .unwrap();
+        assert_eq!(config.requires, "^1.0.0");
+
+        assert!(matches!(
+            from_str_as::<Simple>("tire = "),
+            Err(Error::Parse { .. })
+        ));
+        assert!(matches!(
+            from_reader_as::<Simple, _>(std::io::Cursor::new("tire = 1\n")),
+            Err(Error::Deserialize { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_str_multi() {
+        let documents = from_str_multi(
+            "---\n\
+             kind = 'Service'\n\
+             name = 'web'\n\
+             banner = m'{name} ---'\n\
+             ---\n\
+             kind = 'Deployment'\n\
+             [spec]\n\
+             replicas = 3\n\
+             note = '---'\n",
+        )
+        .unwrap();
+        assert_eq!(documents.len(), 2);
+
+        let string = |document: &Statement, path: &str| {
+            document
+                .find_by_path(path)
+                .and_then(|x| x.get_value())
+                .and_then(|x| x.as_string())
+                .cloned()
+        };
+        assert_eq!(string(&documents[0], "kind"), Some("Service".to_string()));
+        assert_eq!(string(&documents[0], "banner"), Some("web ---".to_string()));
+        assert!(documents[0].find_by_path("spec").is_none());
+        assert_eq!(
+            string(&documents[1], "kind"),
+            Some("Deployment".to_string())
+        );
+        assert_eq!(string(&documents[1], "spec.note"), Some("---".to_string()));
+        assert!(documents[1].find_by_path("name").is_none());
+
+        // Documents are independent, so the same keys may be declared in each
+        assert_eq!(from_str_multi("a = 1\n---\na = 2\n").unwrap().len(), 2);
+        assert!(from_str_multi("a = 1\n---\nb = m!a\n").is_err());
+        assert_eq!(from_str_multi("a = 1\n").unwrap().len(), 1);
+
+        // A single document parser rejects separators
+        let error = from_str("a = 1\n---\nb = 2\n").unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Parse { ref source, .. } if matches!(**source, Error::Expected { .. })
+        ));
+    }
+}
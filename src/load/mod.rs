@@ -9,16 +9,23 @@
 //! - **Loader Trait**: Defines the interface for all loaders
 //! - **StandardLoader**: The primary implementation for loading BarkML files
 //! - **Walk**: Ergonomic API for traversing and extracting data from loaded documents
+//! - **WatchHandle**: Reloads files when they change, behind the `watch` feature
 //!
-use crate::ast::{Scope, Statement};
+use crate::ast::{
+    DEFAULT_CLOSE_DELIMITER, DEFAULT_OPEN_DELIMITER, DEFAULT_RECURSION_LIMIT, Scope, Statement,
+};
 use crate::{Result, error};
 use std::path::Path;
 
 mod standard;
 mod walk;
+#[cfg(feature = "watch")]
+mod watch;
 
 pub use standard::*;
 pub use walk::*;
+#[cfg(feature = "watch")]
+pub use watch::*;
 
 /// LoaderInterface defines the shared interface for structs that
 /// can read and load BarkML files.
@@ -61,6 +68,18 @@ pub trait Loader {
     /// The parsed module statement, or an error if reading or parsing fails
     fn read(&self) -> Result<Statement>;
 
+    /// Creates the scope used to resolve macros in a module read by this loader
+    ///
+    /// Loaders with their own macro settings, such as custom interpolation delimiters,
+    /// override this to configure the scope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the macro settings of the loader are invalid.
+    fn scope(&self, module: &Statement) -> Result<Scope> {
+        Ok(Scope::new(module))
+    }
+
     /// Loads everything into a module statement and resolves macros if enabled
     ///
     /// This is the main entry point for loading BarkML content. It reads the content,
@@ -74,7 +93,7 @@ pub trait Loader {
     fn load(&self) -> Result<Statement> {
         let mut module = self.read()?;
         if self.is_resolution_enabled() {
-            let mut scope = Scope::new(&module);
+            let mut scope = self.scope(&module)?;
             module = scope.apply()?;
         }
         Ok(module)
@@ -95,7 +114,7 @@ pub trait Loader {
 
         // If macro resolution is enabled, validate that all macros can be resolved
         if self.is_resolution_enabled() {
-            let scope = Scope::new(&module);
+            let scope = self.scope(&module)?;
             scope.validate_macros()?;
         }
 
@@ -112,15 +131,57 @@ pub struct LoaderConfig {
     /// Whether to resolve macros during loading
     pub resolve_macros: bool,
 
+    /// Whether documents holding any macro are rejected, for reading untrusted input that
+    /// must not reference other values. Unlike disabling `resolve_macros`, which leaves
+    /// macros unresolved, loading and validating fail on the first macro found
+    pub forbid_macros: bool,
+
     /// Whether to allow collisions between modules (overwrite on conflict)
     pub allow_collisions: bool,
 
+    /// Whether a key may be repeated within one table, section or block of a single file,
+    /// the later statement or entry replaces the earlier one when allowed
+    pub allow_duplicate_keys: bool,
+
+    /// Whether to record every collision and report them together instead of
+    /// failing on the first one
+    pub collect_collisions: bool,
+
+    /// Whether statement keys are compared case-insensitively when merging and looking
+    /// up statements, keys are lowercased while ids keep their original casing
+    pub case_insensitive_keys: bool,
+
     /// Maximum recursion depth for macro resolution
     pub max_recursion_depth: usize,
 
+    /// Maximum depth statements and values may be nested to while parsing, this
+    /// guards against untrusted input exhausting the stack
+    pub max_nesting_depth: usize,
+
+    /// Whether `key: value` without a type keyword is read as an assignment, documents
+    /// mixing it with `key = value` are reported in the loader warnings
+    pub colon_assignments: bool,
+
+    /// Whether only `true` and `false` are read as booleans, the lenient spellings such
+    /// as `yes` or `off` are read as strings when enabled
+    pub strict_booleans: bool,
+
     /// Whether to validate content during loading
     pub validate_on_load: bool,
 
+    /// Opening and closing delimiters of interpolation segments in macro strings, loading
+    /// fails when either of them is empty
+    pub macro_delimiters: (String, String),
+
+    /// Version of the application reading the configuration, blocks labeled with
+    /// `VERSION_GATE_LABEL` and a version requirement it does not satisfy are left out of
+    /// the loaded module
+    pub context_version: Option<semver::Version>,
+
+    /// Defaults merged beneath the loaded module, statements missing from the module are
+    /// taken from here while statements present in the module always win
+    pub default_values: Option<Statement>,
+
     /// Search paths for auto-discovery of modules
     pub search_paths: Vec<std::path::PathBuf>,
 }
@@ -129,9 +190,22 @@ impl Default for LoaderConfig {
     fn default() -> Self {
         Self {
             resolve_macros: true,
+            forbid_macros: false,
             allow_collisions: false,
-            max_recursion_depth: 100,
+            allow_duplicate_keys: false,
+            collect_collisions: false,
+            case_insensitive_keys: false,
+            max_recursion_depth: DEFAULT_RECURSION_LIMIT,
+            max_nesting_depth: 64,
+            colon_assignments: false,
+            strict_booleans: false,
             validate_on_load: false,
+            macro_delimiters: (
+                DEFAULT_OPEN_DELIMITER.to_string(),
+                DEFAULT_CLOSE_DELIMITER.to_string(),
+            ),
+            context_version: None,
+            default_values: None,
             search_paths: vec![std::env::current_dir().unwrap_or_else(|_| ".".into())],
         }
     }
@@ -304,7 +378,10 @@ mod tests {
         let config = LoaderConfig::default();
         assert!(config.resolve_macros);
         assert!(!config.allow_collisions);
+        assert!(!config.collect_collisions);
+        assert!(!config.case_insensitive_keys);
         assert_eq!(config.max_recursion_depth, 100);
+        assert_eq!(config.max_nesting_depth, 64);
         assert!(!config.validate_on_load);
     }
 
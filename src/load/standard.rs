@@ -1,7 +1,7 @@
 use std::{
     fs::File,
-    io::{Read, Seek},
-    path::Path,
+    io::{Cursor, Read, Seek},
+    path::{Path, PathBuf},
     time::Instant,
 };
 
@@ -9,7 +9,7 @@ use super::{LoadStats, Loader, LoaderConfig, utils};
 use crate::{Result, error};
 use crate::{
     StatementData,
-    ast::Statement,
+    ast::{Collision, Data, Location, Metadata, Scope, Statement, Value},
     syn::{Parser, Token},
 };
 use indexmap::IndexMap;
@@ -41,6 +41,12 @@ pub struct StandardLoader {
 
     /// Cache of parsed files to avoid re-parsing
     file_cache: IndexMap<std::path::PathBuf, Statement>,
+
+    /// Collisions recorded while merging when collisions are collected
+    collisions: Vec<Collision>,
+
+    /// Warnings reported by the parser for the sources of the most recent add or import call
+    warnings: Vec<(String, Location)>,
 }
 
 impl Default for StandardLoader {
@@ -58,9 +64,27 @@ impl StandardLoader {
             config,
             stats: LoadStats::new(),
             file_cache: IndexMap::new(),
+            collisions: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
+    /// Sets the version that version gated blocks are checked against
+    ///
+    /// A block opting in with the [`VERSION_GATE_LABEL`] label, such as
+    /// `tracing 'version' ^2.1 { ... }`, is only kept in the loaded module when this
+    /// version satisfies every requirement among its labels. Blocks without the label are
+    /// always kept, as is every block without a context version.
+    pub fn with_context_version(mut self, version: semver::Version) -> Self {
+        self.config.context_version = Some(version);
+        self
+    }
+
+    /// Gets the configuration of this loader
+    pub fn config(&self) -> &LoaderConfig {
+        &self.config
+    }
+
     /// Creates a new StandardLoader with a builder pattern
     pub fn builder() -> StandardLoaderBuilder {
         StandardLoaderBuilder::new()
@@ -81,87 +105,120 @@ impl StandardLoader {
         self.file_cache.len()
     }
 
-    /// Merges the contents of the right statement into the left statement with enhanced error handling
-    ///
-    /// This function recursively merges two statements, handling different statement types
-    /// and respecting the collision policy. It provides detailed error information
-    /// and supports partial merging with rollback on failure.
+    /// Gets the warnings reported while parsing, such as mixed assignment styles
     ///
-    /// # Arguments
-    ///
-    /// * `left` - The target statement to merge into (modified in-place)
-    /// * `right` - The source statement to merge from
-    /// * `allow_collisions` - Whether to allow collisions (overwrite on conflict)
+    /// Only the warnings of the most recent add or import call are kept, each call
+    /// clears the warnings of the previous one. Files served from the cache are not
+    /// parsed again and report no warnings.
+    pub fn warnings(&self) -> &[(String, Location)] {
+        &self.warnings
+    }
+
+    /// Gets the collisions recorded so far when collisions are collected
+    pub fn collisions(&self) -> &[Collision] {
+        &self.collisions
+    }
+
+    /// Takes the collisions recorded so far, leaving none behind
     ///
-    /// # Returns
+    /// Reading fails while any collision is recorded, taking them acknowledges the
+    /// collisions so later reads use the merged modules as they are.
+    pub fn take_collisions(&mut self) -> Vec<Collision> {
+        std::mem::take(&mut self.collisions)
+    }
+
+    /// Merges the contents of the right statement into the left statement
     ///
-    /// Ok(()) if the merge was successful, or an error if there was a collision
-    /// and collisions are not allowed
+    /// See [`Statement::merge`] for the merge rules, `path` is the dot separated path of the
+    /// statements being merged and is used when recording collisions. Collisions are only
+    /// pushed to `collisions` when the config collects them, a collision that is already
+    /// recorded is not recorded again.
     fn merge_statements(
         left: &mut Statement,
         right: &Statement,
-        allow_collisions: bool,
+        path: &str,
+        config: &LoaderConfig,
+        collisions: &mut Vec<Collision>,
     ) -> Result<()> {
-        match &right.data {
-            StatementData::Group(right_stmts) | StatementData::Labeled(_, right_stmts) => {
-                match &mut left.data {
-                    StatementData::Group(left_stmts) | StatementData::Labeled(_, left_stmts) => {
-                        // Pre-allocate capacity for better performance
-                        let additional_capacity =
-                            right_stmts.len().saturating_sub(left_stmts.len());
-                        if additional_capacity > 0 {
-                            left_stmts.reserve(additional_capacity);
-                        }
-
-                        // Merge each child statement
-                        for (key, value) in right_stmts {
-                            if let Some(target) = left_stmts.get_mut(key) {
-                                // Recursive merge for existing keys
-                                Self::merge_statements(target, value, allow_collisions)?;
-                            } else {
-                                // Simple insert for new keys
-                                left_stmts.insert(key.clone(), value.clone());
-                            }
-                        }
-                    }
-                    _ => {
-                        // Type mismatch - replace if collisions are allowed
-                        ensure!(
-                            allow_collisions,
-                            error::CollisionSnafu {
-                                left_id: left.id.clone(),
-                                left_location: left.meta.location.clone(),
-                                right_id: right.id.clone(),
-                                right_location: right.meta.location.clone()
-                            }
-                        );
-                        *left = right.clone();
-                    }
-                }
+        let mut found = Vec::new();
+        left.merge_at(
+            right,
+            path,
+            config.allow_collisions,
+            config.collect_collisions.then_some(&mut found),
+        )?;
+        for collision in found {
+            if !collisions.contains(&collision) {
+                collisions.push(collision);
             }
-            StatementData::Single(_) => {
-                // Value collision - replace if allowed
-                ensure!(
-                    allow_collisions,
-                    error::CollisionSnafu {
-                        left_id: left.id.clone(),
-                        left_location: left.meta.location.clone(),
-                        right_id: right.id.clone(),
-                        right_location: right.meta.location.clone()
-                    }
-                );
-                *left = right.clone();
+        }
+        Ok(())
+    }
+
+    /// Lowercases the keys of every nested child map so differently cased ids merge
+    ///
+    /// Statement ids keep their original casing for display, only the keys used for
+    /// merging and lookup are normalized. Children whose keys become equal are merged
+    /// following the collision policy.
+    fn normalize_keys(
+        statement: &mut Statement,
+        path: &str,
+        config: &LoaderConfig,
+        collisions: &mut Vec<Collision>,
+    ) -> Result<()> {
+        let children = match &mut statement.data {
+            StatementData::Group(children) | StatementData::Labeled(_, children) => children,
+            StatementData::Single(_) => return Ok(()),
+        };
+
+        let mut normalized: IndexMap<String, Statement> = IndexMap::with_capacity(children.len());
+        for (key, mut child) in std::mem::take(children) {
+            let key = key.to_lowercase();
+            let child_path = Statement::child_path(path, &key);
+            Self::normalize_keys(&mut child, &child_path, config, collisions)?;
+
+            if let Some(existing) = normalized.get_mut(&key) {
+                Self::merge_statements(existing, &child, &child_path, config, collisions)?;
+            } else {
+                normalized.insert(key, child);
             }
         }
+
+        *children = normalized;
         Ok(())
     }
 
     /// Parses a BarkML file with caching and error recovery
+    ///
+    /// When `origin` is given, it is recorded as the file path of every location in the
+    /// parsed module and `include` directives are resolved relative to its directory.
+    /// Otherwise includes are resolved relative to the current working directory.
     fn parse_file<R>(
         &mut self,
         name: &str,
         code: &mut R,
         filename: Option<String>,
+        origin: Option<&Path>,
+    ) -> Result<Statement>
+    where
+        R: Read + Seek,
+    {
+        let mut include_stack = Vec::new();
+        self.parse_source(name, code, filename, origin, &mut include_stack)
+    }
+
+    /// Parses a BarkML source and merges in any files it includes
+    ///
+    /// The include stack holds the canonical paths of every file currently being
+    /// parsed, so that a file including itself (directly or indirectly) is reported
+    /// as an include cycle instead of recursing forever.
+    fn parse_source<R>(
+        &mut self,
+        name: &str,
+        code: &mut R,
+        filename: Option<String>,
+        origin: Option<&Path>,
+        include_stack: &mut Vec<PathBuf>,
     ) -> Result<Statement>
     where
         R: Read + Seek,
@@ -184,13 +241,24 @@ impl StandardLoader {
         }
 
         let lexer = Token::lexer(&module_code);
-        let mut parser = Parser::new(&filename, lexer);
-        let module = parser.parse().map_err(|e| {
-            // Enhance error with file context
-            error::Error::Io {
-                reason: format!("Failed to parse file '{}': {}", filename, e),
-            }
+        let mut parser = match origin {
+            Some(origin) => Parser::with_file_path(&filename, &origin.to_string_lossy(), lexer),
+            None => Parser::new(&filename, lexer),
+        }
+        .max_nesting_depth(self.config.max_nesting_depth)
+        .strict_booleans(self.config.strict_booleans)
+        .colon_assignments(self.config.colon_assignments)
+        .allow_duplicate_keys(self.config.allow_duplicate_keys);
+        let mut module = parser.parse().map_err(|e| error::Error::Parse {
+            filename: filename.clone(),
+            source: Box::new(e),
         })?;
+        let includes = parser.includes().to_vec();
+        self.warnings.extend_from_slice(parser.warnings());
+
+        if self.config.case_insensitive_keys {
+            Self::normalize_keys(&mut module, "", &self.config, &mut self.collisions)?;
+        }
 
         // Update statistics
         self.stats.files_processed += 1;
@@ -203,7 +271,60 @@ impl StandardLoader {
             })?;
         }
 
-        Ok(module)
+        if includes.is_empty() {
+            return Ok(module);
+        }
+
+        let base_dir = match origin.and_then(Path::parent) {
+            Some(parent) => parent.to_path_buf(),
+            None => std::env::current_dir().map_err(|e| error::Error::Io {
+                reason: format!("Failed to resolve current directory: {}", e),
+            })?,
+        };
+
+        let origin = origin.map(|path| path.canonicalize().unwrap_or(path.to_path_buf()));
+        if let Some(origin) = &origin {
+            include_stack.push(origin.clone());
+        }
+
+        // Included files are merged first so the including file can build on them
+        let mut combined = Statement::new_module(&module.id, IndexMap::new(), module.meta.clone());
+        for (include, location) in includes {
+            let path = base_dir.join(&include);
+            let path = path
+                .canonicalize()
+                .map_err(|_| error::Error::NotFound { path: path.clone() })?;
+            ensure!(
+                !include_stack.contains(&path),
+                error::IncludeCycleSnafu { location, path }
+            );
+
+            let mut file = File::open(&path).map_err(|e| error::Error::Io {
+                reason: format!("Failed to open file '{}': {}", path.display(), e),
+            })?;
+            let included =
+                self.parse_source(name, &mut file, Some(include), Some(&path), include_stack)?;
+            Self::merge_statements(
+                &mut combined,
+                &included,
+                "",
+                &self.config,
+                &mut self.collisions,
+            )?;
+        }
+        Self::merge_statements(
+            &mut combined,
+            &module,
+            "",
+            &self.config,
+            &mut self.collisions,
+        )?;
+
+        if origin.is_some() {
+            include_stack.pop();
+        }
+
+        Ok(combined)
     }
 
     /// Add a module with the given name to this loader with enhanced error handling
@@ -216,10 +337,11 @@ impl StandardLoader {
     where
         R: Read + Seek,
     {
-        let module = self.parse_file(name, code, filename)?;
+        self.warnings.clear();
+        let module = self.parse_file(name, code, filename, None)?;
 
         if let Some(existing) = self.modules.get_mut(name) {
-            Self::merge_statements(existing, &module, self.config.allow_collisions)?;
+            Self::merge_statements(existing, &module, "", &self.config, &mut self.collisions)?;
         } else {
             self.modules.insert(name.to_string(), module);
             self.stats.modules_created += 1;
@@ -228,11 +350,27 @@ impl StandardLoader {
         Ok(self)
     }
 
+    /// Add a module with the given name from an in-memory string
+    pub fn add_str(
+        &mut self,
+        name: &str,
+        content: &str,
+        filename: Option<String>,
+    ) -> Result<&mut Self> {
+        self.add_module(name, &mut Cursor::new(content.as_bytes()), filename)
+    }
+
+    /// Add an in-memory string to this loader and merge it into the main module
+    pub fn add_file_str(&mut self, content: &str, filename: Option<String>) -> Result<&mut Self> {
+        self.add_str("main", content, filename)
+    }
+
     /// Add a single file to this loader as a new module with validation
     pub fn import<P>(&mut self, path: P) -> Result<&mut Self>
     where
         P: AsRef<Path>,
     {
+        self.warnings.clear();
         let path = path.as_ref();
         utils::validate_path(path)?;
 
@@ -241,7 +379,13 @@ impl StandardLoader {
         // Check cache first
         if let Some(cached_module) = self.file_cache.get(path) {
             if let Some(existing) = self.modules.get_mut(&name) {
-                Self::merge_statements(existing, cached_module, self.config.allow_collisions)?;
+                Self::merge_statements(
+                    existing,
+                    cached_module,
+                    "",
+                    &self.config,
+                    &mut self.collisions,
+                )?;
             } else {
                 self.modules.insert(name, cached_module.clone());
                 self.stats.modules_created += 1;
@@ -255,12 +399,12 @@ impl StandardLoader {
         })?;
 
         // Parse and cache the module
-        let module = self.parse_file(&name, &mut file, Some(name.clone()))?;
+        let module = self.parse_file(&name, &mut file, Some(name.clone()), Some(path))?;
         self.file_cache.insert(path.to_path_buf(), module.clone());
 
         // Add to modules
         if let Some(existing) = self.modules.get_mut(&name) {
-            Self::merge_statements(existing, &module, self.config.allow_collisions)?;
+            Self::merge_statements(existing, &module, "", &self.config, &mut self.collisions)?;
         } else {
             self.modules.insert(name, module);
             self.stats.modules_created += 1;
@@ -269,11 +413,25 @@ impl StandardLoader {
         Ok(self)
     }
 
+    /// Add the contents of a reader to this loader as a module with the given name
+    ///
+    /// This is the counterpart of [`StandardLoader::import`] for sources that are not on
+    /// the filesystem, such as a database or network response. The module is kept apart
+    /// from the main module and can be retrieved with [`StandardLoader::get_module`],
+    /// importing a name that already exists merges into that module.
+    pub fn import_reader<R>(&mut self, name: &str, reader: &mut R) -> Result<&mut Self>
+    where
+        R: Read + Seek,
+    {
+        self.add_module(name, reader, Some(name.to_string()))
+    }
+
     /// Add a single file to this loader and merge it into the main module
     pub fn add_file<P>(&mut self, path: P) -> Result<&mut Self>
     where
         P: AsRef<Path>,
     {
+        self.warnings.clear();
         let path = path.as_ref();
         utils::validate_path(path)?;
 
@@ -282,7 +440,13 @@ impl StandardLoader {
         // Check cache first
         if let Some(cached_module) = self.file_cache.get(path) {
             if let Some(existing) = self.modules.get_mut("main") {
-                Self::merge_statements(existing, cached_module, self.config.allow_collisions)?;
+                Self::merge_statements(
+                    existing,
+                    cached_module,
+                    "",
+                    &self.config,
+                    &mut self.collisions,
+                )?;
             } else {
                 self.modules
                     .insert("main".to_string(), cached_module.clone());
@@ -297,12 +461,12 @@ impl StandardLoader {
         })?;
 
         // Parse and cache the module
-        let module = self.parse_file("main", &mut file, Some(name))?;
+        let module = self.parse_file("main", &mut file, Some(name), Some(path))?;
         self.file_cache.insert(path.to_path_buf(), module.clone());
 
         // Add to main module
         if let Some(existing) = self.modules.get_mut("main") {
-            Self::merge_statements(existing, &module, self.config.allow_collisions)?;
+            Self::merge_statements(existing, &module, "", &self.config, &mut self.collisions)?;
         } else {
             self.modules.insert("main".to_string(), module);
             self.stats.modules_created += 1;
@@ -321,9 +485,12 @@ impl StandardLoader {
 
         let files = utils::discover_files(path)?;
 
+        let mut warnings = Vec::new();
         for file in &files {
             self.import(file)?;
+            warnings.append(&mut self.warnings);
         }
+        self.warnings = warnings;
 
         Ok(self)
     }
@@ -338,9 +505,12 @@ impl StandardLoader {
 
         let files = utils::discover_files(path)?;
 
+        let mut warnings = Vec::new();
         for file in &files {
             self.add_file(file)?;
+            warnings.append(&mut self.warnings);
         }
+        self.warnings = warnings;
 
         Ok(self)
     }
@@ -377,6 +547,67 @@ impl StandardLoader {
         })
     }
 
+    /// Layers an override statement over a base statement and merges the result into the main module
+    ///
+    /// Unlike the other merge methods the collision policy is ignored between layers, later
+    /// layers always win. Groups are merged recursively while values from `override_` replace
+    /// those in `base`, and both replace anything already in the main module. This makes it
+    /// possible to stack defaults, environment specific and local configuration in order of
+    /// precedence. Keys of each layer are normalized like those of any other source, keys
+    /// colliding within a single layer follow the collision policy.
+    pub fn overlay(&mut self, base: Statement, override_: Statement) -> Result<&mut Self> {
+        let mut layers = [base, override_];
+        if self.config.case_insensitive_keys {
+            for layer in layers.iter_mut() {
+                Self::normalize_keys(layer, "", &self.config, &mut self.collisions)?;
+            }
+        }
+
+        let config = LoaderConfig {
+            allow_collisions: true,
+            ..self.config.clone()
+        };
+        let [mut layered, override_] = layers;
+        Self::merge_statements(&mut layered, &override_, "", &config, &mut self.collisions)?;
+        if let Some(existing) = self.modules.get_mut("main") {
+            Self::merge_statements(existing, &layered, "", &config, &mut self.collisions)?;
+        } else {
+            self.modules.insert("main".to_string(), layered);
+            self.stats.modules_created += 1;
+        }
+
+        Ok(self)
+    }
+
+    /// Gets the location of the statement that supplied every value in the main module
+    ///
+    /// Entries are keyed by the dot separated path of each value, the location records the
+    /// module and file the value was read from, which identifies the layer that won.
+    pub fn provenance(&self) -> IndexMap<String, Location> {
+        let mut provenance = IndexMap::new();
+        if let Some(main) = self.modules.get("main") {
+            Self::collect_provenance(main, "", &mut provenance);
+        }
+        provenance
+    }
+
+    fn collect_provenance(
+        statement: &Statement,
+        path: &str,
+        provenance: &mut IndexMap<String, Location>,
+    ) {
+        match &statement.data {
+            StatementData::Group(children) | StatementData::Labeled(_, children) => {
+                for (key, child) in children {
+                    Self::collect_provenance(child, &Statement::child_path(path, key), provenance);
+                }
+            }
+            StatementData::Single(_) => {
+                provenance.insert(path.to_string(), statement.meta.location.clone());
+            }
+        }
+    }
+
     /// Gets all module names currently loaded
     pub fn module_names(&self) -> Vec<&String> {
         self.modules.keys().collect()
@@ -396,6 +627,34 @@ impl StandardLoader {
     pub fn remove_module(&mut self, name: &str) -> Option<Statement> {
         self.modules.shift_remove(name)
     }
+
+    /// Assembles every loaded module into a single module holding one section per module
+    ///
+    /// Sections are named after their modules and kept in the order the modules were
+    /// loaded, so the whole load can be deserialized in one go. When macro resolution is
+    /// enabled each module is resolved on its own first, macros can not reference values
+    /// of other modules.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the macro delimiters are empty or the macros of a module can
+    /// not be resolved.
+    pub fn module_tree(&self) -> Result<Statement> {
+        let mut sections = IndexMap::with_capacity(self.modules.len());
+        for (name, module) in self.modules.iter() {
+            let module = if self.is_resolution_enabled() {
+                self.scope(module)?.apply()?
+            } else {
+                module.clone()
+            };
+            let children = module.get_grouped().cloned().unwrap_or_default();
+            sections.insert(
+                name.clone(),
+                Statement::new_section(name, children, module.meta),
+            );
+        }
+        Ok(Statement::new_module(".", sections, Metadata::default()))
+    }
 }
 
 impl Loader for StandardLoader {
@@ -409,10 +668,124 @@ impl Loader for StandardLoader {
     }
 
     fn read(&self) -> Result<Statement> {
-        self.modules
+        ensure!(
+            self.collisions.is_empty(),
+            error::CollisionsSnafu {
+                collisions: self.collisions.clone()
+            }
+        );
+
+        let mut module = self
+            .modules
             .get("main")
             .cloned()
-            .ok_or(error::Error::NoMain)
+            .ok_or(error::Error::NoMain)?;
+        if let Some(defaults) = &self.config.default_values {
+            if self.config.case_insensitive_keys {
+                // Defaults are matched against the normalized keys of the module
+                let (mut defaults, mut collisions) = (defaults.clone(), Vec::new());
+                Self::normalize_keys(&mut defaults, "", &self.config, &mut collisions)?;
+                ensure!(collisions.is_empty(), error::CollisionsSnafu { collisions });
+                fill_defaults(&mut module, &defaults);
+            } else {
+                fill_defaults(&mut module, defaults);
+            }
+        }
+        if let Some(version) = &self.config.context_version {
+            prune_versioned(&mut module, version);
+        }
+        if self.config.forbid_macros
+            && let Some(location) = find_macro(&module)
+        {
+            return error::MacrosForbiddenSnafu {
+                location: location.clone(),
+            }
+            .fail();
+        }
+        Ok(module)
+    }
+
+    fn scope(&self, module: &Statement) -> Result<Scope> {
+        let (open, close) = self.config.macro_delimiters.clone();
+        Ok(Scope::new(module)
+            .with_delimiters(open, close)?
+            .with_recursion_limit(self.config.max_recursion_depth)
+            .with_case_insensitive_keys(self.config.case_insensitive_keys))
+    }
+}
+
+/// Adds the statements of the defaults that are missing from the statement
+///
+/// Groups present in both are filled recursively, any other statement present in both
+/// is kept as is. Defaults are added after the statements already present.
+fn fill_defaults(statement: &mut Statement, defaults: &Statement) {
+    let (
+        StatementData::Group(children) | StatementData::Labeled(_, children),
+        StatementData::Group(default_children) | StatementData::Labeled(_, default_children),
+    ) = (&mut statement.data, &defaults.data)
+    else {
+        return;
+    };
+    for (key, default) in default_children {
+        match children.get_mut(key) {
+            Some(child) => fill_defaults(child, default),
+            None => {
+                children.insert(key.clone(), default.clone());
+            }
+        }
+    }
+}
+
+/// String label that opts a block into being gated on the context version
+pub const VERSION_GATE_LABEL: &str = "version";
+
+/// Removes every version gated block with a version requirement label the version does
+/// not satisfy
+///
+/// Only blocks labeled with [`VERSION_GATE_LABEL`] are gated, requirement labels of any
+/// other block are plain data and never remove it.
+fn prune_versioned(statement: &mut Statement, version: &semver::Version) {
+    if let StatementData::Group(children) | StatementData::Labeled(_, children) =
+        &mut statement.data
+    {
+        children.retain(|_, child| match &child.data {
+            StatementData::Labeled(labels, _)
+                if labels
+                    .iter()
+                    .any(|label| label.as_string().is_some_and(|x| x == VERSION_GATE_LABEL)) =>
+            {
+                labels
+                    .iter()
+                    .filter_map(|label| label.as_require())
+                    .all(|require| require.matches(version))
+            }
+            _ => true,
+        });
+        for child in children.values_mut() {
+            prune_versioned(child, version);
+        }
+    }
+}
+
+/// Returns the location of the first macro in a statement, including its labels and the
+/// members of its tables and arrays
+fn find_macro(statement: &Statement) -> Option<&Location> {
+    fn in_value(value: &Value) -> Option<&Location> {
+        match &value.data {
+            Data::Macro(_) => Some(&value.meta.location),
+            Data::Array(values) => values.iter().find_map(in_value),
+            Data::Table(values) => values.values().find_map(in_value),
+            _ => None,
+        }
+    }
+
+    match &statement.data {
+        StatementData::Single(value) => in_value(value),
+        StatementData::Labeled(labels, children) => labels
+            .iter()
+            .find_map(in_value)
+            .or_else(|| children.values().find_map(find_macro)),
+        StatementData::Group(children) => children.values().find_map(find_macro),
     }
 }
 
@@ -433,21 +806,71 @@ impl StandardLoaderBuilder {
         self
     }
 
+    pub fn forbid_macros(mut self, forbid: bool) -> Self {
+        self.config.forbid_macros = forbid;
+        self
+    }
+
     pub fn allow_collisions(mut self, allow: bool) -> Self {
         self.config.allow_collisions = allow;
         self
     }
 
+    pub fn allow_duplicate_keys(mut self, allow: bool) -> Self {
+        self.config.allow_duplicate_keys = allow;
+        self
+    }
+
     pub fn max_recursion_depth(mut self, depth: usize) -> Self {
         self.config.max_recursion_depth = depth;
         self
     }
 
+    pub fn max_nesting_depth(mut self, depth: usize) -> Self {
+        self.config.max_nesting_depth = depth;
+        self
+    }
+
+    pub fn colon_assignments(mut self, enabled: bool) -> Self {
+        self.config.colon_assignments = enabled;
+        self
+    }
+
+    pub fn strict_booleans(mut self, strict: bool) -> Self {
+        self.config.strict_booleans = strict;
+        self
+    }
+
+    pub fn case_insensitive_keys(mut self, insensitive: bool) -> Self {
+        self.config.case_insensitive_keys = insensitive;
+        self
+    }
+
+    pub fn collect_collisions(mut self, collect: bool) -> Self {
+        self.config.collect_collisions = collect;
+        self
+    }
+
     pub fn validate_on_load(mut self, validate: bool) -> Self {
         self.config.validate_on_load = validate;
         self
     }
 
+    pub fn macro_delimiters(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.config.macro_delimiters = (open.into(), close.into());
+        self
+    }
+
+    pub fn default_values(mut self, defaults: Statement) -> Self {
+        self.config.default_values = Some(defaults);
+        self
+    }
+
+    pub fn context_version(mut self, version: semver::Version) -> Self {
+        self.config.context_version = Some(version);
+        self
+    }
+
     pub fn add_search_path<P: AsRef<Path>>(mut self, path: P) -> Self {
         self.config.search_paths.push(path.as_ref().to_path_buf());
         self
@@ -467,7 +890,7 @@ impl Default for StandardLoaderBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ast::{Location, Metadata, Statement, Value};
+    use crate::ast::{Data, Location, Metadata, Statement, StatementType, Value, ValueType};
     use indexmap::IndexMap;
     use semver::Version;
 
@@ -506,6 +929,95 @@ mod tests {
         assert!(!loader.has_module("test"));
     }
 
+    #[test]
+    fn test_module_tree() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Database {
+            host: String,
+            port: i64,
+            pool: Pool,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Pool {
+            size: i64,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Cache {
+            host: String,
+            ttl: i64,
+            endpoint: String,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Services {
+            database: Database,
+            cache: Cache,
+        }
+
+        let fixture = |name: &str| {
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("examples/modules")
+                .join(name)
+        };
+        let mut loader = StandardLoader::default();
+        loader
+            .import(fixture("database.bml"))
+            .unwrap()
+            .import(fixture("cache.bml"))
+            .unwrap();
+
+        let tree = loader.module_tree().unwrap();
+        let keys = tree.get_grouped().unwrap().keys().collect::<Vec<_>>();
+        assert_eq!(keys, ["database", "cache"]);
+
+        let services: Services = crate::de::from_statement(&tree).unwrap();
+        assert_eq!(services.database.host, "db.internal");
+        assert_eq!(services.database.port, 5432);
+        assert_eq!(services.database.pool.size, 8);
+        assert_eq!(services.cache.host, "cache.internal");
+        assert_eq!(services.cache.ttl, 300);
+        assert_eq!(services.cache.endpoint, "cache.internal:6379");
+
+        // Without resolution the macros are kept as written
+        loader.skip_macro_resolution().unwrap();
+        let tree = loader.module_tree().unwrap();
+        let endpoint = tree
+            .find_by_path("cache.endpoint")
+            .and_then(|x| x.get_value());
+        assert!(endpoint.and_then(|x| x.as_macro()).is_some());
+    }
+
+    #[test]
+    fn test_import_reader() {
+        let mut loader = StandardLoader::default();
+        loader
+            .add_file_str("name = 'main'\n", None)
+            .unwrap()
+            .import_reader("database", &mut Cursor::new("host = 'db'\nport = 5432\n"))
+            .unwrap()
+            .import_reader("network", &mut Cursor::new(b"host = 'net'\n".to_vec()))
+            .unwrap();
+
+        assert_eq!(loader.module_names(), vec!["main", "database", "network"]);
+        let host = |module: &str| {
+            loader
+                .get_module(module)
+                .and_then(|x| x.find_by_path("host"))
+                .and_then(|x| x.get_value())
+                .and_then(|x| x.as_string())
+                .cloned()
+        };
+        assert_eq!(host("database"), Some("db".to_string()));
+        assert_eq!(host("network"), Some("net".to_string()));
+        assert_eq!(host("main"), None);
+
+        let database = loader.get_module("database").unwrap();
+        assert!(database.find_by_path("port").is_some());
+        assert_eq!(database.meta.location.module.as_deref(), Some("database"));
+    }
+
     #[test]
     fn test_statistics() {
         let loader = StandardLoader::default();
@@ -549,6 +1061,7 @@ mod tests {
                                 location: Location::default(),
                                 comment: None,
                                 label: Some("Test".into()),
+                                line_comment: false,
                             },
                         ),
                         Metadata::default(),
@@ -570,6 +1083,7 @@ mod tests {
                                         location: Location::default(),
                                         comment: Some("Documentation".into()),
                                         label: None,
+                                        line_comment: true,
                                     },
                                 )
                                 .unwrap(),
@@ -663,4 +1177,730 @@ mod tests {
         assert!(result.find_by_path("section-1.number").is_some());
         assert!(result.find_by_path("section-2.number").is_some());
     }
+
+    fn include_fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("examples/include")
+            .join(name)
+    }
+
+    #[test]
+    pub fn include_simple() {
+        let mut loader = StandardLoader::default();
+        let result = loader
+            .add_file(include_fixture("simple.bml"))
+            .unwrap()
+            .load()
+            .unwrap();
+
+        assert!(result.find_by_path("app.name").is_some());
+        assert!(result.find_by_path("shared.retries").is_some());
+    }
+
+    #[test]
+    pub fn include_relative() {
+        let mut loader = StandardLoader::default();
+        let result = loader
+            .add_file(include_fixture("relative.bml"))
+            .unwrap()
+            .load()
+            .unwrap();
+
+        assert!(result.find_by_path("app.name").is_some());
+        assert!(result.find_by_path("extra.enabled").is_some());
+
+        let leaf = result.find_by_path("leaf.depth").unwrap();
+        let file_path = leaf.meta.location.file_path.as_deref().unwrap();
+        assert!(file_path.ends_with("leaf.bml"));
+    }
+
+    #[test]
+    pub fn include_cycle() {
+        let mut loader = StandardLoader::default();
+        let result = loader.add_file(include_fixture("cycle-a.bml"));
+
+        assert!(matches!(result, Err(error::Error::IncludeCycle { .. })));
+    }
+
+    #[test]
+    pub fn collect_collisions() {
+        let mut loader = StandardLoader::builder().collect_collisions(true).build();
+        loader
+            .add_module(
+                "main",
+                &mut std::io::Cursor::new("name = 'first'\n[server]\nhost = 'a'\nport = 80\n"),
+                Some("first".into()),
+            )
+            .unwrap()
+            .add_module(
+                "main",
+                &mut std::io::Cursor::new(
+                    "name = 'second'\n[server]\nhost = 'b'\nport = 81\ntls = true\n",
+                ),
+                Some("second".into()),
+            )
+            .unwrap();
+
+        let paths: Vec<&str> = loader
+            .collisions()
+            .iter()
+            .map(|x| x.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["name", "server.host", "server.port"]);
+
+        let collision = &loader.collisions()[1];
+        assert_eq!(collision.left_location.module.as_deref(), Some("first"));
+        assert_eq!(collision.right_location.module.as_deref(), Some("second"));
+
+        match loader.load() {
+            Err(error::Error::Collisions { collisions }) => assert_eq!(collisions.len(), 3),
+            other => panic!("expected collisions error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn take_collisions_allows_reading_again() {
+        let mut loader = StandardLoader::builder().collect_collisions(true).build();
+        loader
+            .add_str("main", "name = 'first'\n", Some("first".into()))
+            .unwrap();
+        for _ in 0..2 {
+            loader
+                .add_str("main", "name = 'second'\n", Some("second".into()))
+                .unwrap();
+        }
+
+        // Merging the same source again does not record the same collision twice
+        assert_eq!(loader.collisions().len(), 1);
+        assert!(loader.read().is_err());
+
+        let collisions = loader.take_collisions();
+        assert_eq!(collisions[0].path, "name");
+        assert!(loader.collisions().is_empty());
+        let module = loader.load().unwrap();
+        assert!(module.find_by_path("name").is_some());
+    }
+
+    #[test]
+    pub fn first_collision_fails_without_collecting() {
+        let mut loader = StandardLoader::default();
+        loader
+            .add_module("main", &mut std::io::Cursor::new("a = 1\nb = 2\n"), None)
+            .unwrap();
+
+        let result = loader.add_module("main", &mut std::io::Cursor::new("a = 3\nb = 4\n"), None);
+        assert!(matches!(result, Err(error::Error::Collision { .. })));
+        assert!(loader.collisions().is_empty());
+    }
+
+    #[test]
+    pub fn collision_locations_name_files() {
+        let dir = std::env::temp_dir().join(format!("barkml-collision-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = dir.join("a.bml");
+        let second = dir.join("b.bml");
+        let third = dir.join("c.bml");
+        std::fs::write(&first, "name = 'app'\n[server]\nport = 80\n").unwrap();
+        std::fs::write(&second, "[server]\ntls = true\n").unwrap();
+        std::fs::write(&third, "[server]\nhost = 'b'\n\n\n\nport = 81\n").unwrap();
+
+        // The section was merged from several files before the port collides
+        let mut loader = StandardLoader::default();
+        loader.add_file(&first).unwrap().add_file(&second).unwrap();
+        let result = loader.add_file(&third).map(|_| ());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let error = result.unwrap_err();
+        let message = error.to_string();
+        let (left, right) = match error {
+            error::Error::Collision {
+                left_location,
+                right_location,
+                ..
+            } => (left_location, right_location),
+            other => panic!("expected a collision, got {:?}", other),
+        };
+        assert!(left.file_path.as_deref().unwrap().ends_with("a.bml"));
+        assert_eq!(left.position(), (3, 1));
+        assert!(right.file_path.as_deref().unwrap().ends_with("c.bml"));
+        assert_eq!(right.position(), (6, 1));
+        assert!(
+            message.contains(&format!("{}:3:1", first.display()))
+                && message.contains(&format!("{}:6:1", third.display())),
+            "{message}"
+        );
+    }
+
+    #[test]
+    pub fn append_merges_arrays() {
+        let mut loader = StandardLoader::default();
+        loader
+            .add_module(
+                "main",
+                &mut std::io::Cursor::new("[server]\nhosts = ['a', 'b']\nports = [80]\n"),
+                Some("first".into()),
+            )
+            .unwrap()
+            .add_module(
+                "main",
+                &mut std::io::Cursor::new("[server]\nhosts += ['c']\nports: array[int] += []\n"),
+                Some("second".into()),
+            )
+            .unwrap();
+
+        let result = loader.load().unwrap();
+        let hosts = result.find_by_path("server.hosts").unwrap();
+        assert_eq!(
+            hosts.type_,
+            StatementType::Assignment(ValueType::Array(vec![ValueType::String; 3]))
+        );
+        match &hosts.get_value().unwrap().data {
+            Data::Array(values) => {
+                let values: Vec<_> = values.iter().map(|x| x.data.clone()).collect();
+                assert_eq!(
+                    values,
+                    vec![
+                        Data::String("a".into()),
+                        Data::String("b".into()),
+                        Data::String("c".into())
+                    ]
+                );
+            }
+            other => panic!("expected an array, got {:?}", other),
+        }
+        assert!(matches!(
+            &result.find_by_path("server.ports").unwrap().get_value().unwrap().data,
+            Data::Array(values) if values.len() == 1
+        ));
+    }
+
+    #[test]
+    pub fn append_onto_non_array_fails() {
+        let mut loader = StandardLoader::default();
+        loader
+            .add_module("main", &mut std::io::Cursor::new("name = 'first'\n"), None)
+            .unwrap();
+
+        let result = loader.add_module(
+            "main",
+            &mut std::io::Cursor::new("name += ['second']\n"),
+            None,
+        );
+        assert!(matches!(result, Err(error::Error::Assign { .. })));
+    }
+
+    const UPPER_DATABASE: &str = "[Database]\nHost = 'localhost'\n";
+    const LOWER_DATABASE: &str = "[database]\nport = 5432\n";
+
+    #[test]
+    pub fn case_insensitive_keys_merge() {
+        let mut loader = StandardLoader::builder()
+            .case_insensitive_keys(true)
+            .build();
+        loader
+            .add_module("main", &mut std::io::Cursor::new(UPPER_DATABASE), None)
+            .unwrap()
+            .add_module("main", &mut std::io::Cursor::new(LOWER_DATABASE), None)
+            .unwrap();
+
+        let result = loader.load().unwrap();
+        assert_eq!(result.child_count(), 1);
+        assert!(result.find_by_path("database.host").is_some());
+        assert!(result.find_by_path("database.port").is_some());
+
+        // The original casing is kept for display
+        assert_eq!(result.find_child("database").unwrap().id, "Database");
+        assert_eq!(result.find_by_path("database.host").unwrap().id, "Host");
+    }
+
+    #[test]
+    pub fn case_insensitive_keys_lookup() {
+        let mut loader = StandardLoader::builder()
+            .case_insensitive_keys(true)
+            .build();
+        loader
+            .add_str(
+                "main",
+                "[Server]\nPort = 80\nexact = m'{Server.Port}'\nlower = m'{server.port}'\n",
+                None,
+            )
+            .unwrap();
+
+        let result = loader.load().unwrap();
+        let server = result.find_child("server").unwrap();
+        for id in ["exact", "lower"] {
+            let value = server.find_child(id).unwrap().get_value().unwrap();
+            assert_eq!(value.data, Data::String("80".to_string()));
+        }
+
+        let module = loader.read().unwrap();
+        let scope = loader.scope(&module).unwrap();
+        assert!(scope.lookup("Server.Port").is_some());
+        assert!(scope.lookup("server.PORT").is_some());
+    }
+
+    #[test]
+    pub fn case_sensitive_keys_stay_separate() {
+        let mut loader = StandardLoader::default();
+        loader
+            .add_module("main", &mut std::io::Cursor::new(UPPER_DATABASE), None)
+            .unwrap()
+            .add_module("main", &mut std::io::Cursor::new(LOWER_DATABASE), None)
+            .unwrap();
+
+        let result = loader.load().unwrap();
+        assert_eq!(result.child_count(), 2);
+        assert!(result.find_by_path("Database.Host").is_some());
+        assert!(result.find_by_path("database.port").is_some());
+    }
+
+    #[test]
+    pub fn add_str_merges_modules() {
+        let mut loader = StandardLoader::default();
+        let result = loader
+            .add_str("main", "[server]\nhost = 'localhost'\n", None)
+            .unwrap()
+            .add_file_str("[server]\nport = 8080\n", None)
+            .unwrap()
+            .load()
+            .unwrap();
+
+        assert!(result.find_by_path("server.host").is_some());
+        assert!(result.find_by_path("server.port").is_some());
+    }
+
+    #[test]
+    pub fn add_str_filename_in_errors() {
+        let mut loader = StandardLoader::default();
+        loader
+            .add_str("main", "port = 80\n", Some("base.bml".to_string()))
+            .unwrap();
+        let Err(error) = loader.add_str("main", "port = 8080\n", Some("override.bml".to_string()))
+        else {
+            panic!("expected a collision error");
+        };
+
+        let message = error.to_string();
+        assert!(message.contains("[base.bml@"));
+        assert!(message.contains("[override.bml@"));
+    }
+
+    #[test]
+    pub fn parse_error_keeps_structure() {
+        let mut loader = StandardLoader::default();
+        let Err(error) = loader.add_str("main", "name = 'app'\nport = = 80\n", None) else {
+            panic!("expected a parse error");
+        };
+
+        let error::Error::Parse { filename, source } = error else {
+            panic!("expected a parse error, found {error:?}");
+        };
+        assert_eq!(filename, "main");
+        let error::Error::Expected { location, .. } = *source else {
+            panic!("expected a syntax error, found {source:?}");
+        };
+        assert_eq!(location.line, 1);
+        assert_eq!(location.module.as_deref(), Some("main"));
+    }
+
+    #[test]
+    pub fn macro_delimiters_from_config() {
+        let mut loader = StandardLoader::builder()
+            .macro_delimiters("<<", ">>")
+            .build();
+        loader
+            .add_str(
+                "main",
+                "name = 'web'\nbody = m'{\"name\": \"<<name>>\"}'\n",
+                None,
+            )
+            .unwrap();
+        loader.validate().unwrap();
+        let module = loader.load().unwrap();
+        let body = module.find_by_path("body").and_then(|x| x.get_value());
+        assert_eq!(
+            body.and_then(|x| x.as_string()).map(String::as_str),
+            Some("{\"name\": \"web\"}")
+        );
+    }
+
+    #[test]
+    pub fn empty_macro_delimiters() {
+        let mut loader = StandardLoader::builder().macro_delimiters("", "}").build();
+        loader.add_str("main", "name = 'web'\n", None).unwrap();
+        assert_matches::assert_matches!(
+            loader.load(),
+            Err(error::Error::MacroDelimiters { open, close }) if open.is_empty() && close == "}"
+        );
+        assert!(loader.validate().is_err());
+        assert_matches::assert_matches!(
+            loader.module_tree(),
+            Err(error::Error::MacroDelimiters { .. })
+        );
+    }
+
+    #[test]
+    pub fn default_values_fill_missing_keys() {
+        let defaults = crate::from_str(
+            "port = 8080\nlog_level = 'info'\n[server]\nhost = '0.0.0.0'\nworkers = 4\n",
+        )
+        .unwrap();
+        let mut loader = StandardLoader::builder().default_values(defaults).build();
+        loader
+            .add_str(
+                "main",
+                "name = 'app'\nport = 9090\n[server]\nworkers = 8\nbanner = m!name\n",
+                None,
+            )
+            .unwrap();
+        let module = loader.load().unwrap();
+
+        let int = |path: &str| {
+            module
+                .find_by_path(path)
+                .and_then(|x| x.get_value())
+                .and_then(|x| x.data.integer_value())
+        };
+        let string = |path: &str| {
+            module
+                .find_by_path(path)
+                .and_then(|x| x.get_value())
+                .and_then(|x| x.as_string())
+                .cloned()
+        };
+        // Present keys override the defaults
+        assert_eq!(int("port"), Some(9090));
+        assert_eq!(int("server.workers"), Some(8));
+        // Missing keys fall back to the defaults
+        assert_eq!(string("log_level"), Some("info".to_string()));
+        assert_eq!(string("server.host"), Some("0.0.0.0".to_string()));
+        assert_eq!(string("server.banner"), Some("app".to_string()));
+
+        let keys = module.get_grouped().unwrap().keys().collect::<Vec<_>>();
+        assert_eq!(keys, ["name", "port", "server", "log_level"]);
+    }
+
+    #[test]
+    pub fn default_values_match_case_insensitive_keys() {
+        let defaults = crate::from_str(
+            "Port = 8080
+[Server]
+Host = '0.0.0.0'
+",
+        )
+        .unwrap();
+        let mut loader = StandardLoader::builder()
+            .case_insensitive_keys(true)
+            .default_values(defaults)
+            .build();
+        loader
+            .add_str(
+                "main",
+                "port = 9090
+[server]
+host = 'localhost'
+",
+                None,
+            )
+            .unwrap();
+        let module = loader.load().unwrap();
+
+        let keys = module.get_grouped().unwrap().keys().collect::<Vec<_>>();
+        assert_eq!(keys, ["port", "server"]);
+        let port = module.find_by_path("port").and_then(|x| x.get_value());
+        assert_eq!(port.and_then(|x| x.as_int()), Some(&9090));
+        let server = module.find_child("server").unwrap();
+        assert_eq!(server.get_grouped().unwrap().len(), 1);
+    }
+
+    #[test]
+    pub fn context_version_prunes_blocks() {
+        let source = "name = 'app'\n\
+                      tracing 'version' ^2.1 {\n  enabled = true\n}\n\
+                      legacy 'version' <2 {\n  enabled = true\n}\n\
+                      dependency 'serde' ^9 {\n  optional = false\n}\n\
+                      [server]\n\
+                      listener 'http' 'version' >=1.5, <3 {\n  port = 80\n}\n\
+                      listener 'https' {\n  port = 443\n}\n\
+                      banner = m!name\n";
+        let load = |loader: StandardLoader| {
+            let mut loader = loader;
+            loader.add_str("main", source, None).unwrap();
+            loader.load().unwrap()
+        };
+        let has_block = |module: &Statement, id: &str| module.blocks().any(|(x, ..)| x == id);
+        let version = |text: &str| semver::Version::parse(text).unwrap();
+
+        let module = load(StandardLoader::default().with_context_version(version("2.3.0")));
+        assert!(has_block(&module, "tracing"));
+        assert!(!has_block(&module, "legacy"));
+        // Requirement labels of blocks that do not opt in are not a version gate
+        assert!(has_block(&module, "dependency"));
+        let server = module.find_by_path("server").unwrap();
+        assert_eq!(server.get_grouped().unwrap().len(), 3);
+        assert!(module.find_by_path("server.banner").is_some());
+
+        let module = load(
+            StandardLoader::builder()
+                .context_version(version("1.0.0"))
+                .build(),
+        );
+        assert!(!has_block(&module, "tracing"));
+        assert!(has_block(&module, "legacy"));
+        let server = module.find_by_path("server").unwrap();
+        assert_eq!(server.get_grouped().unwrap().len(), 2);
+
+        // Without a context version every block is kept
+        let module = load(StandardLoader::default());
+        assert!(has_block(&module, "tracing"));
+        assert!(has_block(&module, "legacy"));
+        let server = module.find_by_path("server").unwrap();
+        assert_eq!(server.get_grouped().unwrap().len(), 3);
+    }
+
+    #[test]
+    pub fn colon_assignments_from_config() {
+        let mut loader = StandardLoader::builder().colon_assignments(true).build();
+        loader
+            .add_str("main", "host: 'localhost'\nport = 8080\n", None)
+            .unwrap();
+        let module = loader.get_module("main").unwrap();
+        assert!(module.find_by_path("host").is_some());
+        assert!(module.find_by_path("port").is_some());
+
+        let warnings = loader.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].0.contains("'port'"));
+        assert_eq!(warnings[0].1.module.as_deref(), Some("main"));
+
+        // Warnings of an earlier source are cleared by the next one
+        loader.add_str("main", "name = 'app'\n", None).unwrap();
+        assert!(loader.warnings().is_empty());
+
+        let mut loader = StandardLoader::default();
+        assert!(loader.add_str("main", "host: 'localhost'\n", None).is_err());
+    }
+
+    #[test]
+    pub fn strict_booleans_from_config() {
+        let code = "enabled = on\nverbose = true\nmode = Off\n";
+        let value = |loader: &StandardLoader, path: &str| {
+            loader
+                .get_module("main")
+                .and_then(|x| x.find_by_path(path))
+                .and_then(|x| x.get_value())
+                .map(|x| x.data.clone())
+        };
+
+        let mut loader = StandardLoader::default();
+        loader.add_str("main", code, None).unwrap();
+        assert_eq!(value(&loader, "enabled"), Some(Data::Bool(true)));
+        assert_eq!(value(&loader, "mode"), Some(Data::Bool(false)));
+
+        let mut loader = StandardLoader::builder().strict_booleans(true).build();
+        loader.add_str("main", code, None).unwrap();
+        assert_eq!(
+            value(&loader, "enabled"),
+            Some(Data::String("on".to_string()))
+        );
+        assert_eq!(value(&loader, "verbose"), Some(Data::Bool(true)));
+        assert_eq!(
+            value(&loader, "mode"),
+            Some(Data::String("Off".to_string()))
+        );
+    }
+
+    #[test]
+    pub fn allow_duplicate_keys_from_config() {
+        let code = "[server]\nport = 80\nport = 8080\n";
+        let mut loader = StandardLoader::default();
+        assert!(matches!(
+            loader.add_str("main", code, None),
+            Err(error::Error::Parse { source, .. })
+                if matches!(*source, error::Error::DuplicateKey { .. })
+        ));
+
+        let mut loader = StandardLoader::builder().allow_duplicate_keys(true).build();
+        let module = loader.add_str("main", code, None).unwrap().load().unwrap();
+        let port = module.find_by_path("server.port").unwrap();
+        assert_eq!(port.get_value().unwrap().as_int(), Some(&8080));
+    }
+
+    #[test]
+    pub fn forbid_macros_from_config() {
+        let mut loader = StandardLoader::builder().forbid_macros(true).build();
+        loader
+            .add_str(
+                "main",
+                "name = 'app'\n[server]\nlimits = { owner = m'{name}' }\n",
+                None,
+            )
+            .unwrap();
+        assert!(matches!(
+            loader.load(),
+            Err(error::Error::MacrosForbidden { location }) if location.line == 2
+        ));
+        assert!(matches!(
+            loader.validate(),
+            Err(error::Error::MacrosForbidden { .. })
+        ));
+
+        // Forbidding macros also applies when they would not be resolved
+        let mut loader = StandardLoader::builder()
+            .forbid_macros(true)
+            .resolve_macros(false)
+            .build();
+        loader.add_str("main", "alias = m!name\n", None).unwrap();
+        assert!(loader.load().is_err());
+
+        let mut loader = StandardLoader::builder().forbid_macros(true).build();
+        loader
+            .add_str("main", "name = 'app'\nlevel = :info\n", None)
+            .unwrap();
+        assert!(loader.validate().is_ok());
+        assert!(loader.load().is_ok());
+    }
+
+    #[test]
+    pub fn recursion_limit_from_config() {
+        let mut source = (0..150)
+            .map(|x| format!("link{} = m!link{}\n", x, x + 1))
+            .collect::<String>();
+        source.push_str("link150 = 'end'\n");
+
+        let mut loader = StandardLoader::default();
+        loader.add_str("main", &source, None).unwrap();
+        assert!(matches!(
+            loader.load(),
+            Err(error::Error::RecursionLimit { limit: 100, .. })
+        ));
+
+        let mut loader = StandardLoader::builder().max_recursion_depth(200).build();
+        loader.add_str("main", &source, None).unwrap();
+        loader.validate().unwrap();
+        let module = loader.load().unwrap();
+        let first = module.find_by_path("link0").and_then(|x| x.get_value());
+        assert_eq!(
+            first.and_then(|x| x.as_string()).map(String::as_str),
+            Some("end")
+        );
+
+        let mut loader = StandardLoader::builder().max_recursion_depth(120).build();
+        loader.add_str("main", &source, None).unwrap();
+        assert!(matches!(
+            loader.load(),
+            Err(error::Error::RecursionLimit { limit: 120, .. })
+        ));
+    }
+
+    #[test]
+    pub fn nesting_limit_from_config() {
+        let mut loader = StandardLoader::builder().max_nesting_depth(4).build();
+        let Err(error) = loader.add_str("main", "value = [[[[1]]]]\n", None) else {
+            panic!("expected a nesting error");
+        };
+
+        let error::Error::Parse { source, .. } = error else {
+            panic!("expected a parse error, found {error:?}");
+        };
+        assert!(matches!(
+            *source,
+            error::Error::NestingLimit { limit: 4, .. }
+        ));
+
+        let mut loader = StandardLoader::builder().max_nesting_depth(7).build();
+        assert!(loader.add_str("main", "value = [[[[1]]]]\n", None).is_ok());
+    }
+
+    fn layer(name: &str, code: &str) -> Statement {
+        Parser::new(name, Token::lexer(code)).parse().unwrap()
+    }
+
+    #[test]
+    pub fn overlay_layers() {
+        let mut loader = StandardLoader::default();
+        loader
+            .add_str(
+                "main",
+                "[server]\nhost = 'localhost'\nport = 80\nworkers = 2\n[log]\nlevel = 'info'\n",
+                Some("defaults".to_string()),
+            )
+            .unwrap()
+            .overlay(
+                layer(
+                    "environment",
+                    "[server]\nhost = 'example.com'\nport = 443\n",
+                ),
+                layer("local", "[server]\nport = 8443\n[log]\nlevel = 'debug'\n"),
+            )
+            .unwrap();
+
+        let result = loader.load().unwrap();
+        let value = |path: &str| {
+            result
+                .find_by_path(path)
+                .unwrap()
+                .get_value()
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(value("server.host").as_string().unwrap(), "example.com");
+        assert_eq!(value("server.port").as_int(), Some(&8443));
+        assert_eq!(value("server.workers").as_int(), Some(&2));
+        assert_eq!(value("log.level").as_string().unwrap(), "debug");
+
+        let provenance = loader.provenance();
+        let layers: Vec<(&str, &str)> = provenance
+            .iter()
+            .map(|(path, location)| (path.as_str(), location.module.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            layers,
+            vec![
+                ("server.host", "environment"),
+                ("server.port", "local"),
+                ("server.workers", "defaults"),
+                ("log.level", "local"),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn overlay_normalizes_keys() {
+        let mut loader = StandardLoader::builder()
+            .case_insensitive_keys(true)
+            .collect_collisions(true)
+            .build();
+        loader
+            .add_str("main", "[Server]\nPort = 80\n", None)
+            .unwrap()
+            .overlay(
+                layer("environment", "[server]\nport = 443\n"),
+                layer("local", "[SERVER]\nHost = 'example.com'\n"),
+            )
+            .unwrap();
+        assert!(loader.collisions().is_empty());
+
+        let result = loader.load().unwrap();
+        assert_eq!(result.child_count(), 1);
+        let server = result.find_child("server").unwrap();
+        assert_eq!(server.get_grouped().unwrap().len(), 2);
+        let port = result.find_by_path("server.port").unwrap().get_value();
+        assert_eq!(port.unwrap().as_int(), Some(&443));
+        assert!(result.find_by_path("server.host").is_some());
+
+        // Keys colliding within one layer are collected like any other collision
+        loader
+            .overlay(
+                layer("environment", "[log]\nLevel = 'info'\nlevel = 'warn'\n"),
+                layer("local", "[log]\n"),
+            )
+            .unwrap();
+        let paths: Vec<&str> = loader
+            .collisions()
+            .iter()
+            .map(|x| x.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["log.level"]);
+    }
 }
@@ -153,6 +153,61 @@ impl<'source> Walk<'source> {
         unreachable!("Path navigation should have returned or errored")
     }
 
+    /// Locate the value at a dot-separated path
+    ///
+    /// Statements are navigated with `Statement::find_by_path`, while table values
+    /// are navigated key by key.
+    fn find_value(&self, path: &str) -> Result<&'source Value> {
+        match self {
+            Self::Statement(stmt) => {
+                let target = stmt.find_by_path(path).context(error::NoFieldSnafu {
+                    location: stmt.meta.location.clone(),
+                    field: path.to_string(),
+                })?;
+
+                target.get_value().context(error::NoValueSnafu {
+                    location: target.meta.location.clone(),
+                    field: path.to_string(),
+                })
+            }
+            Self::Value(value) => path.split('.').try_fold(*value, |current, part| {
+                let table = current.as_table().context(error::NotScopeSnafu {
+                    location: current.meta.location.clone(),
+                })?;
+
+                table.get(part).context(error::NoFieldSnafu {
+                    location: current.meta.location.clone(),
+                    field: path.to_string(),
+                })
+            }),
+        }
+    }
+
+    /// Get a string value by dot-separated path
+    pub fn get_string(&self, path: &str) -> Result<String> {
+        self.find_value(path)?.try_into()
+    }
+
+    /// Get a signed 64-bit integer value by dot-separated path
+    pub fn get_i64(&self, path: &str) -> Result<i64> {
+        self.find_value(path)?.try_into()
+    }
+
+    /// Get a boolean value by dot-separated path
+    pub fn get_bool(&self, path: &str) -> Result<bool> {
+        self.find_value(path)?.try_into()
+    }
+
+    /// Get a 64-bit floating point value by dot-separated path
+    pub fn get_f64(&self, path: &str) -> Result<f64> {
+        self.find_value(path)?.try_into()
+    }
+
+    /// Get an array value by dot-separated path
+    pub fn get_array(&self, path: &str) -> Result<Vec<Value>> {
+        self.find_value(path)?.try_into()
+    }
+
     /// Get the identifier of the current statement
     pub fn get_id(&self) -> Option<&str> {
         match self {
@@ -366,6 +421,7 @@ impl fmt::Display for NodeType {
                 match stmt_type {
                     crate::StatementType::Control(_) => "Control",
                     crate::StatementType::Assignment(_) => "Assignment",
+                    crate::StatementType::Append(_) => "Append",
                     crate::StatementType::Block { .. } => "Block",
                     crate::StatementType::Section(_) => "Section",
                     crate::StatementType::Module(_) => "Module",
@@ -462,4 +518,52 @@ mod tests {
         let node_type = walker.node_type();
         assert!(matches!(node_type, NodeType::Statement(_)));
     }
+
+    const TYPED_DOCUMENT: &str = r#"
+name = "barkml"
+[server]
+port = 8080
+enabled = true
+ratio = 0.75
+tags = ["a", "b"]
+limits = { retries = 3 }
+"#;
+
+    #[test]
+    fn test_typed_getters() {
+        let module = crate::from_str(TYPED_DOCUMENT).unwrap();
+        let walker = Walk::new(&module);
+
+        assert_eq!(walker.get_string("name").unwrap(), "barkml");
+        assert_eq!(walker.get_i64("server.port").unwrap(), 8080);
+        assert!(walker.get_bool("server.enabled").unwrap());
+        assert_eq!(walker.get_f64("server.ratio").unwrap(), 0.75);
+
+        let tags = walker.get_array("server.tags").unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].as_string().unwrap(), "a");
+
+        let limits = module.find_by_path("server.limits").unwrap();
+        let limits = Walk::from_value(limits.get_value().unwrap());
+        assert_eq!(limits.get_i64("retries").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_typed_getter_errors() {
+        let module = crate::from_str(TYPED_DOCUMENT).unwrap();
+        let walker = Walk::new(&module);
+
+        assert!(matches!(
+            walker.get_string("server.missing"),
+            Err(error::Error::NoField { .. })
+        ));
+        assert!(matches!(
+            walker.get_string("server"),
+            Err(error::Error::NoValue { .. })
+        ));
+        assert!(matches!(
+            walker.get_bool("server.port"),
+            Err(error::Error::ImplicitConvert { .. })
+        ));
+    }
 }
@@ -0,0 +1,204 @@
+use super::{Loader, LoaderConfig, StandardLoader, utils};
+use crate::{Result, ast::Statement, error};
+use notify::{RecursiveMode, Watcher};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// How long watched files must stay unchanged before they are reloaded
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Handle to the file watcher started by `StandardLoader::watch`
+///
+/// Every burst of changes to the watched files produces one message holding either
+/// the re-read and resolved main module or the error that prevented loading it.
+/// Watching stops when the handle is dropped.
+pub struct WatchHandle {
+    receiver: Receiver<Result<Statement>>,
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl WatchHandle {
+    /// Blocks until the next reload, returns `None` once the watcher has stopped
+    pub fn recv(&self) -> Option<Result<Statement>> {
+        self.receiver.recv().ok()
+    }
+
+    /// Returns the next reload if one is ready without blocking
+    pub fn try_recv(&self) -> Option<Result<Statement>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Blocks for up to the given timeout waiting for the next reload
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Result<Statement>> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+}
+
+impl StandardLoader {
+    /// Watches files and directories and reloads them whenever they change
+    ///
+    /// A reload uses the configuration of this loader, files are merged into the main
+    /// module in the given order as with `add_file` and directories are read with `add_dir`.
+    /// Modules added to this loader in any other way are not part of a reload. Changes are
+    /// debounced by `WATCH_DEBOUNCE` so a burst of writes results in a single reload.
+    pub fn watch(&self, paths: &[PathBuf]) -> Result<WatchHandle> {
+        let watch_error = |reason: String| error::Error::Io { reason };
+
+        let mut targets = Vec::with_capacity(paths.len());
+        for path in paths {
+            utils::validate_path(path)?;
+            targets.push(std::path::absolute(path).map_err(|e| {
+                watch_error(format!("Failed to resolve '{}': {}", path.display(), e))
+            })?);
+        }
+
+        let (event_sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = event_sender.send(event);
+        })
+        .map_err(|e| watch_error(format!("Failed to create file watcher: {}", e)))?;
+
+        // Files are watched through their directory so that editors replacing the
+        // file instead of writing to it are still noticed
+        for target in &targets {
+            let watched = match target.parent() {
+                Some(parent) if !target.is_dir() => parent,
+                _ => target.as_path(),
+            };
+            watcher
+                .watch(watched, RecursiveMode::NonRecursive)
+                .map_err(|e| {
+                    watch_error(format!("Failed to watch '{}': {}", watched.display(), e))
+                })?;
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let config = self.config().clone();
+        std::thread::spawn(move || {
+            let mut pending = false;
+            loop {
+                let next = if pending {
+                    events.recv_timeout(WATCH_DEBOUNCE)
+                } else {
+                    events.recv().map_err(|_| RecvTimeoutError::Disconnected)
+                };
+                let message = match next {
+                    Ok(Ok(event)) => {
+                        pending |= is_relevant(&event, &targets);
+                        continue;
+                    }
+                    Ok(Err(e)) => Err(watch_error(format!("Failed to watch files: {}", e))),
+                    Err(RecvTimeoutError::Timeout) => {
+                        pending = false;
+                        reload(&config, &targets)
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return,
+                };
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(WatchHandle {
+            receiver,
+            _watcher: watcher,
+        })
+    }
+}
+
+fn is_relevant(event: &notify::Event, targets: &[PathBuf]) -> bool {
+    !event.kind.is_access()
+        && event.paths.iter().any(|path| {
+            targets
+                .iter()
+                .any(|target| path == target || path.parent() == Some(target.as_path()))
+        })
+}
+
+fn reload(config: &LoaderConfig, targets: &[PathBuf]) -> Result<Statement> {
+    let mut loader = StandardLoader::new(config.clone());
+    for target in targets {
+        if target.is_dir() {
+            loader.add_dir(target)?;
+        } else {
+            loader.add_file(target)?;
+        }
+    }
+    loader.load()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::time::Instant;
+
+    /// Removes the temporary directory when dropped, even if the test fails
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Receives reloads until one passes the check, panicking after the timeout
+    fn reload_until<F>(handle: &WatchHandle, timeout: Duration, check: F) -> Result<Statement>
+    where
+        F: Fn(&Result<Statement>) -> bool,
+    {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let reload = handle
+                .recv_timeout(remaining)
+                .expect("a matching reload before the timeout");
+            if check(&reload) {
+                return reload;
+            }
+        }
+    }
+
+    fn port(reload: &Result<Statement>) -> Option<i64> {
+        let statement = reload.as_ref().ok()?;
+        let value = statement.find_by_path("port")?.get_value()?;
+        value.as_int().copied()
+    }
+
+    /// Rewrites the file until the watcher reports it, so events are known to arrive
+    fn wait_until_ready(handle: &WatchHandle, path: &Path, code: &str) {
+        for _ in 0..50 {
+            std::fs::write(path, code).unwrap();
+            if handle.recv_timeout(Duration::from_millis(200)).is_some() {
+                return;
+            }
+        }
+        panic!("the watcher never reported a change");
+    }
+
+    #[test]
+    fn watch_reloads_changed_files() {
+        let dir =
+            TempDir(std::env::temp_dir().join(format!("barkml-watch-{}", std::process::id())));
+        std::fs::create_dir_all(&dir.0).unwrap();
+        let path = dir.0.join("app.bml");
+        std::fs::write(&path, "port = 80\n").unwrap();
+
+        let handle = StandardLoader::default()
+            .watch(std::slice::from_ref(&path))
+            .unwrap();
+        wait_until_ready(&handle, &path, "port = 80\n");
+
+        // Reloads still queued from the handshake are skipped until the change shows up
+        std::fs::write(&path, "port = 8080\n").unwrap();
+        let reload = reload_until(&handle, Duration::from_secs(10), |x| port(x) == Some(8080));
+        assert_eq!(port(&reload), Some(8080));
+
+        // Parse errors are delivered instead of stopping the watcher
+        std::fs::write(&path, "port = \n").unwrap();
+        let reload = reload_until(&handle, Duration::from_secs(10), |x| x.is_err());
+        assert!(reload.is_err());
+    }
+}
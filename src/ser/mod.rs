@@ -13,7 +13,9 @@
 use serde::Serialize;
 
 // Local crate
-use crate::{Location, Metadata, Result, Statement, Value};
+use crate::{
+    Data, Location, Metadata, Result, Statement, StatementData, StatementType, Value, ValueType,
+};
 
 pub(crate) mod error;
 mod statement;
@@ -26,6 +28,9 @@ pub use value::*;
 ///
 /// This is the main entry point for serializing Rust data structures to BarkML statements.
 /// It supports serializing structs, maps, and other complex data types to statement groups.
+/// Statements are created in the order fields and entries are visited, so structs and
+/// ordered maps such as `IndexMap` keep their order while `HashMap` output varies between
+/// runs, use [`to_statement_sorted`] for a stable order in that case.
 ///
 /// # Errors
 ///
@@ -60,6 +65,120 @@ where
     Ok(result)
 }
 
+/// Options controlling how Rust data structures are serialized to BarkML
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SerializerConfig {
+    /// Sort the keys of every table and statement group lexically, this makes the output
+    /// independent of the iteration order of the source maps
+    pub sort_keys: bool,
+}
+
+/// Serialize a type `T` to a BarkML `Statement` using the given configuration.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be serialized to a BarkML statement or if
+/// any nested values fail to serialize.
+pub fn to_statement_with<T>(value: &T, config: &SerializerConfig) -> Result<Statement>
+where
+    T: Serialize,
+{
+    let mut result = to_statement(value)?;
+    if config.sort_keys {
+        sort_statement(&mut result);
+    }
+    Ok(result)
+}
+
+/// Serialize a type `T` to a BarkML `Statement` with all keys sorted lexically.
+///
+/// This produces the same statement regardless of map iteration order, which keeps
+/// generated configuration files stable and diff-friendly.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be serialized to a BarkML statement or if
+/// any nested values fail to serialize.
+pub fn to_statement_sorted<T>(value: &T) -> Result<Statement>
+where
+    T: Serialize,
+{
+    to_statement_with(value, &SerializerConfig { sort_keys: true })
+}
+
+/// Serialize a type `T` to a BarkML `Value` using the given configuration.
+///
+/// # Errors
+///
+/// Returns an error if the value type cannot be represented as a BarkML value or if
+/// the serialization process fails.
+pub fn to_value_with<T>(value: &T, config: &SerializerConfig) -> Result<Value>
+where
+    T: Serialize,
+{
+    let mut result = to_value(value)?;
+    if config.sort_keys {
+        sort_value(&mut result);
+    }
+    Ok(result)
+}
+
+fn sort_statement(statement: &mut Statement) {
+    sort_statement_type(&mut statement.type_);
+    match &mut statement.data {
+        StatementData::Labeled(labels, children) => {
+            labels.iter_mut().for_each(sort_value);
+            children.sort_keys();
+            children.values_mut().for_each(sort_statement);
+        }
+        StatementData::Group(children) => {
+            children.sort_keys();
+            children.values_mut().for_each(sort_statement);
+        }
+        StatementData::Single(value) => sort_value(value),
+    }
+}
+
+fn sort_statement_type(type_: &mut StatementType) {
+    match type_ {
+        StatementType::Control(vt) | StatementType::Assignment(vt) | StatementType::Append(vt) => {
+            sort_value_type(vt)
+        }
+        StatementType::Block { labels, contents } => {
+            labels.iter_mut().for_each(sort_value_type);
+            contents.sort_keys();
+            contents.values_mut().for_each(sort_statement_type);
+        }
+        StatementType::Section(contents) | StatementType::Module(contents) => {
+            contents.sort_keys();
+            contents.values_mut().for_each(sort_statement_type);
+        }
+    }
+}
+
+fn sort_value(value: &mut Value) {
+    match &mut value.data {
+        Data::Array(children) => children.iter_mut().for_each(sort_value),
+        Data::Table(children) => {
+            children.sort_keys();
+            children.values_mut().for_each(sort_value);
+        }
+        _ => {}
+    }
+}
+
+fn sort_value_type(type_: &mut ValueType) {
+    match type_ {
+        ValueType::Array(children) => children.iter_mut().for_each(sort_value_type),
+        ValueType::Table(children) => {
+            children.sort_keys();
+            children.values_mut().for_each(sort_value_type);
+        }
+        ValueType::Optional(inner) => sort_value_type(inner),
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // External crates
@@ -94,6 +213,9 @@ mod tests {
         features: Vec<String>,
     }
 
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct RawBytes(#[serde(with = "serde_bytes")] Vec<u8>);
+
     #[test]
     fn serialize_simple_values_works_correctly() {
         // Arrange & Act - Test string
@@ -170,6 +292,32 @@ mod tests {
         assert_eq!(deserialized, map);
     }
 
+    #[test]
+    fn serialize_statement_preserves_order_works_correctly() {
+        // Arrange
+        let mut sections = IndexMap::new();
+        for name in ["zeta", "alpha", "mid", "delta", "omega"] {
+            let mut section = IndexMap::new();
+            section.insert("z".to_string(), 1);
+            section.insert("a".to_string(), 2);
+            sections.insert(name.to_string(), section);
+        }
+
+        // Act
+        let statement = to_statement(&sections).expect("should serialize");
+
+        // Assert
+        let ids = statement
+            .children()
+            .map(|x| x.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["zeta", "alpha", "mid", "delta", "omega"]);
+        for section in statement.children() {
+            let table = section.get_value().and_then(|x| x.as_table()).unwrap();
+            assert_eq!(table.keys().collect::<Vec<_>>(), vec!["z", "a"]);
+        }
+    }
+
     #[test]
     fn roundtrip_serialization_works_correctly() {
         // Arrange
@@ -190,4 +338,84 @@ mod tests {
         // Assert
         assert_eq!(roundtrip, original);
     }
+
+    #[test]
+    fn bytes_roundtrip_as_byte_strings() {
+        // Arrange
+        let buffer = serde_bytes::ByteBuf::from(b"binarystring".to_vec());
+        let raw = RawBytes(vec![0, 159, 146, 150, 255]);
+
+        // Act
+        let buffer_value = to_value(&buffer).expect("should serialize byte buffer");
+        let raw_value = to_value(&raw).expect("should serialize raw bytes");
+
+        // Assert - Bytes are stored as byte strings rather than integer arrays
+        assert_eq!(
+            buffer_value.data,
+            crate::Data::Bytes(b"binarystring".to_vec())
+        );
+        assert_eq!(buffer_value.to_string(), "b'YmluYXJ5c3RyaW5n'");
+        assert_eq!(
+            raw_value.data,
+            crate::Data::Bytes(vec![0, 159, 146, 150, 255])
+        );
+        assert_eq!(raw_value.to_string(), "b'AJ+Slv8='");
+
+        let buffer_back: serde_bytes::ByteBuf =
+            from_value(&buffer_value).expect("should deserialize byte buffer");
+        let raw_back: RawBytes = from_value(&raw_value).expect("should deserialize raw bytes");
+        assert_eq!(buffer_back, buffer);
+        assert_eq!(raw_back, raw);
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Inventory {
+        name: String,
+        items: std::collections::HashMap<String, u32>,
+    }
+
+    fn sample_inventory() -> Inventory {
+        Inventory {
+            name: "depot".to_string(),
+            items: (0..16)
+                .map(|i| (format!("item-{:02}", 15 - i), i))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn sorted_serialization_is_stable() {
+        // Arrange
+        let expected: Vec<String> = (0..16).map(|i| format!("item-{:02}", i)).collect();
+
+        // Act
+        let first = to_statement_sorted(&sample_inventory()).expect("should serialize");
+        let second = to_statement_sorted(&sample_inventory()).expect("should serialize");
+        let value = to_value_with(
+            &sample_inventory().items,
+            &SerializerConfig { sort_keys: true },
+        )
+        .expect("should serialize value");
+
+        // Assert
+        let keys = |statement: &Statement| -> Vec<String> {
+            let items = statement.find_child("items").expect("should have items");
+            match &items.data {
+                StatementData::Group(children) => children.keys().cloned().collect(),
+                StatementData::Single(value) => value.as_table().unwrap().keys().cloned().collect(),
+                StatementData::Labeled(..) => panic!("unexpected block"),
+            }
+        };
+        assert_eq!(keys(&first), expected);
+        assert_eq!(keys(&first), keys(&second));
+        assert_eq!(
+            value
+                .as_table()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
 }
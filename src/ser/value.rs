@@ -12,6 +12,7 @@ use serde::ser::{
 use super::error::{Error, Result};
 
 // Local crate
+use crate::interop::semver::{REQUIRE_MARKER, VERSION_MARKER};
 use crate::{Metadata, Value};
 
 /// Serializer for BarkML values.
@@ -125,11 +126,29 @@ impl<'a> Serializer for &'a mut ValueSerializer {
         ))
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Value>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Value>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        // Versions and requirements marked by the semver field helpers keep their types
+        if name != VERSION_MARKER && name != REQUIRE_MARKER {
+            return value.serialize(self);
+        }
+        let metadata = self.metadata.clone();
+        let serialized = value.serialize(self)?;
+        let text = serialized.as_string().ok_or_else(|| Error::Message {
+            message: format!("expected a string for '{name}'"),
+        })?;
+        let message = |e: ::semver::Error| Error::Message {
+            message: format!("invalid semantic version '{text}': {e}"),
+        };
+        if name == VERSION_MARKER {
+            let version = ::semver::Version::parse(text).map_err(message)?;
+            Ok(Value::new_version(version, metadata))
+        } else {
+            let require = ::semver::VersionReq::parse(text).map_err(message)?;
+            Ok(Value::new_require(require, metadata))
+        }
     }
 
     fn serialize_newtype_variant<T>(
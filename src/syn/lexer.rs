@@ -1,7 +1,7 @@
 use crate::{Result, error};
 use base64::Engine;
 use logos::{Lexer, Logos, Skip};
-use snafu::ResultExt;
+use snafu::{ResultExt, ensure};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
@@ -43,6 +43,7 @@ fn base_callback(lex: &mut Lexer<Token>) -> Location {
         source_text: Some(source_text),
         length: span.end - span.start,
         file_path: None,
+        byte_start: span.start,
     }
 }
 
@@ -91,12 +92,16 @@ pub enum Token {
     RParen(Location),
     #[token("=", base_callback)]
     Assign(Location),
+    #[token("+=", base_callback)]
+    AppendAssign(Location),
     #[token(":", base_callback)]
     Colon(Location),
     #[token("?", base_callback)]
     Question(Location),
     #[token(",", base_callback)]
     Comma(Location),
+    #[token("---", document_separator)]
+    DocumentSeparator(Location),
 
     // Keywords
     #[token("null", base_callback, priority = 10)]
@@ -143,18 +148,28 @@ pub enum Token {
     KeyVersion(Location),
     #[token("require", base_callback, priority = 10)]
     KeyRequire(Location),
+    #[token("datetime", base_callback, priority = 10)]
+    KeyDateTime(Location),
+    #[token("decimal", base_callback, priority = 10)]
+    KeyDecimal(Location),
     #[token("label", base_callback, priority = 10)]
     KeyLabel(Location),
     #[token("array", base_callback, priority = 10)]
     KeyArray(Location),
     #[token("table", base_callback, priority = 10)]
     KeyTable(Location),
+    #[token("enum", base_callback, priority = 10)]
+    KeyEnum(Location),
     #[token("section", base_callback, priority = 10)]
     KeySection(Location),
     #[token("block", base_callback, priority = 10)]
     KeyBlock(Location),
     #[token("symbol", base_callback, priority = 10)]
     KeySymbol(Location),
+    #[token("include", base_callback, priority = 10)]
+    KeyInclude(Location),
+    #[token("any", base_callback, priority = 10)]
+    KeyAny(Location),
 
     // Unused but reserved
     #[token("module", base_callback, priority = 10)]
@@ -190,21 +205,29 @@ pub enum Token {
         r"[+-]?[0-9][0-9_]*\.[0-9][0-9_]*([eE][+-]?[0-9][0-9_]*)?(f64|f32)?",
         float
     )]
+    #[token("inf", special_float, priority = 11)]
+    #[token("-inf", special_float, priority = 11)]
+    #[token("nan", special_float, priority = 11)]
     Float((Location, HashableFloat)),
 
     #[regex(r"m'[^']*'", macro_string)]
     MacroString((Location, String)),
     #[regex(r"b'[-A-Za-z0-9+/]*={0,3}'", byte_string)]
+    #[regex(r"x'[^']*'", hex_string)]
     ByteString((Location, Vec<u8>)),
+    // Regular expressions are read raw so backslashes reach the pattern untouched
+    #[regex(r"r'[^']*'", regex_string)]
+    #[regex(r#"r"[^"]*""#, regex_string)]
+    Regex((Location, String)),
     #[regex(r"'[^']*'", quote_string)]
-    #[regex(r#""[^"]*""#, quote_string)]
+    #[regex(r#""[^"]*""#, double_quote_string)]
     String((Location, String)),
 
     #[regex(r"[a-zA-Z][a-zA-Z0-9_\-]*", |x| {
         (base_callback(x), x.slice().to_string()) }, priority = 5
     )]
     Identifier((Location, String)),
-    #[regex(r"m\![a-zA-Z][a-zA-Z0-9_\-\.]*", |x| {
+    #[regex(r"m\!@?[a-zA-Z][a-zA-Z0-9_\-\.]*", |x| {
         (base_callback(x), x.slice().trim_start_matches("m!").to_string()) }
     , priority = 6)]
     MacroIdentifier((Location, String)),
@@ -230,6 +253,41 @@ pub enum Token {
     )]
     Require((Location, semver::VersionReq)),
 
+    #[regex(
+        r"[0-9]{4}-[0-9]{2}-[0-9]{2}[Tt][0-9]{2}:[0-9]{2}:[0-9]{2}(\.[0-9]+)?([Zz]|[+-][0-9]{2}:[0-9]{2})",
+        |x| { (base_callback(x), x.slice().to_string()) }
+    )]
+    DateTime((Location, String)),
+
+    // Fixed-point decimal, kept as text so it can be parsed exactly
+    #[regex(r"[+-]?[0-9][0-9_]*(\.[0-9][0-9_]*)?d", |x| {
+        (base_callback(x), x.slice().trim_end_matches('d').replace('_', ""))
+    })]
+    Decimal((Location, String)),
+
+    // Network addresses, kept as text and parsed by the parser. IPv4 addresses take
+    // priority over version literals of the same length such as 1.2.3.4
+    #[regex(r"[0-9]{1,3}(\.[0-9]{1,3}){3}", |x| {
+        (base_callback(x), x.slice().to_string())
+    }, priority = 20)]
+    #[regex(r"[0-9a-fA-F]{1,4}(:[0-9a-fA-F]{1,4}){7}", |x| {
+        (base_callback(x), x.slice().to_string())
+    })]
+    #[regex(r"([0-9a-fA-F]{1,4}(:[0-9a-fA-F]{1,4})*)?::([0-9a-fA-F]{1,4}(:[0-9a-fA-F]{1,4})*)?", |x| {
+        (base_callback(x), x.slice().to_string())
+    })]
+    Ip((Location, String)),
+    #[regex(r"[0-9]{1,3}(\.[0-9]{1,3}){3}/[0-9]+", |x| {
+        (base_callback(x), x.slice().to_string())
+    })]
+    #[regex(r"[0-9a-fA-F]{1,4}(:[0-9a-fA-F]{1,4}){7}/[0-9]+", |x| {
+        (base_callback(x), x.slice().to_string())
+    })]
+    #[regex(r"([0-9a-fA-F]{1,4}(:[0-9a-fA-F]{1,4})*)?::([0-9a-fA-F]{1,4}(:[0-9a-fA-F]{1,4})*)?/[0-9]+", |x| {
+        (base_callback(x), x.slice().to_string())
+    })]
+    Cidr((Location, String)),
+
     #[regex(r"(#[ \t\f]*[^\n\r]+[\n\r])*", line_comment)]
     LineComment((Location, String)),
     #[regex(r"\/\*[^\/\*]*\*\/", multiline_comment)]
@@ -238,6 +296,20 @@ pub enum Token {
 
 impl Token {
     pub fn location(&self, module: Option<String>) -> Location {
+        match self.source() {
+            Some(source) => {
+                let mut src = source.clone();
+                if let Some(module_name) = module.as_ref() {
+                    src.set_module(module_name.as_str());
+                }
+                src
+            }
+            None => Location::default(),
+        }
+    }
+
+    /// Returns the location recorded by the lexer for this token
+    pub(crate) fn source(&self) -> Option<&Location> {
         match self {
             Self::Error(source)
             | Self::True(source)
@@ -251,9 +323,11 @@ impl Token {
             | Self::LParen(source)
             | Self::RParen(source)
             | Self::Assign(source)
+            | Self::AppendAssign(source)
             | Self::Colon(source)
             | Self::Question(source)
             | Self::Comma(source)
+            | Self::DocumentSeparator(source)
             | Self::KeyNull(source)
             | Self::KeyBool(source)
             | Self::KeyString(source)
@@ -272,19 +346,26 @@ impl Token {
             | Self::KeyBytes(source)
             | Self::KeyVersion(source)
             | Self::KeyRequire(source)
+            | Self::KeyDateTime(source)
+            | Self::KeyDecimal(source)
             | Self::KeyLabel(source)
             | Self::KeyArray(source)
             | Self::KeyTable(source)
+            | Self::KeyEnum(source)
             | Self::KeySection(source)
             | Self::KeyBlock(source)
+            | Self::KeyInclude(source)
+            | Self::KeyAny(source)
             | Self::KeyModule(source)
             | Self::KeyUse(source)
             | Self::KeyAs(source)
             | Self::KeySchema(source)
+            | Self::KeySymbol(source)
             | Self::Int((source, ..))
             | Self::Float((source, ..))
             | Self::MacroString((source, ..))
             | Self::LabelIdentifier((source, ..))
+            | Self::SymbolIdentifier((source, ..))
             | Self::ByteString((source, ..))
             | Self::String((source, ..))
             | Self::Identifier((source, ..))
@@ -292,18 +373,130 @@ impl Token {
             | Self::ControlIdentifier((source, ..))
             | Self::Version((source, ..))
             | Self::Require((source, ..))
+            | Self::DateTime((source, ..))
+            | Self::Decimal((source, ..))
+            | Self::Ip((source, ..))
+            | Self::Cidr((source, ..))
+            | Self::Regex((source, ..))
             | Self::LineComment((source, ..))
-            | Self::MultiLineComment((source, ..)) => {
-                let mut src = source.clone();
-                if let Some(module_name) = module.as_ref() {
-                    src.set_module(module_name.as_str());
-                }
-                src
-            }
-            _ => Location::default(),
+            | Self::MultiLineComment((source, ..)) => Some(source),
+            _ => None,
         }
     }
 
+    /// Returns the location recorded by the lexer for this token, for filling in details
+    /// the lexer does not know about such as the module and file path
+    pub(crate) fn source_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            Self::Error(source)
+            | Self::True(source)
+            | Self::False(source)
+            | Self::Exclaim(source)
+            | Self::Dollar(source)
+            | Self::LBracket(source)
+            | Self::RBracket(source)
+            | Self::LBrace(source)
+            | Self::RBrace(source)
+            | Self::LParen(source)
+            | Self::RParen(source)
+            | Self::Assign(source)
+            | Self::AppendAssign(source)
+            | Self::Colon(source)
+            | Self::Question(source)
+            | Self::Comma(source)
+            | Self::DocumentSeparator(source)
+            | Self::KeyNull(source)
+            | Self::KeyBool(source)
+            | Self::KeyString(source)
+            | Self::KeyInt(source)
+            | Self::KeyInt8(source)
+            | Self::KeyInt16(source)
+            | Self::KeyInt32(source)
+            | Self::KeyInt64(source)
+            | Self::KeyUInt8(source)
+            | Self::KeyUInt16(source)
+            | Self::KeyUInt32(source)
+            | Self::KeyUInt64(source)
+            | Self::KeyFloat(source)
+            | Self::KeyFloat32(source)
+            | Self::KeyFloat64(source)
+            | Self::KeyBytes(source)
+            | Self::KeyVersion(source)
+            | Self::KeyRequire(source)
+            | Self::KeyDateTime(source)
+            | Self::KeyDecimal(source)
+            | Self::KeyLabel(source)
+            | Self::KeyArray(source)
+            | Self::KeyTable(source)
+            | Self::KeyEnum(source)
+            | Self::KeySection(source)
+            | Self::KeyBlock(source)
+            | Self::KeyInclude(source)
+            | Self::KeyAny(source)
+            | Self::KeyModule(source)
+            | Self::KeyUse(source)
+            | Self::KeyAs(source)
+            | Self::KeySchema(source)
+            | Self::KeySymbol(source)
+            | Self::Int((source, ..))
+            | Self::Float((source, ..))
+            | Self::MacroString((source, ..))
+            | Self::LabelIdentifier((source, ..))
+            | Self::SymbolIdentifier((source, ..))
+            | Self::ByteString((source, ..))
+            | Self::String((source, ..))
+            | Self::Identifier((source, ..))
+            | Self::MacroIdentifier((source, ..))
+            | Self::ControlIdentifier((source, ..))
+            | Self::Version((source, ..))
+            | Self::Require((source, ..))
+            | Self::DateTime((source, ..))
+            | Self::Decimal((source, ..))
+            | Self::Ip((source, ..))
+            | Self::Cidr((source, ..))
+            | Self::Regex((source, ..))
+            | Self::LineComment((source, ..))
+            | Self::MultiLineComment((source, ..)) => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this token is a keyword that names a type in annotations
+    pub const fn is_type_keyword(&self) -> bool {
+        matches!(
+            self,
+            Self::KeyAny(_)
+                | Self::KeyArray(_)
+                | Self::KeyBool(_)
+                | Self::KeyBytes(_)
+                | Self::KeyDateTime(_)
+                | Self::KeyDecimal(_)
+                | Self::KeyEnum(_)
+                | Self::KeyFloat(_)
+                | Self::KeyFloat32(_)
+                | Self::KeyFloat64(_)
+                | Self::KeyInt(_)
+                | Self::KeyInt8(_)
+                | Self::KeyInt16(_)
+                | Self::KeyInt32(_)
+                | Self::KeyInt64(_)
+                | Self::KeyInt128(_)
+                | Self::KeyLabel(_)
+                | Self::KeyNull(_)
+                | Self::KeyRequire(_)
+                | Self::KeyString(_)
+                | Self::KeySymbol(_)
+                | Self::KeyTable(_)
+                | Self::KeyUInt(_)
+                | Self::KeyUInt8(_)
+                | Self::KeyUInt16(_)
+                | Self::KeyUInt32(_)
+                | Self::KeyUInt64(_)
+                | Self::KeyUInt128(_)
+                | Self::KeyVersion(_)
+        )
+    }
+
     /// Compares two tokens ignoring their location information
     pub fn eq_kind(&self, other: &Self) -> bool {
         match (self, other) {
@@ -320,9 +513,11 @@ impl Token {
             (Self::LParen(_), Self::LParen(_)) => true,
             (Self::RParen(_), Self::RParen(_)) => true,
             (Self::Assign(_), Self::Assign(_)) => true,
+            (Self::AppendAssign(_), Self::AppendAssign(_)) => true,
             (Self::Colon(_), Self::Colon(_)) => true,
             (Self::Question(_), Self::Question(_)) => true,
             (Self::Comma(_), Self::Comma(_)) => true,
+            (Self::DocumentSeparator(_), Self::DocumentSeparator(_)) => true,
             (Self::KeyNull(_), Self::KeyNull(_)) => true,
             (Self::KeyBool(_), Self::KeyBool(_)) => true,
             (Self::KeyString(_), Self::KeyString(_)) => true,
@@ -344,12 +539,17 @@ impl Token {
             (Self::KeyBytes(_), Self::KeyBytes(_)) => true,
             (Self::KeyVersion(_), Self::KeyVersion(_)) => true,
             (Self::KeyRequire(_), Self::KeyRequire(_)) => true,
+            (Self::KeyDateTime(_), Self::KeyDateTime(_)) => true,
+            (Self::KeyDecimal(_), Self::KeyDecimal(_)) => true,
             (Self::KeyLabel(_), Self::KeyLabel(_)) => true,
             (Self::KeyArray(_), Self::KeyArray(_)) => true,
             (Self::KeyTable(_), Self::KeyTable(_)) => true,
+            (Self::KeyEnum(_), Self::KeyEnum(_)) => true,
             (Self::KeySection(_), Self::KeySection(_)) => true,
             (Self::KeyBlock(_), Self::KeyBlock(_)) => true,
             (Self::KeySymbol(_), Self::KeySymbol(_)) => true,
+            (Self::KeyInclude(_), Self::KeyInclude(_)) => true,
+            (Self::KeyAny(_), Self::KeyAny(_)) => true,
             (Self::KeyModule(_), Self::KeyModule(_)) => true,
             (Self::KeyUse(_), Self::KeyUse(_)) => true,
             (Self::KeyAs(_), Self::KeyAs(_)) => true,
@@ -366,6 +566,11 @@ impl Token {
             (Self::ControlIdentifier((_, id1)), Self::ControlIdentifier((_, id2))) => id1 == id2,
             (Self::Version((_, ver1)), Self::Version((_, ver2))) => ver1 == ver2,
             (Self::Require((_, req1)), Self::Require((_, req2))) => req1 == req2,
+            (Self::DateTime((_, dt1)), Self::DateTime((_, dt2))) => dt1 == dt2,
+            (Self::Decimal((_, dec1)), Self::Decimal((_, dec2))) => dec1 == dec2,
+            (Self::Ip((_, ip1)), Self::Ip((_, ip2))) => ip1 == ip2,
+            (Self::Cidr((_, cidr1)), Self::Cidr((_, cidr2))) => cidr1 == cidr2,
+            (Self::Regex((_, regex1)), Self::Regex((_, regex2))) => regex1 == regex2,
             (Self::LineComment((_, comment1)), Self::LineComment((_, comment2))) => {
                 comment1 == comment2
             }
@@ -421,6 +626,22 @@ impl From<HashableFloat> for f64 {
     }
 }
 
+/// Reads a `---` document separator, which must be on a line of its own
+fn document_separator(lexer: &mut Lexer<Token>) -> Result<Location> {
+    let location = base_callback(lexer);
+    let start = lexer.span().start;
+    let line_start = lexer.source()[..start].ends_with(['\n', '\r']) || start == 0;
+    let line_end = lexer
+        .remainder()
+        .lines()
+        .next()
+        .is_none_or(|x| x.trim().is_empty());
+    if line_start && line_end {
+        return Ok(location);
+    }
+    error::DocumentSeparatorSnafu { location }.fail()
+}
+
 fn multiline_comment(lexer: &mut Lexer<Token>) -> (Location, String) {
     let slice = lexer.slice();
     let comment = slice.trim_start_matches("/*").trim_end_matches("*/").trim();
@@ -471,6 +692,16 @@ fn quote_string(lexer: &mut Lexer<Token>) -> (Location, String) {
     (base_callback(lexer), value.to_string())
 }
 
+/// Reads a double quoted string, a backslash ending a line joins it with the next line
+fn double_quote_string(lexer: &mut Lexer<Token>) -> Result<(Location, String)> {
+    let (location, value) = quote_string(lexer);
+    if value.ends_with('\\') {
+        return error::LineContinuationSnafu { location }.fail();
+    }
+    let value = value.replace("\\\r\n", "").replace("\\\n", "");
+    Ok((location, value))
+}
+
 fn byte_string(lexer: &mut Lexer<Token>) -> Result<(Location, Vec<u8>)> {
     let slice = lexer
         .slice()
@@ -487,6 +718,38 @@ fn byte_string(lexer: &mut Lexer<Token>) -> Result<(Location, Vec<u8>)> {
     ))
 }
 
+fn hex_string(lexer: &mut Lexer<Token>) -> Result<(Location, Vec<u8>)> {
+    let slice = lexer
+        .slice()
+        .trim_start_matches("x'")
+        .trim_end_matches('\'');
+    let location = base_callback(lexer);
+    if let Some(digit) = slice.chars().find(|x| !x.is_ascii_hexdigit()) {
+        return error::HexSnafu {
+            location,
+            reason: format!("'{digit}' is not a hexadecimal digit"),
+        }
+        .fail();
+    }
+    ensure!(
+        slice.len().is_multiple_of(2),
+        error::HexSnafu {
+            location,
+            reason: format!("odd number of digits ({})", slice.len()),
+        }
+    );
+    let bytes = (0..slice.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&slice[index..index + 2], 16).unwrap_or_default())
+        .collect();
+    Ok((location, bytes))
+}
+
+fn regex_string(lexer: &mut Lexer<Token>) -> (Location, String) {
+    let slice = lexer.slice();
+    (base_callback(lexer), slice[2..slice.len() - 1].to_string())
+}
+
 fn macro_string(lexer: &mut Lexer<Token>) -> (Location, String) {
     let slice = lexer.slice();
     (
@@ -527,6 +790,16 @@ fn float(lexer: &mut Lexer<Token>) -> Result<(Location, HashableFloat)> {
     ))
 }
 
+/// Produces the special floating point values for the `inf`, `-inf` and `nan` keywords
+fn special_float(lexer: &mut Lexer<Token>) -> (Location, HashableFloat) {
+    let value = match lexer.slice() {
+        "nan" => f64::NAN,
+        "-inf" => f64::NEG_INFINITY,
+        _ => f64::INFINITY,
+    };
+    (base_callback(lexer), HashableFloat::Generic(value))
+}
+
 macro_rules! number {
     ($radix_name: ident : $radix: literal [ $($type: ident as $wrap: ident where $suffix: literal),* ] ) => {
         pub(crate) mod $radix_name {
@@ -545,10 +818,20 @@ macro_rules! number {
                         return Ok((location.clone(), super::Integer::$wrap(value)));
                     }
                 )*
-                let value = i64::from_str_radix(slice, $radix).context($crate::error::IntegerSnafu {
+                // Unsuffixed integers widen to the smallest of i64, u64, i128 and u128 that fits
+                if let Ok(value) = i64::from_str_radix(slice, $radix) {
+                    return Ok((location.clone(), super::Integer::Signed(value)));
+                }
+                if let Ok(value) = u64::from_str_radix(slice, $radix) {
+                    return Ok((location.clone(), super::Integer::Unsigned(value)));
+                }
+                if let Ok(value) = i128::from_str_radix(slice, $radix) {
+                    return Ok((location.clone(), super::Integer::I128(value)));
+                }
+                let value = u128::from_str_radix(slice, $radix).context($crate::error::IntegerSnafu {
                     location: location.clone(),
                 })?;
-                Ok((location.clone(), super::Integer::Signed(value)))
+                Ok((location.clone(), super::Integer::U128(value)))
             }
         }
     }
@@ -612,6 +895,8 @@ number!(binary: 2 [
 mod test {
     use super::{HashableFloat, Integer, Token};
     use crate::ast::Location;
+    use crate::error::Error;
+    use assert_matches::assert_matches;
     use logos::Logos;
 
     fn assert_single_token(input: &str, expected: Token) {
@@ -636,6 +921,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_hex_string() {
+        let mut lexer = Token::lexer("x'48656c6C6f' x''");
+        assert_matches!(lexer.next(), Some(Ok(Token::ByteString((_, bytes)))) if bytes == b"Hello");
+        assert_matches!(lexer.next(), Some(Ok(Token::ByteString((_, bytes)))) if bytes.is_empty());
+        assert!(lexer.next().is_none());
+
+        for invalid in ["x'486'", "x'4g'", "x'+1'", "x'é0'"] {
+            let mut lexer = Token::lexer(invalid);
+            assert_matches!(lexer.next().unwrap(), Err(Error::Hex { .. }));
+        }
+    }
+
     #[test]
     fn test_boolean_tokens() {
         // Test all boolean true variants
@@ -666,11 +964,36 @@ mod test {
         assert_single_token("(", Token::LParen(Location::default()));
         assert_single_token(")", Token::RParen(Location::default()));
         assert_single_token("=", Token::Assign(Location::default()));
+        assert_single_token("+=", Token::AppendAssign(Location::default()));
         assert_single_token(":", Token::Colon(Location::default()));
         assert_single_token("?", Token::Question(Location::default()));
         assert_single_token(",", Token::Comma(Location::default()));
     }
 
+    #[test]
+    fn test_document_separator() {
+        assert_single_token("---", Token::DocumentSeparator(Location::default()));
+
+        let tokens = Token::lexer("a = 1\n---\nb = 2\n---  \r\n")
+            .map(|x| x.unwrap())
+            .filter(|x| matches!(x, Token::DocumentSeparator(_)))
+            .map(|x| x.location(None))
+            .map(|x| (x.line, x.column))
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, vec![(1, 0), (3, 0)]);
+
+        for input in ["a = 1 ---", "  ---", "--- a = 1"] {
+            let errors = Token::lexer(input)
+                .filter_map(|x| x.err())
+                .collect::<Vec<_>>();
+            assert_matches!(
+                errors.as_slice(),
+                [Error::DocumentSeparator { .. }],
+                "{input}"
+            );
+        }
+    }
+
     #[test]
     fn test_keyword_tokens() {
         assert_single_token("null", Token::KeyNull(Location::default()));
@@ -682,6 +1005,114 @@ mod test {
         assert_single_token("uint", Token::KeyUInt(Location::default()));
         assert_single_token("float", Token::KeyFloat(Location::default()));
         assert_single_token("bytes", Token::KeyBytes(Location::default()));
+        assert_single_token("include", Token::KeyInclude(Location::default()));
+        assert_single_token("any", Token::KeyAny(Location::default()));
+        assert_single_token("datetime", Token::KeyDateTime(Location::default()));
+        assert_single_token("decimal", Token::KeyDecimal(Location::default()));
+    }
+
+    #[test]
+    fn test_decimal_tokens() {
+        for (input, expected) in [("1.50d", "1.50"), ("-0.1d", "-0.1"), ("1_000d", "1000")] {
+            assert_single_token(
+                input,
+                Token::Decimal((Location::default(), expected.to_string())),
+            );
+        }
+    }
+
+    #[test]
+    fn test_network_tokens() {
+        for input in [
+            "10.0.0.1",
+            "255.255.255.255",
+            "fe80::1",
+            "::",
+            "::1",
+            "1:2:3:4:5:6:7:8",
+        ] {
+            assert_single_token(input, Token::Ip((Location::default(), input.to_string())));
+        }
+        for input in ["10.0.0.0/8", "2001:db8::/32", "::/0"] {
+            assert_single_token(input, Token::Cidr((Location::default(), input.to_string())));
+        }
+
+        // Three parts are still a version, four are an address
+        assert_matches!(Token::lexer("1.2.3").next(), Some(Ok(Token::Version(_))));
+        assert_matches!(
+            Token::lexer("1.2.3-beta.6").next(),
+            Some(Ok(Token::Version(_)))
+        );
+        assert_matches!(Token::lexer("1.2.3.4").next(), Some(Ok(Token::Ip(_))));
+        assert_matches!(Token::lexer("1.2.3.4-beta").next(), Some(Err(_)));
+    }
+
+    #[test]
+    fn test_regex_tokens() {
+        for (input, expected) in [
+            (r"r'^\d+$'", r"^\d+$"),
+            (r#"r"it's [a-z]+""#, "it's [a-z]+"),
+            ("r''", ""),
+        ] {
+            assert_single_token(
+                input,
+                Token::Regex((Location::default(), expected.to_string())),
+            );
+        }
+
+        // A lone r is still an identifier
+        assert_matches!(Token::lexer("r").next(), Some(Ok(Token::Identifier(_))));
+    }
+
+    #[test]
+    fn test_datetime_tokens() {
+        for input in [
+            "2024-01-02T03:04:05Z",
+            "2024-01-02T03:04:05.123+05:30",
+            "2024-13-40T00:00:00-08:00",
+        ] {
+            assert_single_token(
+                input,
+                Token::DateTime((Location::default(), input.to_string())),
+            );
+        }
+    }
+
+    #[test]
+    fn test_integer_widening() {
+        let lex = |input: &str| Token::lexer(input).next().unwrap();
+
+        // i64::MAX stays signed, one past it widens to u64
+        assert_matches!(
+            lex("9223372036854775807"),
+            Ok(Token::Int((_, Integer::Signed(i64::MAX))))
+        );
+        assert_matches!(
+            lex("9223372036854775808"),
+            Ok(Token::Int((_, Integer::Unsigned(9223372036854775808))))
+        );
+        assert_matches!(
+            lex("0xFFFFFFFFFFFFFFFF"),
+            Ok(Token::Int((_, Integer::Unsigned(u64::MAX))))
+        );
+        // Negative values below i64::MIN and values beyond u64 use 128-bit integers
+        assert_matches!(
+            lex("-9223372036854775809"),
+            Ok(Token::Int((_, Integer::I128(-9223372036854775809))))
+        );
+        assert_matches!(
+            lex("18446744073709551616"),
+            Ok(Token::Int((_, Integer::I128(18446744073709551616))))
+        );
+        assert_matches!(
+            lex("340282366920938463463374607431768211455"),
+            Ok(Token::Int((_, Integer::U128(u128::MAX))))
+        );
+        // Values beyond u128 are an error
+        assert_matches!(
+            lex("340282366920938463463374607431768211456"),
+            Err(Error::Integer { .. })
+        );
     }
 
     #[test]
@@ -763,6 +1194,47 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_special_float_tokens() {
+        for (input, check) in [
+            ("inf", f64::is_infinite as fn(f64) -> bool),
+            ("nan", f64::is_nan),
+        ] {
+            let mut lexer = Token::lexer(input);
+            match lexer.next().unwrap() {
+                Ok(Token::Float((_, HashableFloat::Generic(value)))) => assert!(check(value)),
+                other => panic!("Expected Float token for {input}, got {other:?}"),
+            }
+            assert!(lexer.next().is_none());
+        }
+
+        let mut lexer = Token::lexer("-inf");
+        match lexer.next().unwrap() {
+            Ok(Token::Float((_, HashableFloat::Generic(value)))) => {
+                assert_eq!(value, f64::NEG_INFINITY)
+            }
+            other => panic!("Expected Float token for -inf, got {other:?}"),
+        }
+
+        let mut lexer = Token::lexer("-0.0");
+        match lexer.next().unwrap() {
+            Ok(Token::Float((_, HashableFloat::Generic(value)))) => {
+                assert!(value == 0.0 && value.is_sign_negative())
+            }
+            other => panic!("Expected Float token for -0.0, got {other:?}"),
+        }
+
+        // The keywords must not shadow longer identifiers
+        assert_single_token(
+            "info",
+            Token::Identifier((Location::default(), "info".into())),
+        );
+        assert_single_token(
+            "nano",
+            Token::Identifier((Location::default(), "nano".into())),
+        );
+    }
+
     #[test]
     fn test_string_tokens() {
         // Test single quoted string
@@ -781,6 +1253,28 @@ mod test {
             panic!("Expected String token");
         }
 
+        // Test line continuations in double quoted strings
+        for (input, expected) in [
+            ("\"foo\\\nbar\"", "foobar"),
+            ("\"foo \\\r\n  bar\"", "foo   bar"),
+            ("\"a\\\nb\\\nc\"", "abc"),
+            ("\"C:\\dir\"", "C:\\dir"),
+            ("'foo\\\nbar'", "foo\\\nbar"),
+        ] {
+            let mut lexer = Token::lexer(input);
+            assert_matches!(
+                lexer.next(),
+                Some(Ok(Token::String((_, value)))) if value == expected
+            );
+        }
+        let mut lexer = Token::lexer("\"foo\\\"");
+        let error = lexer.next().unwrap().unwrap_err();
+        assert_matches!(error, Error::LineContinuation { .. });
+        assert_eq!(
+            error.to_string(),
+            "[1:1] - invalid line continuation: '\\' must be followed by a line break"
+        );
+
         // Test macro string
         let mut lexer = Token::lexer("m'macro'");
         if let Token::MacroString((_, value)) = lexer.next().unwrap().unwrap() {
@@ -808,6 +1302,14 @@ mod test {
             panic!("Expected MacroIdentifier token");
         }
 
+        // Test absolute macro identifier
+        let mut lexer = Token::lexer("m!@section.key");
+        if let Token::MacroIdentifier((_, value)) = lexer.next().unwrap().unwrap() {
+            assert_eq!(value, "@section.key");
+        } else {
+            panic!("Expected MacroIdentifier token");
+        }
+
         // Test label identifier
         let mut lexer = Token::lexer("!label");
         if let Token::LabelIdentifier((_, value)) = lexer.next().unwrap().unwrap() {
@@ -1,18 +1,45 @@
 use super::lexer::{HashableFloat, Integer, Token};
 use super::read::{Read, TokenReader};
-use crate::ast::{Location, Metadata, Statement, Value, ValueType};
+use crate::ast::{Location, Metadata, Statement, StatementType, Value, ValueType};
 use crate::{Result, error};
 use indexmap::IndexMap;
 use logos::Lexer;
 use snafu::{OptionExt, ensure};
 
-/// Maximum recursion depth to prevent stack overflow attacks
-const MAX_RECURSION_DEPTH: usize = 64;
+/// Default maximum nesting depth to prevent stack overflow attacks
+pub(crate) const MAX_NESTING_DEPTH: usize = 64;
+
+/// Keywords naming a value type, `null` may also be written as `nil` or `none`
+const TYPE_KEYWORDS: &[&str] = &[
+    "string", "int", "i8", "i16", "i32", "i64", "i128", "uint", "u8", "u16", "u32", "u64", "u128",
+    "float", "f32", "f64", "bool", "bytes", "version", "require", "datetime", "decimal", "label",
+    "symbol", "null", "any", "array", "table", "enum",
+];
 
 pub struct Parser<'source> {
     tokens: TokenReader<'source>,
     /// Current recursion depth for preventing stack overflow
     recursion_depth: usize,
+    /// Maximum depth statements and values may be nested to
+    max_nesting_depth: usize,
+    /// Paths referenced by `include` directives, in the order they were declared
+    includes: Vec<(String, Location)>,
+    /// Whether `key: value` without a type keyword is read as an assignment
+    colon_assignments: bool,
+    /// Whether a later statement or table entry may replace an earlier one with the same key
+    allow_duplicate_keys: bool,
+    /// Whether the first untyped assignment used `:` rather than `=`
+    assignment_style: Option<bool>,
+    /// Non-fatal issues found while parsing, in the order they were found
+    warnings: Vec<(String, Location)>,
+    /// Comments read ahead of the next statement or section before knowing which one
+    pending_meta: Option<Metadata>,
+    /// Whether statement errors are recorded in `errors` instead of ending the parse
+    recover: bool,
+    /// Errors recorded while recovering, in the order they were found
+    errors: Vec<error::Error>,
+    /// Byte offset of the token the last recovery stopped at
+    recovered_at: Option<usize>,
 }
 
 impl<'source> Parser<'source> {
@@ -27,10 +54,26 @@ impl<'source> Parser<'source> {
                     column: 0,
                     source_text: None,
                     length: 0,
+                    byte_start: 0,
                     file_path: None,
                 },
+                strict_booleans: false,
+                file_path: None,
+                line: 0,
+                statement_start: false,
+                pending: Vec::new(),
             },
             recursion_depth: 0,
+            max_nesting_depth: MAX_NESTING_DEPTH,
+            includes: Vec::new(),
+            colon_assignments: false,
+            allow_duplicate_keys: false,
+            assignment_style: None,
+            warnings: Vec::new(),
+            pending_meta: None,
+            recover: false,
+            errors: Vec::new(),
+            recovered_at: None,
         }
     }
 
@@ -46,19 +89,70 @@ impl<'source> Parser<'source> {
                     column: 0,
                     source_text: None,
                     length: 0,
+                    byte_start: 0,
                     file_path: Some(file_path.to_string()),
                 },
+                strict_booleans: false,
+                file_path: Some(file_path.to_string()),
+                line: 0,
+                statement_start: false,
+                pending: Vec::new(),
             },
             recursion_depth: 0,
+            max_nesting_depth: MAX_NESTING_DEPTH,
+            includes: Vec::new(),
+            colon_assignments: false,
+            allow_duplicate_keys: false,
+            assignment_style: None,
+            warnings: Vec::new(),
+            pending_meta: None,
+            recover: false,
+            errors: Vec::new(),
+            recovered_at: None,
         }
     }
 
+    /// Sets the maximum depth statements and values may be nested to
+    pub fn max_nesting_depth(mut self, depth: usize) -> Self {
+        self.max_nesting_depth = depth;
+        self
+    }
+
+    /// Sets whether `key: value` is read as an assignment when no type keyword follows
+    /// the colon
+    ///
+    /// Typed assignments such as `key: int = 3` keep their meaning. Mixing `key: value`
+    /// and `key = value` in one document is allowed but reported in [`Parser::warnings`].
+    pub fn colon_assignments(mut self, enabled: bool) -> Self {
+        self.colon_assignments = enabled;
+        self
+    }
+
+    /// Sets whether a key may be repeated within one table, section or block
+    ///
+    /// Repeated keys are rejected with [`error::Error::DuplicateKey`] by default. When
+    /// allowed, the later statement or entry replaces the earlier one. Blocks with the same
+    /// id and labels are always rejected.
+    pub fn allow_duplicate_keys(mut self, allow: bool) -> Self {
+        self.allow_duplicate_keys = allow;
+        self
+    }
+
+    /// Sets whether only `true` and `false` are read as booleans
+    ///
+    /// The lenient spellings `yes`, `no`, `on`, `off` and their capitalized forms, as well
+    /// as `True` and `False`, are read as strings instead.
+    pub fn strict_booleans(mut self, strict: bool) -> Self {
+        self.tokens.strict_booleans = strict;
+        self
+    }
+
     /// Check recursion depth and increment it, returning an error if max depth is exceeded
     fn enter_recursion(&mut self) -> Result<()> {
-        if self.recursion_depth >= MAX_RECURSION_DEPTH {
-            return error::RecursionLimitSnafu {
+        if self.recursion_depth >= self.max_nesting_depth {
+            return error::NestingLimitSnafu {
                 location: self.tokens.location(),
-                limit: MAX_RECURSION_DEPTH,
+                limit: self.max_nesting_depth,
             }
             .fail();
         }
@@ -74,18 +168,180 @@ impl<'source> Parser<'source> {
     }
 
     pub fn parse(&mut self) -> Result<Statement> {
-        self.module()
+        let module = self.module()?;
+        self.end_of_input()?;
+        Ok(module)
+    }
+
+    /// Parses as much of the source as possible, returning the module along with the errors
+    /// found on the way
+    ///
+    /// A statement that fails to parse is reported and skipped up to the next line starting
+    /// with an identifier, a control statement, a section, an include, a comment or a label,
+    /// or up to the closing brace of the block it is in. The module holds every statement
+    /// that parsed, which suits editors that want a tree even for a broken document.
+    pub fn parse_recover(&mut self) -> (Statement, Vec<error::Error>) {
+        self.recover = true;
+        let module = match self.module() {
+            Ok(module) => module,
+            Err(e) => {
+                self.errors.push(e);
+                Statement::new_module(".", IndexMap::new(), Metadata::new(self.tokens.location()))
+            }
+        };
+        if let Err(e) = self.end_of_input() {
+            self.errors.push(e);
+        }
+        self.recover = false;
+        (module, std::mem::take(&mut self.errors))
+    }
+
+    /// Fails unless the whole source has been read
+    fn end_of_input(&mut self) -> Result<()> {
+        if let Some(token) = self.tokens.next()? {
+            return error::ExpectedSnafu {
+                location: self.tokens.location(),
+                expected: "end of input",
+                got: token,
+                context: "documents separated by '---' must be parsed with parse_documents"
+                    .to_string(),
+            }
+            .fail();
+        }
+        Ok(())
+    }
+
+    /// Records an error that leaves the token stream intact when recovering, otherwise
+    /// returns it
+    fn record(&mut self, error: error::Error) -> Result<()> {
+        if !self.recover {
+            return Err(error);
+        }
+        self.errors.push(error);
+        Ok(())
+    }
+
+    /// Records a statement error when recovering and skips to the start of the next
+    /// statement, otherwise returns the error
+    ///
+    /// Inside a block the closing brace also ends the skipped tokens. When the last recovery
+    /// stopped at the same token, at least one token is skipped so recovery always progresses.
+    fn recover(&mut self, error: error::Error, in_block: bool) -> Result<()> {
+        self.record(error)?;
+        self.pending_meta = None;
+
+        let mut stalled = true;
+        loop {
+            let line = self.tokens.line();
+            let (offset, boundary) = match self.tokens.lexer.peek() {
+                None => break,
+                Some(Err(_)) => (None, false),
+                Some(Ok(token)) => {
+                    let source = token.source();
+                    let boundary = (in_block && matches!(token, Token::RBrace(_)))
+                        || (source.is_some_and(|x| x.line > line)
+                            && matches!(
+                                token,
+                                Token::Identifier(_)
+                                    | Token::String(_)
+                                    | Token::ControlIdentifier(_)
+                                    | Token::LBracket(_)
+                                    | Token::KeyInclude(_)
+                                    | Token::LineComment(_)
+                                    | Token::MultiLineComment(_)
+                                    | Token::LabelIdentifier(_)
+                                    | Token::DocumentSeparator(_)
+                            ));
+                    (source.map(|x| x.byte_start), boundary)
+                }
+            };
+            stalled &= offset.is_some() && offset == self.recovered_at;
+            if boundary && !stalled {
+                self.recovered_at = offset;
+                break;
+            }
+            self.tokens.discard();
+        }
+        Ok(())
+    }
+
+    /// Parses a source holding several documents separated by `---` lines
+    ///
+    /// Every document is parsed as an independent module, a separator at the very start of
+    /// the source does not start an empty document.
+    pub fn parse_documents(&mut self) -> Result<Vec<Statement>> {
+        if let Some(Token::DocumentSeparator(_)) = self.tokens.peek()? {
+            self.tokens.next()?;
+        }
+        let mut documents = vec![self.module()?];
+        while let Some(Token::DocumentSeparator(_)) = self.tokens.peek()? {
+            self.tokens.next()?;
+            documents.push(self.module()?);
+        }
+        Ok(documents)
+    }
+
+    /// Returns the paths referenced by `include` directives in the parsed module
+    ///
+    /// Includes are not resolved by the parser itself; loaders are responsible for
+    /// reading the referenced files and merging them into the module.
+    pub fn includes(&self) -> &[(String, Location)] {
+        &self.includes
+    }
+
+    /// Returns the comments found after the last statement of the parsed source
+    pub(crate) fn trailing_metadata(&self) -> Option<&Metadata> {
+        self.pending_meta.as_ref()
+    }
+
+    /// Returns the warnings found while parsing along with their locations
+    pub fn warnings(&self) -> &[(String, Location)] {
+        &self.warnings
+    }
+
+    /// Records the style of an untyped assignment and warns when it differs from the
+    /// style of the first one
+    fn note_assignment_style(&mut self, colon: bool, id: &str, location: &Location) {
+        let first = *self.assignment_style.get_or_insert(colon);
+        if first != colon {
+            let (used, earlier) = if colon { (":", "=") } else { ("=", ":") };
+            self.warnings.push((
+                format!(
+                    "'{}' is assigned with '{}' while earlier assignments use '{}'",
+                    id, used, earlier
+                ),
+                location.clone(),
+            ));
+        }
+    }
+
+    /// Parses a single type annotation that makes up the entire input
+    pub(crate) fn type_annotation(&mut self) -> Result<ValueType> {
+        let type_ = self.value_type()?;
+        if let Some(token) = self.tokens.next()? {
+            return error::ExpectedSnafu {
+                location: self.tokens.location(),
+                expected: "end of input",
+                got: token,
+                context: "while parsing type annotation".to_string(),
+            }
+            .fail();
+        }
+        Ok(type_)
     }
 
     fn metadata(&mut self) -> Result<Metadata> {
-        let mut meta = Metadata {
+        let mut meta = self.pending_meta.take().unwrap_or(Metadata {
             location: self.tokens.location(),
             comment: None,
             label: None,
-        };
+            line_comment: false,
+        });
 
         // Process comments
         while let Some(token) = self.tokens.peek()? {
+            // The comment is only shown as line comments if every part of it was one
+            let is_line = matches!(token, Token::LineComment(_));
             match token {
                 Token::LineComment((_, comment)) | Token::MultiLineComment((_, comment)) => {
                     self.tokens.discard();
@@ -93,8 +349,10 @@ impl<'source> Parser<'source> {
                     if let Some(existing) = meta.comment.as_mut() {
                         existing.push('\n');
                         existing.push_str(&comment);
+                        meta.line_comment &= is_line;
                     } else {
                         meta.comment = Some(comment.clone());
+                        meta.line_comment = is_line;
                     }
                 }
                 Token::LabelIdentifier((_, label)) => {
@@ -117,16 +375,37 @@ impl<'source> Parser<'source> {
         Ok(meta)
     }
 
+    /// Attaches the comment and label read ahead of a collection element to its value
+    fn attach_metadata(value: &mut Value, pending: Option<Metadata>) {
+        let Some(pending) = pending else {
+            return;
+        };
+        if value.meta.comment.is_none() {
+            value.meta.comment = pending.comment;
+            value.meta.line_comment = pending.line_comment;
+        }
+        if value.meta.label.is_none() {
+            value.meta.label = pending.label;
+        }
+    }
+
     fn value_type(&mut self) -> Result<ValueType> {
         self.enter_recursion()?;
         let result = self.value_type_impl();
         self.exit_recursion();
-        result
+        let type_ = result?;
+        // A trailing '?' marks the type as optional
+        if let Some(Token::Question(_)) = self.tokens.peek()? {
+            self.tokens.discard();
+            return Ok(ValueType::Optional(Box::new(type_)));
+        }
+        Ok(type_)
     }
 
     fn value_type_impl(&mut self) -> Result<ValueType> {
-        let token = self.tokens.next()?.context(error::EofSnafu {
+        let token = self.tokens.next()?.context(error::UnexpectedEofSnafu {
             location: self.tokens.location(),
+            expected: "a type",
         })?;
         match token {
             Token::KeyString(_) => Ok(ValueType::String),
@@ -150,12 +429,52 @@ impl<'source> Parser<'source> {
             Token::KeyBytes(_) => Ok(ValueType::Bytes),
             Token::KeyVersion(_) => Ok(ValueType::Version),
             Token::KeyRequire(_) => Ok(ValueType::Require),
+            #[cfg(feature = "datetime")]
+            Token::KeyDateTime(_) => Ok(ValueType::DateTime),
+            #[cfg(not(feature = "datetime"))]
+            Token::KeyDateTime(_) => error::DateTimeSnafu {
+                location: token.location(Some(self.tokens.module_name.clone())),
+                reason: "datetime support requires the 'datetime' feature",
+            }
+            .fail(),
+            #[cfg(feature = "decimal")]
+            Token::KeyDecimal(_) => Ok(ValueType::Decimal),
+            #[cfg(not(feature = "decimal"))]
+            Token::KeyDecimal(_) => error::DecimalSnafu {
+                location: token.location(Some(self.tokens.module_name.clone())),
+                reason: "decimal support requires the 'decimal' feature",
+            }
+            .fail(),
+            // Network types are not reserved so `ip` and `cidr` remain usable as keys
+            #[cfg(feature = "net")]
+            Token::Identifier((_, id)) if id == "ip" => Ok(ValueType::Ip),
+            #[cfg(feature = "net")]
+            Token::Identifier((_, id)) if id == "cidr" => Ok(ValueType::Cidr),
+            #[cfg(not(feature = "net"))]
+            Token::Identifier((_, ref id)) if id == "ip" || id == "cidr" => error::IpSnafu {
+                location: token.location(Some(self.tokens.module_name.clone())),
+                reason: "network address support requires the 'net' feature",
+            }
+            .fail(),
+            // Not reserved either, so `regex` remains usable as a key
+            #[cfg(feature = "regex")]
+            Token::Identifier((_, id)) if id == "regex" => Ok(ValueType::Regex),
+            #[cfg(not(feature = "regex"))]
+            Token::Identifier((_, ref id)) if id == "regex" => error::RegexSnafu {
+                location: token.location(Some(self.tokens.module_name.clone())),
+                reason: "regular expression support requires the 'regex' feature",
+            }
+            .fail(),
             Token::KeyLabel(_) => Ok(ValueType::Label),
             Token::KeySymbol(_) => Ok(ValueType::Symbol),
+            Token::KeyAny(_) => Ok(ValueType::Any),
             Token::KeyArray(location) => {
                 let mut location = location.clone();
                 location.set_module(self.tokens.module_name.as_str());
-                let tok = self.tokens.next()?.context(error::EofSnafu { location })?;
+                let tok = self.tokens.next()?.context(error::UnexpectedEofSnafu {
+                    location,
+                    expected: "'['",
+                })?;
                 let tok_loc = tok.location(Some(self.tokens.module_name.clone()));
                 ensure!(
                     matches!(tok, Token::LBracket(_)),
@@ -167,7 +486,11 @@ impl<'source> Parser<'source> {
                     }
                 );
                 let mut children = Vec::new();
-                while let Some(tok) = self.tokens.peek()? {
+                loop {
+                    let tok = self.tokens.peek()?.context(error::UnexpectedEofSnafu {
+                        location: self.tokens.location(),
+                        expected: "one of ']', ',' or a member type",
+                    })?;
                     match tok {
                         Token::Comma(_) => {
                             self.tokens.discard();
@@ -187,7 +510,10 @@ impl<'source> Parser<'source> {
             Token::KeyTable(location) => {
                 let mut location = location.clone();
                 location.set_module(self.tokens.module_name.as_str());
-                let tok = self.tokens.next()?.context(error::EofSnafu { location })?;
+                let tok = self.tokens.next()?.context(error::UnexpectedEofSnafu {
+                    location,
+                    expected: "'{'",
+                })?;
                 let tok_loc = tok.location(Some(self.tokens.module_name.clone()));
                 ensure!(
                     matches!(tok, Token::LBrace(_)),
@@ -199,7 +525,11 @@ impl<'source> Parser<'source> {
                     }
                 );
                 let mut children = IndexMap::new();
-                while let Some(tok) = self.tokens.peek()? {
+                loop {
+                    let tok = self.tokens.peek()?.context(error::UnexpectedEofSnafu {
+                        location: self.tokens.location(),
+                        expected: "one of '}', ',' or a field name",
+                    })?;
                     match tok {
                         Token::Comma(_) => {
                             self.tokens.discard();
@@ -210,8 +540,9 @@ impl<'source> Parser<'source> {
                             break;
                         }
                         _ => {
-                            let id = self.tokens.next()?.context(error::EofSnafu {
+                            let id = self.tokens.next()?.context(error::UnexpectedEofSnafu {
                                 location: self.tokens.location(),
+                                expected: "a field name",
                             })?;
                             let id = match id {
                                 Token::Identifier((_, id)) | Token::String((_, id)) => {
@@ -225,8 +556,9 @@ impl<'source> Parser<'source> {
                                 }
                                 .fail(),
                             }?;
-                            let eq = self.tokens.next()?.context(error::EofSnafu {
+                            let eq = self.tokens.next()?.context(error::UnexpectedEofSnafu {
                                 location: self.tokens.location(),
+                                expected: "':'",
                             })?;
                             let eq_loc = eq.location(Some(self.tokens.module_name.clone()));
                             ensure!(
@@ -248,14 +580,67 @@ impl<'source> Parser<'source> {
                 }
                 Ok(ValueType::Table(children))
             }
+            Token::KeyEnum(location) => {
+                let mut location = location.clone();
+                location.set_module(self.tokens.module_name.as_str());
+                let tok = self.tokens.next()?.context(error::UnexpectedEofSnafu {
+                    location,
+                    expected: "'{'",
+                })?;
+                let tok_loc = tok.location(Some(self.tokens.module_name.clone()));
+                ensure!(
+                    matches!(tok, Token::LBrace(_)),
+                    error::ExpectedSnafu {
+                        location: tok_loc.clone(),
+                        expected: "{",
+                        got: tok.clone(),
+                        context: "while parsing enum type definition".to_string()
+                    }
+                );
+                let mut symbols = Vec::new();
+                // Symbols are separated by a comma or a line break like array elements
+                let mut separated = true;
+                loop {
+                    let tok = self.tokens.peek()?.context(error::UnexpectedEofSnafu {
+                        location: self.tokens.location(),
+                        expected: "one of '}', ',' or a symbol",
+                    })?;
+                    match tok {
+                        Token::Comma(_) => {
+                            self.ensure_element_before(&tok, separated, "}")?;
+                            self.tokens.discard();
+                            separated = true;
+                        }
+                        Token::RBrace(_) if !symbols.is_empty() => {
+                            self.tokens.discard();
+                            break;
+                        }
+                        Token::SymbolIdentifier((_, ref symbol)) => {
+                            self.ensure_separated(&tok, separated, "enum symbols")?;
+                            // Read rather than discarded so the next symbol is checked
+                            // against the line of this one
+                            self.tokens.next()?;
+                            separated = false;
+                            if !symbols.contains(symbol) {
+                                symbols.push(symbol.clone());
+                            }
+                        }
+                        got => {
+                            return error::ExpectedSnafu {
+                                location: got.location(Some(self.tokens.module_name.clone())),
+                                expected: "a symbol",
+                                got: got.clone(),
+                                context: "while parsing enum type definition".to_string(),
+                            }
+                            .fail();
+                        }
+                    }
+                }
+                Ok(ValueType::Enum(symbols))
+            }
             _ => error::ExpectedSnafu {
                 location: self.tokens.location(),
-                expected: vec![
-                    "string", "int", "i8", "i16", "i32", "i64", "i128", "uint", "u8", "u16", "u32",
-                    "u64", "u128", "float", "f32", "f64", "bool", "bytes", "version", "require",
-                    "label", "symbol", "null", "array", "table",
-                ]
-                .join(", "),
+                expected: TYPE_KEYWORDS.join(", "),
                 got: token.clone(),
                 context: "while parsing value type".to_string(),
             }
@@ -271,12 +656,33 @@ impl<'source> Parser<'source> {
     }
 
     fn value_impl(&mut self) -> Result<(Value, ValueType)> {
-        let meta = self.metadata()?;
+        let (token, meta) = self.value_token()?;
+
+        // Arrays and tables are parsed separately to keep recursive frames small
+        match token {
+            Token::LBracket(_) => self.array(meta),
+            Token::LBrace(location) => self.table(meta, location),
+            token => self.scalar(token, meta),
+        }
+    }
+
+    /// Reads the comments, label and first token of a value
+    #[inline(never)]
+    fn value_token(&mut self) -> Result<(Token, Metadata)> {
+        let mut meta = self.metadata()?;
 
-        let token = self.tokens.next()?.context(error::EofSnafu {
+        let token = self.tokens.next()?.context(error::UnexpectedEofSnafu {
             location: self.tokens.location(),
+            expected: "a value",
         })?;
+        // Values are located at their own token rather than the one preceding them
+        meta.location = self.tokens.location();
+        Ok((token, meta))
+    }
 
+    /// Parses a value that holds no other values from its token
+    #[inline(never)]
+    fn scalar(&mut self, token: Token, meta: Metadata) -> Result<(Value, ValueType)> {
         match token {
             // Simple value types
             Token::KeyNull(_) => Ok((Value::new_null(meta), ValueType::Null)),
@@ -352,107 +758,88 @@ impl<'source> Parser<'source> {
             Token::Require((_, value)) => {
                 Ok((Value::new_require(value.clone(), meta), ValueType::Require))
             }
-            // Array parsing
-            Token::LBracket(_) => {
-                let mut children = Vec::with_capacity(8);
-                let mut child_types = Vec::with_capacity(8);
-
-                while let Some(token) = self.tokens.peek()? {
-                    match token {
-                        Token::Comma(_) => {
-                            self.tokens.discard();
-                            continue;
-                        }
-                        Token::RBracket(_) => {
-                            self.tokens.discard();
-                            break;
-                        }
-                        _ => {
-                            let (value, type_) = self.value()?;
-                            children.push(value);
-                            child_types.push(type_);
-                        }
-                    };
-                }
 
-                Ok((
-                    Value::new_array(children, meta),
-                    ValueType::Array(child_types),
-                ))
+            // Date and time types
+            #[cfg(feature = "datetime")]
+            Token::DateTime((mut location, value)) => {
+                location.set_module(self.tokens.module_name.as_str());
+                let value = chrono::DateTime::parse_from_rfc3339(value.as_str()).map_err(|e| {
+                    error::Error::DateTime {
+                        location,
+                        reason: e.to_string(),
+                    }
+                })?;
+                Ok((Value::new_datetime(value, meta), ValueType::DateTime))
             }
-            Token::LBrace(location) => {
-                let mut children = IndexMap::new();
-                let mut child_types = IndexMap::new();
-                while let Some(token) = self.tokens.peek()? {
-                    match token {
-                        Token::Comma(_) => {
-                            self.tokens.discard();
-                            continue;
-                        }
-                        Token::RBrace(_) => {
-                            self.tokens.discard();
-                            break;
-                        }
-                        Token::Identifier(_) | Token::String(_) => {
-                            let next_token = self.tokens.next()?.context(error::EofSnafu {
-                                location: self.tokens.location(),
-                            })?;
+            #[cfg(not(feature = "datetime"))]
+            Token::DateTime(_) => error::DateTimeSnafu {
+                location: token.location(Some(self.tokens.module_name.clone())),
+                reason: "datetime support requires the 'datetime' feature",
+            }
+            .fail(),
 
-                            let id = match next_token {
-                                Token::Identifier((location, id))
-                                | Token::String((location, id)) => {
-                                    let mut loc = location.clone();
-                                    loc.set_module(self.tokens.module_name.as_str());
-                                    (loc, id.clone())
-                                }
-                                _ => unreachable!(), // We already matched this in the peek
-                            };
-
-                            let vtype = if let Some(Token::Colon(_)) = self.tokens.peek()? {
-                                self.tokens.discard();
-                                Some(self.value_type()?)
-                            } else {
-                                None
-                            };
-
-                            let eq_tok = self.tokens.next()?.context(error::EofSnafu {
-                                location: id.0.clone(),
-                            })?;
+            // Fixed-point decimals
+            #[cfg(feature = "decimal")]
+            Token::Decimal((mut location, value)) => {
+                location.set_module(self.tokens.module_name.as_str());
+                let value =
+                    value
+                        .parse::<rust_decimal::Decimal>()
+                        .map_err(|e| error::Error::Decimal {
+                            location,
+                            reason: e.to_string(),
+                        })?;
+                Ok((Value::new_decimal(value, meta), ValueType::Decimal))
+            }
+            #[cfg(not(feature = "decimal"))]
+            Token::Decimal(_) => error::DecimalSnafu {
+                location: token.location(Some(self.tokens.module_name.clone())),
+                reason: "decimal support requires the 'decimal' feature",
+            }
+            .fail(),
 
-                            let eq_loc = eq_tok.location(Some(self.tokens.module_name.clone()));
-                            ensure!(
-                                matches!(eq_tok, Token::Assign(_)),
-                                error::ExpectedSnafu {
-                                    location: eq_loc.clone(),
-                                    expected: "=",
-                                    got: eq_tok.clone(),
-                                    context: format!(
-                                        "while parsing table entry for key '{}'",
-                                        id.1
-                                    )
-                                }
-                            );
+            // Network addresses
+            #[cfg(feature = "net")]
+            Token::Ip((mut location, value)) => {
+                location.set_module(self.tokens.module_name.as_str());
+                let value = value
+                    .parse::<std::net::IpAddr>()
+                    .map_err(|e| error::Error::Ip {
+                        location,
+                        reason: e.to_string(),
+                    })?;
+                Ok((Value::new_ip(value, meta), ValueType::Ip))
+            }
+            #[cfg(feature = "net")]
+            Token::Cidr((mut location, value)) => {
+                location.set_module(self.tokens.module_name.as_str());
+                let value = value
+                    .parse::<crate::ast::Cidr>()
+                    .map_err(|reason| error::Error::Ip { location, reason })?;
+                Ok((Value::new_cidr(value, meta), ValueType::Cidr))
+            }
+            #[cfg(not(feature = "net"))]
+            Token::Ip(_) | Token::Cidr(_) => error::IpSnafu {
+                location: token.location(Some(self.tokens.module_name.clone())),
+                reason: "network address support requires the 'net' feature",
+            }
+            .fail(),
 
-                            let (child, child_type) = self.value()?;
-                            children.insert(id.1.clone(), child);
-                            child_types.insert(id.1, vtype.unwrap_or(child_type));
-                        }
-                        _ => {
-                            return error::ExpectedSnafu {
-                                location,
-                                expected: ", } identifier string",
-                                got: token.clone(),
-                                context: "while parsing table entries".to_string(),
-                            }
-                            .fail();
-                        }
-                    }
-                }
-                Ok((
-                    Value::new_table(children, meta),
-                    ValueType::Table(child_types),
-                ))
+            // Regular expressions are compiled here so bad patterns fail the parse
+            #[cfg(feature = "regex")]
+            Token::Regex((mut location, value)) => {
+                location.set_module(self.tokens.module_name.as_str());
+                let value = value
+                    .parse::<crate::ast::Pattern>()
+                    .map_err(|reason| error::Error::Regex { location, reason })?;
+                Ok((Value::new_regex(value, meta), ValueType::Regex))
+            }
+            #[cfg(not(feature = "regex"))]
+            Token::Regex(_) => error::RegexSnafu {
+                location: token.location(Some(self.tokens.module_name.clone())),
+                reason: "regular expression support requires the 'regex' feature",
             }
+            .fail(),
             // Error for unexpected tokens
             _ => error::ExpectedSnafu {
                 location: token.location(Some(self.tokens.module_name.clone())),
@@ -464,6 +851,207 @@ impl<'source> Parser<'source> {
         }
     }
 
+    /// Parses the elements of an array after its opening bracket
+    fn array(&mut self, mut meta: Metadata) -> Result<(Value, ValueType)> {
+        let mut children = Vec::with_capacity(8);
+        let mut child_types = Vec::with_capacity(8);
+        let mut pending = None;
+        // Elements are separated by a comma or a line break
+        let mut separated = true;
+
+        while let Some(token) = self.next_element(
+            &mut separated,
+            &mut pending,
+            "]",
+            "one of ']', ',' or an array element",
+        )? {
+            self.ensure_separated(&token, separated, "array elements")?;
+            separated = false;
+            let (mut value, type_) = self.value()?;
+            Self::attach_metadata(&mut value, pending.take());
+            children.push(value);
+            child_types.push(type_);
+        }
+        // The value covers everything up to and including the closing bracket
+        meta.location = meta.location.span_to(&self.tokens.location());
+
+        Ok((
+            Value::new_array(children, meta),
+            ValueType::Array(child_types),
+        ))
+    }
+
+    /// Parses the entries of a table after its opening brace
+    fn table(&mut self, mut meta: Metadata, location: Location) -> Result<(Value, ValueType)> {
+        let mut children: IndexMap<String, Value> = IndexMap::new();
+        let mut child_types = IndexMap::new();
+        let mut pending = None;
+        // Entries are separated by a comma or a line break
+        let mut separated = true;
+
+        while let Some(token) = self.next_element(
+            &mut separated,
+            &mut pending,
+            "}",
+            "one of '}', ',' or a table key",
+        )? {
+            let (key, vtype) = self.table_key(&token, separated, &location)?;
+            separated = false;
+
+            let (mut child, child_type) = self.value()?;
+            Self::attach_metadata(&mut child, pending.take());
+            if let Some(first) = children.get(&key) {
+                ensure!(
+                    self.allow_duplicate_keys,
+                    error::DuplicateKeySnafu {
+                        key,
+                        first_location: first.meta.location.clone(),
+                        second_location: child.meta.location.clone(),
+                    }
+                );
+            }
+            children.insert(key.clone(), child);
+            child_types.insert(key, vtype.unwrap_or(child_type));
+        }
+        // The value covers everything up to and including the closing brace
+        meta.location = meta.location.span_to(&self.tokens.location());
+        Ok((
+            Value::new_table(children, meta),
+            ValueType::Table(child_types),
+        ))
+    }
+
+    /// Reads the commas and comments of a collection up to its next element, returning
+    /// None once the closing `close` token has been consumed
+    ///
+    /// Kept out of the recursive collection parsers so their stack frames stay small.
+    #[inline(never)]
+    fn next_element(
+        &mut self,
+        separated: &mut bool,
+        pending: &mut Option<Metadata>,
+        close: &str,
+        expected: &str,
+    ) -> Result<Option<Token>> {
+        loop {
+            let token = self.tokens.peek()?.context(error::UnexpectedEofSnafu {
+                location: self.tokens.location(),
+                expected,
+            })?;
+            match token {
+                Token::Comma(_) => {
+                    self.ensure_element_before(&token, *separated, close)?;
+                    self.tokens.discard();
+                    *separated = true;
+                }
+                Token::RBracket(_) | Token::RBrace(_)
+                    if token.source().and_then(|x| x.source_text.as_deref()) == Some(close) =>
+                {
+                    // Consumed to keep the location past nested collections
+                    self.tokens.next()?;
+                    return Ok(None);
+                }
+                // Comments before an element belong to the element, trailing comments
+                // before the closing token are allowed
+                Token::LineComment(_) | Token::MultiLineComment(_) => {
+                    *pending = Some(self.metadata()?);
+                }
+                token => return Ok(Some(token)),
+            }
+        }
+    }
+
+    /// Reads a table key with its optional type annotation up to and including the `=`
+    #[inline(never)]
+    fn table_key(
+        &mut self,
+        token: &Token,
+        separated: bool,
+        location: &Location,
+    ) -> Result<(String, Option<ValueType>)> {
+        if !matches!(token, Token::Identifier(_) | Token::String(_)) {
+            return error::ExpectedSnafu {
+                location: location.clone(),
+                expected: ", } identifier string",
+                got: token.clone(),
+                context: "while parsing table entries".to_string(),
+            }
+            .fail();
+        }
+        self.ensure_separated(token, separated, "table entries")?;
+
+        let next_token = self.tokens.next()?.context(error::UnexpectedEofSnafu {
+            location: self.tokens.location(),
+            expected: "a table key",
+        })?;
+
+        let id = match next_token {
+            Token::Identifier((location, id)) | Token::String((location, id)) => {
+                let mut loc = location.clone();
+                loc.set_module(self.tokens.module_name.as_str());
+                (loc, id.clone())
+            }
+            _ => unreachable!(), // We already matched this in the peek
+        };
+
+        let vtype = if let Some(Token::Colon(_)) = self.tokens.peek()? {
+            self.tokens.discard();
+            Some(self.value_type()?)
+        } else {
+            None
+        };
+
+        let eq_tok = self.tokens.next()?.context(error::UnexpectedEofSnafu {
+            location: id.0.clone(),
+            expected: if vtype.is_some() {
+                "'='"
+            } else {
+                "one of '=' or ':'"
+            },
+        })?;
+
+        let eq_loc = eq_tok.location(Some(self.tokens.module_name.clone()));
+        ensure!(
+            matches!(eq_tok, Token::Assign(_)),
+            error::ExpectedSnafu {
+                location: eq_loc.clone(),
+                expected: "=",
+                got: eq_tok.clone(),
+                context: format!("while parsing table entry for key '{}'", id.1)
+            }
+        );
+        Ok((id.1, vtype))
+    }
+
+    /// Ensures a collection element starts after a comma or on a new line
+    fn ensure_separated(&mut self, token: &Token, separated: bool, context: &str) -> Result<()> {
+        let location = token.location(Some(self.tokens.module_name.clone()));
+        ensure!(
+            separated || location.line > self.tokens.location().line,
+            error::ExpectedSnafu {
+                location,
+                expected: ", or a new line",
+                got: token.clone(),
+                context: format!("while parsing {context}"),
+            }
+        );
+        Ok(())
+    }
+
+    /// Ensures a comma in a collection follows an element rather than another comma
+    fn ensure_element_before(&mut self, token: &Token, separated: bool, close: &str) -> Result<()> {
+        ensure!(
+            !separated,
+            error::ExpectedSnafu {
+                location: token.location(Some(self.tokens.module_name.clone())),
+                expected: format!("value or {close}"),
+                got: token.clone(),
+                context: "while parsing collection elements".to_string(),
+            }
+        );
+        Ok(())
+    }
+
     fn statement(&mut self) -> Result<Statement> {
         self.enter_recursion()?;
         let result = self.statement_impl();
@@ -472,185 +1060,296 @@ impl<'source> Parser<'source> {
     }
 
     fn statement_impl(&mut self) -> Result<Statement> {
-        let meta = self.metadata()?;
+        self.tokens.statement_start = true;
+        let mut meta = self.metadata()?;
 
-        let token = self.tokens.next()?.context(error::EofSnafu {
+        let token = self.tokens.next()?.context(error::UnexpectedEofSnafu {
             location: self.tokens.location(),
+            expected: "a statement",
         })?;
+        // Statements are located at their identifier rather than the token preceding them
+        meta.location = self.tokens.location();
 
         match &token {
-            Token::ControlIdentifier((location, id)) => {
-                // Handle control statements ($identifier)
-                let mut location = location.clone();
-                location.set_module(self.tokens.module_name.as_str());
+            Token::ControlIdentifier((location, id)) => self.control(location, id, meta),
 
-                // Check for type annotation
-                let type_ = if let Some(Token::Colon(_)) = self.tokens.peek()? {
-                    self.tokens.discard();
-                    Some(self.value_type()?)
-                } else {
-                    None
-                };
+            Token::Identifier((location, id)) | Token::String((location, id)) => {
+                let mut loc = location.clone();
+                loc.set_module(self.tokens.module_name.as_str());
 
-                // Expect assignment operator
-                let eq = self.tokens.next()?.context(error::EofSnafu {
+                // Check if this is an assignment or a block
+                match self.tokens.peek()? {
+                    Some(token @ (Token::Colon(_) | Token::Assign(_) | Token::AppendAssign(_))) => {
+                        self.assignment(id, loc, token, meta)
+                    }
+                    Some(_) => self.block(id, meta),
+                    None => error::UnexpectedEofSnafu {
+                        location: self.tokens.location(),
+                        expected: "one of '=', ':' or a block",
+                    }
+                    .fail(),
+                }
+            }
+
+            value => error::ExpectedSnafu {
+                expected: "statement",
+                got: value.clone(),
+                location: value.location(Some(self.tokens.module_name.clone())),
+                context: "Expected a statement (assignment, control statement, or block)"
+                    .to_string(),
+            }
+            .fail(),
+        }
+    }
+
+    /// Parses a control statement (`$identifier`) after its identifier
+    #[inline(never)]
+    fn control(&mut self, location: &Location, id: &str, meta: Metadata) -> Result<Statement> {
+        // Handle control statements ($identifier)
+        let mut location = location.clone();
+        location.set_module(self.tokens.module_name.as_str());
+
+        // Check for type annotation
+        let type_ = if let Some(Token::Colon(_)) = self.tokens.peek()? {
+            self.tokens.discard();
+            Some(self.value_type()?)
+        } else {
+            None
+        };
+
+        // Expect assignment operator
+        let eq = self.tokens.next()?.context(error::UnexpectedEofSnafu {
+            location: location.clone(),
+            expected: if type_.is_some() {
+                "'='"
+            } else {
+                "one of '=' or ':'"
+            },
+        })?;
+
+        let eq_loc = eq.location(Some(self.tokens.module_name.clone()));
+        ensure!(
+            matches!(eq, Token::Assign(_)),
+            error::ExpectedSnafu {
+                location: eq_loc.clone(),
+                expected: "=",
+                got: eq.clone(),
+                context: format!("while parsing control statement '${}'", id)
+            }
+        );
+
+        // Parse value and check type compatibility
+        let (value, vtype) = self.value()?;
+        if let Some(type_) = type_.as_ref() {
+            ensure!(
+                type_.can_assign(&vtype),
+                error::AssignSnafu {
                     location: location.clone(),
+                    left: type_.clone(),
+                    right: vtype
+                }
+            );
+        }
+
+        Statement::new_control(id, type_, value, meta)
+    }
+
+    /// Parses an assignment after its key, `token` is the assignment operator
+    #[inline(never)]
+    fn assignment(
+        &mut self,
+        id: &str,
+        loc: Location,
+        token: Token,
+        meta: Metadata,
+    ) -> Result<Statement> {
+        let colon = matches!(token, Token::Colon(_));
+        let mut append = matches!(token, Token::AppendAssign(_));
+        self.tokens.discard();
+
+        // A colon without a type keyword assigns when colon assignments
+        // are enabled, otherwise it starts a type annotation
+        let annotated = colon
+            && (!self.colon_assignments
+                || self.tokens.peek()?.is_some_and(|x| {
+                    x.is_type_keyword()
+                        || matches!(x, Token::Identifier((_, id)) if id == "ip" || id == "cidr" || id == "regex")
+                }));
+        let mut parsed = None;
+        let type_ = if annotated {
+            let type_ = self.value_type()?;
+
+            // `key: null` is a null value when colons assign
+            if self.colon_assignments
+                && type_ == ValueType::Null
+                && !matches!(
+                    self.tokens.peek()?,
+                    Some(Token::Assign(_) | Token::AppendAssign(_))
+                )
+            {
+                let meta = Metadata::new(self.tokens.location());
+                parsed = Some((Value::new_null(meta), ValueType::Null));
+                None
+            } else {
+                // Now expect assignment operator
+                let eq = self.tokens.next()?.context(error::UnexpectedEofSnafu {
+                    location: loc.clone(),
+                    expected: "one of '=' or '+='",
                 })?;
 
                 let eq_loc = eq.location(Some(self.tokens.module_name.clone()));
+                append = matches!(eq, Token::AppendAssign(_));
                 ensure!(
-                    matches!(eq, Token::Assign(_)),
+                    matches!(eq, Token::Assign(_) | Token::AppendAssign(_)),
                     error::ExpectedSnafu {
-                        location: eq_loc.clone(),
-                        expected: "=",
-                        got: eq.clone(),
-                        context: format!("while parsing control statement '${}'", id)
-                    }
-                );
-
-                // Parse value and check type compatibility
-                let (value, vtype) = self.value()?;
-                if let Some(type_) = type_.as_ref() {
-                    ensure!(
-                        vtype.can_assign(type_),
-                        error::AssignSnafu {
-                            location: location.clone(),
-                            left: type_.clone(),
-                            right: vtype
-                        }
-                    );
-                }
+                        location: eq_loc.clone(),
+                        expected: "one of '=' or '+='",
+                        got: eq.clone(),
+                        context: format!("while parsing assignment to '{}'", id)
+                    }
+                );
 
-                Ok(Statement::new_control(id.as_str(), type_, value, meta)?)
+                Some(type_)
             }
+        } else {
+            None
+        };
+        if self.colon_assignments && type_.is_none() {
+            self.note_assignment_style(colon, id, &loc);
+        }
 
-            Token::Identifier((location, id)) | Token::String((location, id)) => {
-                let mut loc = location.clone();
-                loc.set_module(self.tokens.module_name.as_str());
+        // Parse value and check type compatibility
+        let (value, vtype) = match parsed {
+            Some(parsed) => parsed,
+            None => self.value()?,
+        };
+        if let Some(type_) = type_.as_ref() {
+            ensure!(
+                type_.can_assign(&vtype),
+                error::AssignSnafu {
+                    location: loc.clone(),
+                    left: type_.clone(),
+                    right: vtype
+                }
+            );
+        }
 
-                // Check if this is an assignment or a block
-                if let Some(token) = self.tokens.peek()? {
-                    match token {
-                        Token::Colon(_) | Token::Assign(_) => {
-                            // This is an assignment
-
-                            // Check for type annotation
-                            let type_ = if matches!(token, Token::Colon(_)) {
-                                self.tokens.discard();
-                                let type_ = self.value_type()?;
-
-                                // Now expect assignment operator
-                                let eq = self.tokens.next()?.context(error::EofSnafu {
-                                    location: loc.clone(),
-                                })?;
-
-                                let eq_loc = eq.location(Some(self.tokens.module_name.clone()));
-                                ensure!(
-                                    matches!(eq, Token::Assign(_)),
-                                    error::ExpectedSnafu {
-                                        location: eq_loc.clone(),
-                                        expected: "=",
-                                        got: eq.clone(),
-                                        context: format!("while parsing assignment to '{}'", id)
-                                    }
-                                );
-
-                                Some(type_)
-                            } else {
-                                // No type annotation, just consume the assignment operator
-                                self.tokens.discard();
-                                None
-                            };
-
-                            // Parse value and check type compatibility
-                            let (value, vtype) = self.value()?;
-                            if let Some(type_) = type_.as_ref() {
-                                ensure!(
-                                    vtype.can_assign(type_),
-                                    error::AssignSnafu {
-                                        location: loc.clone(),
-                                        left: type_.clone(),
-                                        right: vtype
-                                    }
-                                );
-                            }
+        if append {
+            // Only arrays can be appended onto an existing value
+            ensure!(
+                matches!(vtype, ValueType::Array(_)),
+                error::AssignSnafu {
+                    location: loc.clone(),
+                    left: ValueType::Array(vec![ValueType::Any]),
+                    right: vtype
+                }
+            );
+            return Statement::new_append(id, type_, value, meta);
+        }
 
-                            Ok(Statement::new_assign(id.as_str(), type_, value, meta)?)
-                        }
+        Statement::new_assign(id, type_, value, meta)
+    }
 
-                        _ => {
-                            // This is a block
-                            let mut labels = Vec::with_capacity(4);
-
-                            // Parse labels until we hit the opening brace
-                            loop {
-                                match self.tokens.peek()? {
-                                    Some(Token::LBrace(_)) => {
-                                        self.tokens.discard();
-                                        break;
-                                    }
-                                    Some(Token::Comma(_)) => {
-                                        self.tokens.discard();
-                                    }
-                                    Some(_) => {
-                                        labels.push(self.value()?.0);
-                                    }
-                                    None => {
-                                        return error::ExpectedSnafu {
-                                            location: self.tokens.location(),
-                                            expected: "{",
-                                            got: token.clone(),
-                                            context: format!(
-                                                "while parsing block statement '{}'",
-                                                id
-                                            ),
-                                        }
-                                        .fail();
-                                    }
-                                }
-                            }
+    /// Parses a block with its labels and children after its id
+    #[inline(never)]
+    fn block(&mut self, id: &str, mut meta: Metadata) -> Result<Statement> {
+        let labels = self.block_labels()?;
+
+        // Parse block contents
+        let mut children = IndexMap::with_capacity(8);
+        while !self.block_end(&mut meta)? {
+            match self.statement() {
+                Ok(value) => {
+                    if let Err(e) =
+                        Self::insert_child(&mut children, value, self.allow_duplicate_keys)
+                    {
+                        self.record(e)?;
+                    }
+                }
+                Err(e) => self.recover(e, true)?,
+            }
+        }
 
-                            // Parse block contents
-                            let mut children = IndexMap::with_capacity(8);
-                            while let Some(stmt) = self.tokens.peek()? {
-                                match stmt {
-                                    Token::RBrace(_) => {
-                                        self.tokens.discard();
-                                        break;
-                                    }
-                                    _ => {
-                                        let value = self.statement()?;
-                                        children.insert(value.inject_id(), value);
-                                    }
-                                }
-                            }
+        Ok(Statement::new_block(id, labels, children, meta))
+    }
 
-                            Ok(Statement::new_block(id.as_str(), labels, children, meta))
-                        }
-                    }
-                } else {
-                    error::ExpectedSnafu {
+    /// Parses block labels up to and including the opening brace
+    #[inline(never)]
+    fn block_labels(&mut self) -> Result<Vec<Value>> {
+        let mut labels = Vec::with_capacity(4);
+        loop {
+            match self.tokens.peek()? {
+                Some(Token::LBrace(_)) => {
+                    self.tokens.discard();
+                    return Ok(labels);
+                }
+                Some(Token::Comma(_)) => {
+                    self.tokens.discard();
+                }
+                Some(_) => {
+                    labels.push(self.value()?.0);
+                }
+                None => {
+                    return error::UnexpectedEofSnafu {
                         location: self.tokens.location(),
-                        expected: "= or : or block contents",
-                        got: token.clone(),
-                        context: format!(
-                            "while parsing identifier '{}' - expected assignment or block",
-                            id
-                        ),
+                        expected: "one of '{' or a block label",
                     }
-                    .fail()
+                    .fail();
                 }
             }
+        }
+    }
 
-            value => error::ExpectedSnafu {
-                expected: "statement",
-                got: value.clone(),
-                location: value.location(Some(self.tokens.module_name.clone())),
-                context: "Expected a statement (assignment, control statement, or block)"
-                    .to_string(),
+    /// Consumes the closing brace of a block if it is next, extending the block location
+    /// up to it
+    #[inline(never)]
+    fn block_end(&mut self, meta: &mut Metadata) -> Result<bool> {
+        let token = self.tokens.peek()?.context(error::UnexpectedEofSnafu {
+            location: self.tokens.location(),
+            expected: "one of '}' or a statement",
+        })?;
+        let Token::RBrace(close) = token else {
+            return Ok(false);
+        };
+        self.tokens.discard();
+        // The block covers everything up to its closing brace
+        meta.location = meta.location.span_to(&close);
+        Ok(true)
+    }
+
+    /// Adds a child statement under its injection id, rejecting blocks that would replace
+    /// an earlier block with the same id and labels
+    ///
+    /// An append statement whose id is already assigned concatenates onto that value. Any
+    /// other statement repeating a key is rejected unless `allow_duplicates` is set.
+    fn insert_child(
+        children: &mut IndexMap<String, Statement>,
+        child: Statement,
+        allow_duplicates: bool,
+    ) -> Result<()> {
+        let key = child.inject_id();
+        if let (StatementType::Append(_), Some(existing)) = (&child.type_, children.get_mut(&key)) {
+            return existing.append(&child);
+        }
+        ensure!(
+            child.get_labeled().is_none() || !children.contains_key(&key),
+            error::DuplicateBlockSnafu {
+                id: key,
+                location: child.meta.location.clone(),
             }
-            .fail(),
+        );
+        if let Some(first) = children.get(&key) {
+            ensure!(
+                allow_duplicates,
+                error::DuplicateKeySnafu {
+                    key,
+                    first_location: first.meta.location.clone(),
+                    second_location: child.meta.location.clone(),
+                }
+            );
         }
+        children.insert(key, child);
+        Ok(())
     }
 
     fn module(&mut self) -> Result<Statement> {
@@ -664,18 +1363,84 @@ impl<'source> Parser<'source> {
         let parent_meta = self.metadata()?;
         let mut children = IndexMap::with_capacity(16); // Pre-allocate with reasonable capacity
 
-        while let Some(token) = self.tokens.peek()? {
-            let meta = self.metadata()?;
-            match token {
+        loop {
+            match self.next_statement_impl() {
+                Ok(Some(child)) => {
+                    if let Err(e) =
+                        Self::insert_child(&mut children, child, self.allow_duplicate_keys)
+                    {
+                        self.record(e)?;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => self.recover(e, false)?,
+            }
+        }
+
+        Ok(Statement::new_module(".", children, parent_meta))
+    }
+
+    /// Parses the next top level statement or section, returning None at the end of the input
+    /// or of the current document when the source holds several documents
+    ///
+    /// This allows processing a source one statement at a time instead of building the whole
+    /// module with `parse`. Sections are returned with all of their statements and include
+    /// directives are skipped after being recorded in `includes`.
+    pub fn next_statement(&mut self) -> Result<Option<Statement>> {
+        self.enter_recursion()?;
+        let result = self.next_statement_impl();
+        self.exit_recursion();
+        result
+    }
+
+    fn next_statement_impl(&mut self) -> Result<Option<Statement>> {
+        loop {
+            self.tokens.statement_start = true;
+            if self.tokens.peek()?.is_none() {
+                return Ok(None);
+            }
+            let mut meta = self.metadata()?;
+            let Some(token) = self.tokens.peek()? else {
+                // Comments after the last statement are kept for formatting
+                self.pending_meta = meta.has_comment().then_some(meta);
+                return Ok(None);
+            };
+            return match token {
+                Token::KeyInclude(_) => {
+                    // Consume through the reader so the location carries the file path
+                    self.tokens.next()?;
+                    let location = self.tokens.location();
+
+                    let path = self.tokens.next()?.context(error::UnexpectedEofSnafu {
+                        location: location.clone(),
+                        expected: "an include path",
+                    })?;
+
+                    let path = match path {
+                        Token::String((_, path)) => Ok(path),
+                        value => error::ExpectedSnafu {
+                            location: value.location(Some(self.tokens.module_name.clone())),
+                            expected: "string",
+                            got: value.clone(),
+                            context: "while parsing include path".to_string(),
+                        }
+                        .fail(),
+                    }?;
+
+                    self.includes.push((path, location));
+                    continue;
+                }
                 Token::LBracket(location) => {
                     let mut location = location.clone();
                     location.set_module(self.tokens.module_name.as_str());
                     self.tokens.discard();
 
                     // Get section identifier
-                    let id = self.tokens.next()?.context(error::EofSnafu {
+                    let id = self.tokens.next()?.context(error::UnexpectedEofSnafu {
                         location: location.clone(),
+                        expected: "a section name",
                     })?;
+                    meta.location = self.tokens.location();
 
                     let id = match id {
                         Token::Identifier((_, id)) | Token::String((_, id)) => Ok(id),
@@ -689,8 +1454,9 @@ impl<'source> Parser<'source> {
                     }?;
 
                     // Ensure closing bracket
-                    let close = self.tokens.next()?.context(error::EofSnafu {
+                    let close = self.tokens.next()?.context(error::UnexpectedEofSnafu {
                         location: self.tokens.location(),
+                        expected: "']'",
                     })?;
 
                     let close_loc = close.location(Some(self.tokens.module_name.clone()));
@@ -708,25 +1474,40 @@ impl<'source> Parser<'source> {
                     let mut statements = IndexMap::with_capacity(8);
                     while let Some(stmt) = self.tokens.peek()? {
                         match stmt {
-                            Token::LBracket(_) => break,
-                            _ => {
-                                let value = self.statement()?;
-                                statements.insert(value.inject_id(), value);
+                            Token::LBracket(_)
+                            | Token::KeyInclude(_)
+                            | Token::DocumentSeparator(_) => break,
+                            // Comments may belong to the statement or section that follows
+                            Token::LineComment(_) | Token::MultiLineComment(_) => {
+                                let meta = self.metadata()?;
+                                self.pending_meta = Some(meta);
                             }
+                            _ => match self.statement() {
+                                Ok(value) => {
+                                    if let Err(e) = Self::insert_child(
+                                        &mut statements,
+                                        value,
+                                        self.allow_duplicate_keys,
+                                    ) {
+                                        self.record(e)?;
+                                    }
+                                }
+                                Err(e) => self.recover(e, false)?,
+                            },
                         }
                     }
 
-                    let child = Statement::new_section(id.as_str(), statements, meta);
-                    children.insert(child.inject_id(), child);
+                    Ok(Some(Statement::new_section(id.as_str(), statements, meta)))
                 }
+                // The separator ends the current document
+                Token::DocumentSeparator(_) => Ok(None),
                 _ => {
-                    let value = self.statement()?;
-                    children.insert(value.inject_id(), value);
+                    // The comment and label were already read, hand them to the statement
+                    self.pending_meta = Some(meta);
+                    self.statement().map(Some)
                 }
-            }
+            };
         }
-
-        Ok(Statement::new_module(".", children, parent_meta))
     }
 }
 
@@ -734,7 +1515,7 @@ impl<'source> Parser<'source> {
 mod test {
     use super::Parser;
     use crate::ast::Metadata;
-    use crate::ast::{Location, Statement, Value, ValueType};
+    use crate::ast::{Location, Statement, StatementType, Value, ValueType};
     use crate::syn::lexer::Token;
     use indexmap::IndexMap;
     use logos::Logos;
@@ -928,6 +1709,8 @@ mod test {
             ("version", ValueType::Version),
             ("require", ValueType::Require),
             ("bytes", ValueType::Bytes),
+            ("any", ValueType::Any),
+            ("array[any]", ValueType::Array(vec![ValueType::Any])),
             (
                 "array[string, int, bool]",
                 ValueType::Array(vec![ValueType::String, ValueType::Signed, ValueType::Bool]),
@@ -946,6 +1729,164 @@ mod test {
         }
     }
 
+    /// Sorts every token into type keywords, other keywords and non keywords, a new
+    /// token fails to compile here until it is sorted
+    fn is_type_keyword(token: &Token) -> Option<bool> {
+        match token {
+            Token::KeyNull(_)
+            | Token::KeyBool(_)
+            | Token::KeyString(_)
+            | Token::KeyInt(_)
+            | Token::KeyUInt(_)
+            | Token::KeyInt8(_)
+            | Token::KeyUInt8(_)
+            | Token::KeyInt16(_)
+            | Token::KeyUInt16(_)
+            | Token::KeyInt32(_)
+            | Token::KeyUInt32(_)
+            | Token::KeyInt64(_)
+            | Token::KeyUInt64(_)
+            | Token::KeyInt128(_)
+            | Token::KeyUInt128(_)
+            | Token::KeyFloat(_)
+            | Token::KeyFloat64(_)
+            | Token::KeyFloat32(_)
+            | Token::KeyBytes(_)
+            | Token::KeyVersion(_)
+            | Token::KeyRequire(_)
+            | Token::KeyDateTime(_)
+            | Token::KeyDecimal(_)
+            | Token::KeyLabel(_)
+            | Token::KeySymbol(_)
+            | Token::KeyAny(_)
+            | Token::KeyArray(_)
+            | Token::KeyTable(_)
+            | Token::KeyEnum(_) => Some(true),
+            Token::KeySection(_)
+            | Token::KeyBlock(_)
+            | Token::KeyInclude(_)
+            | Token::KeyModule(_)
+            | Token::KeyUse(_)
+            | Token::KeyAs(_)
+            | Token::KeySchema(_) => Some(false),
+            Token::Newline
+            | Token::Error(_)
+            | Token::True(_)
+            | Token::False(_)
+            | Token::Exclaim(_)
+            | Token::Dollar(_)
+            | Token::LBracket(_)
+            | Token::RBracket(_)
+            | Token::LBrace(_)
+            | Token::RBrace(_)
+            | Token::LParen(_)
+            | Token::RParen(_)
+            | Token::Assign(_)
+            | Token::AppendAssign(_)
+            | Token::Colon(_)
+            | Token::Question(_)
+            | Token::Comma(_)
+            | Token::DocumentSeparator(_)
+            | Token::Int(_)
+            | Token::Float(_)
+            | Token::MacroString(_)
+            | Token::ByteString(_)
+            | Token::String(_)
+            | Token::Identifier(_)
+            | Token::MacroIdentifier(_)
+            | Token::LabelIdentifier(_)
+            | Token::SymbolIdentifier(_)
+            | Token::ControlIdentifier(_)
+            | Token::Version(_)
+            | Token::Require(_)
+            | Token::DateTime(_)
+            | Token::Decimal(_)
+            | Token::Ip(_)
+            | Token::Cidr(_)
+            | Token::Regex(_)
+            | Token::LineComment(_)
+            | Token::MultiLineComment(_) => None,
+        }
+    }
+
+    #[test]
+    fn type_keywords() {
+        let keywords = super::TYPE_KEYWORDS.iter().chain(&[
+            "nil", "none", "section", "block", "include", "module", "use", "as", "schema",
+        ]);
+        for keyword in keywords {
+            let mut lexer = Token::lexer(keyword);
+            let token = lexer.next().unwrap().unwrap();
+            assert!(lexer.next().is_none(), "{keyword}");
+            let is_type = is_type_keyword(&token).unwrap_or_else(|| panic!("{keyword}"));
+            assert_eq!(is_type, token.is_type_keyword(), "{keyword}");
+            assert_eq!(
+                is_type,
+                super::TYPE_KEYWORDS.contains(keyword) || matches!(token, Token::KeyNull(_)),
+                "{keyword}"
+            );
+            if !is_type {
+                continue;
+            }
+
+            // Collection types need their members
+            let case = match *keyword {
+                "array" => "array[any]".to_string(),
+                "table" => "table{}".to_string(),
+                "enum" => "enum{:a}".to_string(),
+                other => other.to_string(),
+            };
+            let result = parser!(case.as_str()).value_type();
+            match *keyword {
+                #[cfg(not(feature = "datetime"))]
+                "datetime" => assert!(result.is_err()),
+                #[cfg(not(feature = "decimal"))]
+                "decimal" => assert!(result.is_err()),
+                _ => {
+                    let type_ = result.unwrap_or_else(|e| panic!("{keyword}: {e}"));
+                    if !matches!(token, Token::KeyNull(_)) {
+                        assert_eq!(type_.to_annotation(), case);
+                    }
+                }
+            }
+        }
+
+        // Keywords that are not types are rejected with the list of types
+        let error = parser!("section").value_type().unwrap_err().to_string();
+        assert!(
+            error.contains("decimal, label, symbol, null, any"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn eof_expectations() {
+        for (case, expected) in [
+            ("tags = [1, 2", "one of ']', ',' or an array element"),
+            ("port =", "a value"),
+            ("owner = { team = 'ops'", "one of '}', ',' or a table key"),
+            ("owner = { team", "one of '=' or ':'"),
+            ("listener 'http' { port = 80", "one of '}' or a statement"),
+            ("listener 'http'", "one of '{' or a block label"),
+            ("listener", "one of '=', ':' or a block"),
+            ("port: int", "one of '=' or '+='"),
+            ("port: array[int", "one of ']', ',' or a member type"),
+            ("[server", "']'"),
+        ] {
+            let error = parser!(case).parse().unwrap_err();
+            assert!(
+                matches!(&error, crate::error::Error::UnexpectedEof { expected: found, .. } if found == expected),
+                "{case}: {error}"
+            );
+            assert!(
+                error
+                    .to_string()
+                    .ends_with(&format!("unexpected end of file, expected {expected}")),
+                "{error}"
+            );
+        }
+    }
+
     #[test]
     fn statements() {
         for (case, expected) in [
@@ -983,10 +1924,12 @@ mod test {
                                 column: 0,
                                 file_path: None,
                                 length: 0,
+                                byte_start: 0,
                                 source_text: None,
                             },
                             comment: None,
                             label: Some("Hint".to_string()),
+                            line_comment: false,
                         },
                     ),
                     Metadata {
@@ -996,10 +1939,12 @@ mod test {
                             column: 0,
                             file_path: None,
                             length: 0,
+                            byte_start: 0,
                             source_text: None,
                         },
                         comment: Some("Comment".to_string()),
                         label: None,
+                        line_comment: true,
                     },
                 )
                 .unwrap(),
@@ -1038,10 +1983,12 @@ mod test {
                                 column: 0,
                                 file_path: None,
                                 length: 0,
+                                byte_start: 0,
                                 source_text: None,
                             },
                             comment: None,
                             label: Some("Hint".to_string()),
+                            line_comment: false,
                         },
                     ),
                     Metadata {
@@ -1051,10 +1998,12 @@ mod test {
                             column: 0,
                             file_path: None,
                             length: 0,
+                            byte_start: 0,
                             source_text: None,
                         },
                         comment: Some("Comment".to_string()),
                         label: None,
+                        line_comment: true,
                     },
                 )
                 .unwrap(),
@@ -1084,10 +2033,12 @@ mod test {
                             column: 0,
                             file_path: None,
                             length: 0,
+                            byte_start: 0,
                             source_text: None,
                         },
                         comment: Some("Comment".to_string()),
                         label: None,
+                        line_comment: true,
                     },
                 ),
             ),
@@ -1098,6 +2049,784 @@ mod test {
         }
     }
 
+    #[test]
+    fn any_type() {
+        let mut parser = parser!("value: any = [1, 'two', true]");
+        let statement = parser.statement().unwrap();
+        assert_eq!(statement.type_, StatementType::Assignment(ValueType::Any));
+        assert_eq!(statement.get_value().unwrap().as_array().unwrap().len(), 3);
+
+        let mut parser = parser!("value: array[any] = [1, 'two', true]");
+        let statement = parser.statement().unwrap();
+        assert_eq!(
+            statement.type_,
+            StatementType::Assignment(ValueType::Array(vec![ValueType::Any]))
+        );
+
+        let mut parser = parser!("value: array[int] = [1, 'two']");
+        assert!(parser.statement().is_err());
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn datetime_values() {
+        let mut parser = parser!("stamp: datetime = 2024-01-02T03:04:05Z");
+        let statement = parser.statement().unwrap();
+        assert_eq!(
+            statement.type_,
+            StatementType::Assignment(ValueType::DateTime)
+        );
+        let value = statement.get_value().unwrap();
+        assert_eq!(value.as_datetime().unwrap().offset().local_minus_utc(), 0);
+        assert_eq!(value.to_string(), "2024-01-02T03:04:05Z");
+
+        let mut parser = parser!("stamp = 2024-01-02T03:04:05.250+05:30");
+        let statement = parser.statement().unwrap();
+        let value = statement.get_value().unwrap();
+        assert_eq!(
+            value.as_datetime().unwrap().offset().local_minus_utc(),
+            5 * 3600 + 30 * 60
+        );
+        assert_eq!(value.to_string(), "2024-01-02T03:04:05.250+05:30");
+
+        let mut parser = parser!("stamp = 2024-13-40T00:00:00Z");
+        assert_matches::assert_matches!(
+            parser.statement(),
+            Err(crate::error::Error::DateTime { .. })
+        );
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimal_values() {
+        let mut parser = parser!("price: decimal = 0.1d");
+        let statement = parser.statement().unwrap();
+        assert_eq!(
+            statement.type_,
+            StatementType::Assignment(ValueType::Decimal)
+        );
+        let value = statement.get_value().unwrap();
+        assert_eq!(
+            value.as_decimal(),
+            Some(&"0.1".parse::<rust_decimal::Decimal>().unwrap())
+        );
+        assert!(value.as_float().is_none());
+        assert_eq!(value.to_string(), "0.1d");
+
+        // Trailing zeros are kept and the written value reads back the same
+        let mut parser = parser!("price = 1.50d");
+        let value = parser.statement().unwrap().get_value().unwrap().clone();
+        assert_eq!(value.to_string(), "1.50d");
+        let written = format!("price = {value}");
+        let mut parser = parser!(written.as_str());
+        assert_eq!(parser.statement().unwrap().get_value().unwrap(), &value);
+
+        let mut parser = parser!("price: decimal = 0.1");
+        assert!(parser.statement().is_err());
+
+        let mut parser = parser!("price = 79228162514264337593543950336d");
+        assert_matches::assert_matches!(
+            parser.statement(),
+            Err(crate::error::Error::Decimal { .. })
+        );
+    }
+
+    #[test]
+    fn file_paths() {
+        let path = "config/app.bml";
+        let code = "[server]\nlistener 'http' {\n  limits = { ports = [80, 443] }\n}\n";
+        let module = Parser::with_file_path("app", path, Token::lexer(code))
+            .parse()
+            .unwrap();
+        let listener = module
+            .find_by_path("server")
+            .and_then(|x| x.find_block("listener", &["http"]))
+            .unwrap();
+        let limits = listener.find_child("limits").unwrap();
+        let ports = &limits.get_value().unwrap().as_table().unwrap()["ports"];
+        for location in [
+            &module.find_by_path("server").unwrap().meta.location,
+            &listener.meta.location,
+            &listener.get_labeled().unwrap().0[0].meta.location,
+            &limits.meta.location,
+            &ports.meta.location,
+            &ports.as_array().unwrap()[1].meta.location,
+        ] {
+            assert_eq!(location.file_path.as_deref(), Some(path), "{location}");
+        }
+
+        let code = "[server]\nlistener 'http' {\n  limits = { ports = [80, = ] }\n}\n";
+        let error = Parser::with_file_path("app", path, Token::lexer(code))
+            .parse()
+            .unwrap_err();
+        let crate::error::Error::Expected { location, .. } = &error else {
+            panic!("expected a syntax error, found {error:?}");
+        };
+        assert_eq!(location.line, 2);
+        assert!(
+            location.to_string().starts_with("[app@config/app.bml:"),
+            "{location}"
+        );
+        assert!(error.to_string().contains(path));
+    }
+
+    #[test]
+    fn file_paths_lexer_errors() {
+        let path = "cfg/app.bml";
+        let error = Parser::with_file_path("app", path, Token::lexer("z = x'zz'"))
+            .parse()
+            .unwrap_err();
+        let crate::error::Error::Hex { location, .. } = &error else {
+            panic!("expected a hex error, found {error:?}");
+        };
+        assert_eq!(location.module.as_deref(), Some("app"));
+        assert_eq!(location.file_path.as_deref(), Some(path));
+        assert!(
+            error.to_string().starts_with("[app@cfg/app.bml:"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn colon_assignments() {
+        let code = "key: 3\ntyped: int = 3\nname: 'web'\nempty: null\nnothing: null = null\nlist: [1, 2]\n";
+        let mut parser = Parser::new("test", Token::lexer(code)).colon_assignments(true);
+        let module = parser.parse().unwrap();
+        let statement = |path: &str| module.find_by_path(path).unwrap();
+
+        assert_eq!(
+            statement("key").type_,
+            StatementType::Assignment(ValueType::Signed)
+        );
+        assert_eq!(statement("key").get_value().unwrap().as_int(), Some(&3));
+        assert_eq!(
+            statement("typed").type_,
+            StatementType::Assignment(ValueType::Signed)
+        );
+        assert_eq!(statement("typed").get_value().unwrap().as_int(), Some(&3));
+        assert_eq!(
+            statement("name").get_value().unwrap().as_string(),
+            Some(&"web".to_string())
+        );
+        assert!(statement("empty").get_value().unwrap().is_null());
+        assert!(statement("nothing").get_value().unwrap().is_null());
+        assert_eq!(
+            statement("list")
+                .get_value()
+                .unwrap()
+                .as_array()
+                .map(Vec::len),
+            Some(2)
+        );
+        assert!(parser.warnings().is_empty());
+
+        // Typed assignments still require a value of the declared type
+        let mut parser = parser!("typed: string = 3").colon_assignments(true);
+        assert_matches::assert_matches!(
+            parser.statement(),
+            Err(crate::error::Error::Assign { .. })
+        );
+
+        // Without the mode a colon must be followed by a type
+        let mut parser = parser!("key: 3");
+        assert_matches::assert_matches!(
+            parser.statement(),
+            Err(crate::error::Error::Expected { .. })
+        );
+
+        // Mixing styles is allowed but reported
+        let mut parser = Parser::new("test", Token::lexer("first: 1\nsecond = 2\nthird: 3\n"))
+            .colon_assignments(true);
+        parser.parse().unwrap();
+        let warnings = parser.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].0,
+            "'second' is assigned with '=' while earlier assignments use ':'"
+        );
+        assert_eq!(warnings[0].1.line, 1);
+    }
+
+    #[test]
+    fn strict_booleans() {
+        let code =
+            "on = 'first'\nvalue = on\nflags = [yes, No, true, false, True]\nswitch off { }\n";
+        let module = Parser::new("test", Token::lexer(code))
+            .strict_booleans(true)
+            .parse()
+            .unwrap();
+        let value = |path: &str| module.find_by_path(path).and_then(|x| x.get_value());
+        assert_eq!(
+            value("on").and_then(|x| x.as_string()).map(String::as_str),
+            Some("first")
+        );
+        assert_eq!(
+            value("value")
+                .and_then(|x| x.as_string())
+                .map(String::as_str),
+            Some("on")
+        );
+        let flags = value("flags")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(flags, vec!["'yes'", "'No'", "true", "false", "'True'"]);
+        assert!(module.find_block("switch", &["off"]).is_some());
+
+        // The lenient spellings remain booleans by default
+        let mut parser = parser!("value = on");
+        let statement = parser.statement().unwrap();
+        assert_eq!(statement.get_value().unwrap().as_bool(), Some(&true));
+    }
+
+    #[test]
+    fn empty_collection_annotations() {
+        let mut parser = parser!("x: array[string] = []");
+        let statement = parser.statement().unwrap();
+        assert_eq!(
+            statement.type_,
+            StatementType::Assignment(ValueType::Array(vec![ValueType::String]))
+        );
+        assert_eq!(statement.get_value().unwrap().as_array(), Some(&vec![]));
+
+        let fields = IndexMap::from([("a".to_string(), ValueType::Signed)]);
+        let mut parser = parser!("y: table{a: int} = {}");
+        let statement = parser.statement().unwrap();
+        assert_eq!(
+            statement.type_,
+            StatementType::Assignment(ValueType::Table(fields.clone()))
+        );
+        assert!(
+            statement
+                .get_value()
+                .unwrap()
+                .as_table()
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(statement.to_schema(), statement.type_);
+        statement.validate().unwrap();
+
+        let mut parser = parser!("$z: table{a: int}? = {}");
+        let statement = parser.statement().unwrap();
+        assert_eq!(
+            statement.type_,
+            StatementType::Control(ValueType::Optional(Box::new(ValueType::Table(fields))))
+        );
+
+        // Non-empty literals are still checked against the annotation
+        let mut parser = parser!("x: array[string] = [1]");
+        assert_matches::assert_matches!(
+            parser.statement(),
+            Err(crate::error::Error::Assign { .. })
+        );
+        let mut parser = parser!("y: table{a: int} = {b = 1}");
+        assert_matches::assert_matches!(
+            parser.statement(),
+            Err(crate::error::Error::Assign { .. })
+        );
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn network_values() {
+        for (case, expected) in [
+            ("ip = 10.0.0.1", ValueType::Ip),
+            ("ip: ip = fe80::1", ValueType::Ip),
+            ("cidr = 10.0.0.0/8", ValueType::Cidr),
+            ("cidr: cidr = 2001:db8::/32", ValueType::Cidr),
+        ] {
+            let statement = parser!(case).statement().unwrap();
+            assert_eq!(statement.type_, StatementType::Assignment(expected));
+
+            // Addresses are written as they are read
+            let written = statement.to_string();
+            let reparsed = parser!(written.as_str()).statement().unwrap();
+            assert_eq!(reparsed.get_value(), statement.get_value(), "{written}");
+        }
+        let statement = parser!("gateway = 10.0.0.1").statement().unwrap();
+        assert_eq!(
+            statement.get_value().unwrap().as_ip(),
+            Some(&std::net::IpAddr::from([10, 0, 0, 1]))
+        );
+        let statement = parser!("subnet = 10.1.0.0/16").statement().unwrap();
+        let cidr = statement.get_value().unwrap().as_cidr().unwrap();
+        assert_eq!(cidr.address, std::net::IpAddr::from([10, 1, 0, 0]));
+        assert_eq!(cidr.prefix, 16);
+
+        // Versions are not mistaken for addresses
+        let statement = parser!("release = 1.2.3").statement().unwrap();
+        assert_eq!(
+            statement.type_,
+            StatementType::Assignment(ValueType::Version)
+        );
+
+        for case in ["ip = 256.0.0.1", "cidr = 10.0.0.0/33", "cidr = ::/129"] {
+            assert_matches::assert_matches!(
+                parser!(case).statement(),
+                Err(crate::error::Error::Ip { .. }),
+                "{case}"
+            );
+        }
+        assert!(parser!("ip: ip = 1.2.3").statement().is_err());
+    }
+
+    #[test]
+    fn prefixed_block_labels() {
+        // A block named like a literal prefix keeps a label written without a space
+        let module = parser!("x = 'a'\nr'x' { }\nr\"y\" { }\nx'ab' { }\nx'zz' { }")
+            .parse()
+            .unwrap();
+        for (id, label) in [("r", "x"), ("r", "y"), ("x", "ab"), ("x", "zz")] {
+            let block = module.find_block(id, &[label]).unwrap();
+            assert_eq!(block.id, id);
+        }
+
+        // The literal is still read in value positions
+        assert_matches::assert_matches!(
+            Token::lexer("pattern = r'x'").nth(2),
+            Some(Ok(Token::Regex(_)))
+        );
+        assert_matches::assert_matches!(
+            parser!("data = x'ab'").statement().unwrap().get_value(),
+            Some(value) if value.as_bytes().is_some()
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_values() {
+        for (case, expected) in [
+            (r"pattern = r'^\d+$'", ValueType::Regex),
+            (r#"pattern: regex = r"it's [a-z]+""#, ValueType::Regex),
+            (
+                "patterns = [r'a', r'b+']",
+                ValueType::Array(vec![ValueType::Regex; 2]),
+            ),
+        ] {
+            let statement = parser!(case).statement().unwrap();
+            assert_eq!(statement.type_, StatementType::Assignment(expected));
+
+            // Patterns are written as they are read
+            let written = statement.to_string();
+            let reparsed = parser!(written.as_str()).statement().unwrap();
+            assert_eq!(reparsed.get_value(), statement.get_value(), "{written}");
+        }
+        let statement = parser!(r"pattern = r'^\d+$'").statement().unwrap();
+        let pattern = statement.get_value().unwrap().as_regex().unwrap();
+        assert_eq!(pattern.as_str(), r"^\d+$");
+        assert_eq!(pattern.regex().as_str(), r"^\d+$");
+
+        // Bad patterns are reported at the literal
+        assert_matches::assert_matches!(
+            parser!("name = 'app'\npattern = r'[a-z'").parse(),
+            Err(crate::error::Error::Regex { location, .. }) if location.line == 1 && location.column == 10
+        );
+        assert_matches::assert_matches!(
+            parser!("pattern: regex = 'plain'").statement(),
+            Err(crate::error::Error::Assign { .. })
+        );
+    }
+
+    #[test]
+    fn enum_values() {
+        let mode = ValueType::Enum(vec!["fast".to_string(), "safe".to_string()]);
+
+        let statement = parser!("mode: enum{:fast, :safe} = :fast")
+            .statement()
+            .unwrap();
+        assert_eq!(statement.type_, StatementType::Assignment(mode.clone()));
+        assert_eq!(
+            statement.get_value().unwrap().as_symbol(),
+            Some(&"fast".to_string())
+        );
+        assert_eq!(statement.to_string(), "mode: enum{:fast, :safe} = :fast");
+        assert_eq!("enum{:fast, :safe}".parse::<ValueType>().unwrap(), mode);
+
+        // Symbols outside the set are reported with the allowed list
+        let error = parser!("mode: enum{:fast, :safe} = :turbo")
+            .statement()
+            .unwrap_err();
+        assert_matches::assert_matches!(
+            &error,
+            crate::error::Error::EnumSymbol { symbol, allowed, .. }
+                if symbol == "turbo" && allowed == &["fast", "safe"]
+        );
+        assert!(error.to_string().ends_with("allowed symbols: :fast, :safe"));
+
+        assert!(parser!("mode: enum{:fast} = 'fast'").statement().is_err());
+        assert!(parser!("mode: enum{} = :fast").statement().is_err());
+        assert!(parser!("mode: enum{fast} = :fast").statement().is_err());
+
+        // Symbols are separated like array elements
+        for case in [
+            "mode: enum{:fast, :safe,} = :fast",
+            "mode: enum{\n  :fast\n  :safe\n} = :fast",
+        ] {
+            assert_eq!(
+                parser!(case).statement().unwrap().type_,
+                StatementType::Assignment(mode.clone()),
+                "{case}"
+            );
+        }
+        for case in [
+            "mode: enum{:fast :safe} = :fast",
+            "mode: enum{,,:fast,} = :fast",
+            "mode: enum{:fast,, :safe} = :fast",
+            "mode: enum{, :fast} = :fast",
+        ] {
+            assert_matches::assert_matches!(
+                parser!(case).statement(),
+                Err(crate::error::Error::Expected { .. }),
+                "{case}"
+            );
+        }
+
+        let statement = parser!("mode: enum{:fast}? = null").statement().unwrap();
+        assert!(statement.get_value().unwrap().is_null());
+
+        // Symbols nested in arrays are checked against their element type
+        let statement = parser!("modes: array[enum{:a, :b}, enum{:b}] = [:a, :b]")
+            .statement()
+            .unwrap();
+        assert_eq!(statement.get_value().unwrap().as_array().unwrap().len(), 2);
+        assert_matches::assert_matches!(
+            parser!("modes: array[enum{:a, :b}, enum{:b}] = [:a, :a]").statement(),
+            Err(crate::error::Error::EnumSymbol { .. })
+        );
+    }
+
+    #[test]
+    fn optional_types() {
+        let optional = ValueType::Optional(Box::new(ValueType::String));
+
+        let mut parser = parser!("host: string? = null");
+        let statement = parser.statement().unwrap();
+        assert_eq!(statement.type_, StatementType::Assignment(optional.clone()));
+        assert!(statement.get_value().unwrap().is_null());
+
+        let mut parser = parser!("host: string? = 'localhost'");
+        let statement = parser.statement().unwrap();
+        assert_eq!(statement.type_, StatementType::Assignment(optional.clone()));
+        assert_eq!(
+            statement.get_value().unwrap().as_string(),
+            Some(&"localhost".to_string())
+        );
+
+        let mut parser = parser!("ports: array[u16?, u16?] = [80u16, null]");
+        let statement = parser.statement().unwrap();
+        let port = ValueType::Optional(Box::new(ValueType::U16));
+        assert_eq!(
+            statement.type_,
+            StatementType::Assignment(ValueType::Array(vec![port.clone(), port]))
+        );
+
+        let mut parser = parser!("host: string? = 5");
+        assert_matches::assert_matches!(
+            parser.statement(),
+            Err(crate::error::Error::Assign { .. })
+        );
+    }
+
+    #[test]
+    fn annotation_roundtrip() {
+        let types = [
+            ValueType::Array(vec![ValueType::String, ValueType::Signed]),
+            ValueType::Table(IndexMap::from([
+                ("one".to_string(), ValueType::String),
+                (
+                    "nested".to_string(),
+                    ValueType::Array(vec![
+                        ValueType::Table(IndexMap::from([(
+                            "version".to_string(),
+                            ValueType::Version,
+                        )])),
+                        ValueType::Array(vec![ValueType::Any]),
+                    ]),
+                ),
+                (
+                    "maybe key".to_string(),
+                    ValueType::Optional(Box::new(ValueType::Array(vec![ValueType::U8]))),
+                ),
+                ("empty".to_string(), ValueType::Table(IndexMap::new())),
+            ])),
+        ];
+        for type_ in types {
+            let annotation = type_.to_annotation();
+            let mut parser = parser!(annotation.as_str());
+            assert_eq!(parser.value_type().unwrap(), type_, "{annotation}");
+        }
+    }
+
+    #[test]
+    fn streaming_statements() {
+        let source = "name = 'app'\n\
+                      include 'shared.bml'\n\
+                      server 'web' {\n  port = 80\n}\n\
+                      [database]\nhost = 'localhost'\nport = 5432\n\
+                      [cache]\nsize = 64\n";
+
+        let mut parser = parser!(source);
+        let mut children = IndexMap::new();
+        let mut ids = Vec::new();
+        while let Some(statement) = parser.next_statement().unwrap() {
+            ids.push(statement.id.clone());
+            children.insert(statement.inject_id(), statement);
+        }
+        assert_eq!(ids, vec!["name", "server", "database", "cache"]);
+        assert_eq!(parser.includes().len(), 1);
+        assert!(parser.next_statement().unwrap().is_none());
+
+        let streamed = Statement::new_module(".", children, Metadata::default());
+        assert_eq!(streamed, parser!(source).parse().unwrap());
+    }
+
+    #[test]
+    fn byte_offsets() {
+        let source =
+            "name = 'app'\n# Server\nserver 'web' {\n  port = 8080\n}\n[cache]\nsize = 64\n";
+        let module = parser!(source).parse().unwrap();
+
+        let server = module.find_block("server", &["web"]).unwrap();
+        let (labels, children) = server.get_labeled().unwrap();
+        assert_eq!(&source[labels[0].meta.location.span()], "'web'");
+        let location = &children["port"].get_value().unwrap().meta.location;
+        assert_eq!(&source[location.span()], "8080");
+        assert_eq!(location.byte_start, source.find("8080").unwrap());
+        assert_eq!(location.byte_end(), location.byte_start + 4);
+
+        let size = module.find_by_path("cache.size").unwrap();
+        let location = &size.get_value().unwrap().meta.location;
+        assert_eq!(&source[location.span()], "64");
+    }
+
+    #[test]
+    fn parse_recover() {
+        let (module, errors) =
+            parser!("name = 'app'\nport = = 80\nhost = 'localhost'\n").parse_recover();
+        assert_eq!(
+            module.children().map(|x| x.id.as_str()).collect::<Vec<_>>(),
+            vec!["name", "host"]
+        );
+        assert_eq!(errors.len(), 1);
+        assert_matches::assert_matches!(
+            &errors[0],
+            crate::error::Error::Expected { location, .. } if location.line == 1
+        );
+
+        // Errors inside sections and blocks keep the statements around them
+        let source = "[app]\n\
+                      name = 'a'\n\
+                      server {\n  port = ]\n  tls = true\n}\n\
+                      workers = 4\n\
+                      bad = @\n\
+                      [cache]\n\
+                      size = 64\n";
+        let (module, errors) = parser!(source).parse_recover();
+        for path in ["app.name", "app.server.tls", "app.workers", "cache.size"] {
+            assert!(module.find_by_path(path).is_some(), "missing {path}");
+        }
+        assert!(module.find_by_path("app.server.port").is_none());
+        assert_eq!(errors.len(), 2, "{errors:?}");
+
+        // Without errors the result matches parse
+        let source = "a = 1\n[b]\nc = 'd'\n";
+        let (module, errors) = parser!(source).parse_recover();
+        assert!(errors.is_empty());
+        assert_eq!(module, parser!(source).parse().unwrap());
+        assert!(
+            parser!("port = = 80\nhost = 'localhost'\n")
+                .parse()
+                .is_err()
+        );
+
+        // Errors raised before any token is read still make progress
+        let (module, errors) = parser!("a = 1\nb = 2\n")
+            .max_nesting_depth(1)
+            .parse_recover();
+        assert_eq!(module.child_count(), 0);
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn append_assignments() {
+        let module = parser!("hosts = ['a']\nhosts += ['b', 'c']\nports: array[int] += [80]\n")
+            .parse()
+            .unwrap();
+
+        let hosts = module.find_child("hosts").unwrap();
+        assert_eq!(
+            hosts.type_,
+            StatementType::Assignment(ValueType::Array(vec![ValueType::String; 3]))
+        );
+        assert_eq!(
+            hosts.to_string(),
+            "hosts: array[string, string, string] = ['a', 'b', 'c']"
+        );
+
+        // Without an earlier value the append intent is kept for merging
+        let ports = module.find_child("ports").unwrap();
+        assert_eq!(
+            ports.type_,
+            StatementType::Append(ValueType::Array(vec![ValueType::Signed]))
+        );
+        assert_eq!(ports.to_string(), "ports: array[int] += [80]");
+
+        assert_matches::assert_matches!(
+            parser!("name += 'value'\n").parse(),
+            Err(crate::error::Error::Assign { .. })
+        );
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        for (source, key, first, second) in [
+            ("limits = { a = 1, a = 2 }\n", "a", (0, 15), (0, 22)),
+            (
+                "limits = {\n  a = 1\n  b = { a = 1 }\n  a = 2\n}\n",
+                "a",
+                (1, 6),
+                (3, 6),
+            ),
+            (
+                "[app]\nport = 80\nname = 'app'\nport = 8080\n",
+                "port",
+                (1, 0),
+                (3, 0),
+            ),
+            ("port = 80\nport: u16 = 8080u16\n", "port", (0, 0), (1, 0)),
+            (
+                "[app]\nname = 'a'\n[app]\nname = 'b'\n",
+                "app",
+                (0, 1),
+                (2, 1),
+            ),
+            (
+                "server { port = 80\n port = 8080 }\n",
+                "port",
+                (0, 9),
+                (1, 1),
+            ),
+        ] {
+            let Err(error) = parser!(source).parse() else {
+                panic!("duplicate keys should not parse: {source}");
+            };
+            assert_matches::assert_matches!(
+                &error,
+                crate::error::Error::DuplicateKey { key: found, first_location, second_location }
+                    if found == key
+                        && (first_location.line, first_location.column) == first
+                        && (second_location.line, second_location.column) == second
+            );
+            parser!(source).allow_duplicate_keys(true).parse().unwrap();
+        }
+
+        // Appends and distinct keys in nested scopes are not duplicates
+        parser!("hosts = ['a']\nhosts += ['b']\n[app]\nhosts = ['c']\n")
+            .parse()
+            .unwrap();
+
+        // Blocks with the same id and labels stay an error when duplicates are allowed
+        assert_matches::assert_matches!(
+            parser!("server { }\nserver { }\n")
+                .allow_duplicate_keys(true)
+                .parse(),
+            Err(crate::error::Error::DuplicateBlock { .. })
+        );
+    }
+
+    #[test]
+    fn duplicate_blocks() {
+        for (source, line) in [
+            (
+                "server \"web\" { port = 80 }\nserver \"web\" { port = 8080 }\n",
+                1,
+            ),
+            (
+                "[app]\nserver \"web\" { port = 80 }\nserver 'web' { port = 8080 }\n",
+                2,
+            ),
+            (
+                "outer { server { port = 80 }\n server { port = 8080 } }\n",
+                1,
+            ),
+        ] {
+            let Err(error) = parser!(source).parse() else {
+                panic!("duplicate blocks should not parse: {source}");
+            };
+            assert_matches::assert_matches!(
+                &error,
+                crate::error::Error::DuplicateBlock { location, .. } if location.line == line
+            );
+        }
+
+        // Blocks with the same id but different labels are kept side by side
+        let module = parser!("server 'web' { port = 80 }\nserver 'api' { port = 8080 }\n")
+            .parse()
+            .unwrap();
+        assert!(module.find_block("server", &["web"]).is_some());
+        assert!(module.find_block("server", &["api"]).is_some());
+    }
+
+    #[test]
+    fn collection_spans() {
+        let source = "ports = [1, [2, 3], 4]\n\
+                      limits = {\n  cpu = 2,\n  memory = { max = '1G' }\n}\n\
+                      listener 'http' {\n  hosts = []\n}\n";
+        let module = parser!(source).parse().unwrap();
+        let text = |location: &Location| &source[location.span()];
+        let value = |path: &str| module.find_by_path(path).unwrap().get_value().unwrap();
+
+        let ports = value("ports");
+        assert_eq!(text(&ports.meta.location), "[1, [2, 3], 4]");
+        assert_eq!(ports.meta.location.length, 14);
+        let nested = &ports.as_array().unwrap()[1];
+        assert_eq!(text(&nested.meta.location), "[2, 3]");
+
+        let limits = value("limits");
+        assert_eq!(
+            text(&limits.meta.location),
+            "{\n  cpu = 2,\n  memory = { max = '1G' }\n}"
+        );
+        let memory = &limits.as_table().unwrap()["memory"];
+        assert_eq!(text(&memory.meta.location), "{ max = '1G' }");
+
+        let listener = module.find_block("listener", &["http"]).unwrap();
+        assert_eq!(
+            text(&listener.meta.location),
+            "listener 'http' {\n  hosts = []\n}"
+        );
+        assert_eq!(
+            text(
+                &listener
+                    .find_child("hosts")
+                    .unwrap()
+                    .get_value()
+                    .unwrap()
+                    .meta
+                    .location
+            ),
+            "[]"
+        );
+    }
+
+    #[test]
+    fn includes() {
+        let mut parser = parser!("include 'shared.bml'\n[app]\nfoo = 1\ninclude \"other.bml\"\n");
+        let module = parser.parse().unwrap();
+        assert!(module.find_by_path("app.foo").is_some());
+
+        let includes: Vec<&str> = parser
+            .includes()
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect();
+        assert_eq!(includes, vec!["shared.bml", "other.bml"]);
+    }
+
     #[test]
     fn recursion_guard_working() {
         // Test with a reasonable nesting that should work
@@ -1114,17 +2843,17 @@ mod test {
         let mut parser = parser!("1");
 
         // Manually set recursion depth to near the limit
-        parser.recursion_depth = super::MAX_RECURSION_DEPTH - 1;
+        parser.recursion_depth = super::MAX_NESTING_DEPTH - 1;
 
         // This should still work
         assert!(parser.enter_recursion().is_ok());
-        assert_eq!(parser.recursion_depth, super::MAX_RECURSION_DEPTH);
+        assert_eq!(parser.recursion_depth, super::MAX_NESTING_DEPTH);
 
         // This should fail
         let result = parser.enter_recursion();
         assert!(result.is_err());
         let error_msg = format!("{}", result.unwrap_err());
-        assert!(error_msg.contains("recursion limit exceeded"));
+        assert!(error_msg.contains("nesting limit exceeded"));
     }
 
     #[test]
@@ -1139,4 +2868,96 @@ mod test {
             deeply_nested.push('}');
         }
     }
+
+    #[test]
+    fn collection_separators() {
+        for case in [
+            "[1, 2, 3]",
+            "[1, 2,]",
+            "[\n  1\n  2\n]",
+            "[[1, 2]\n [3]]",
+            "{ one = 1, two = 2, }",
+            "{\n  one = 1\n  two = [1,\n 2]\n}",
+            "[]",
+            "{}",
+        ] {
+            assert!(parser!(case).value().is_ok(), "{case} should parse");
+        }
+
+        for case in [
+            "[1 2]",
+            "[[1] [2]]",
+            "[1,, 2]",
+            "[, 1]",
+            "{ one = 1 two = 2 }",
+            "{ one = 1,, two = 2 }",
+        ] {
+            assert!(
+                matches!(
+                    parser!(case).value(),
+                    Err(crate::error::Error::Expected { .. })
+                ),
+                "{case} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn nesting_limit() {
+        // The module, the statement and every value level count towards the depth
+        let nested = |depth: usize| format!("value = {}1{}", "[".repeat(depth), "]".repeat(depth));
+
+        let (under, over) = (nested(7), nested(8));
+        let mut parser = Parser::new("test", Token::lexer(&under)).max_nesting_depth(10);
+        assert!(parser.parse().is_ok());
+
+        let mut parser = Parser::new("test", Token::lexer(&over)).max_nesting_depth(10);
+        assert!(matches!(
+            parser.parse(),
+            Err(crate::error::Error::NestingLimit { limit: 10, .. })
+        ));
+
+        // The default limit stops arrays, tables and blocks before they exhaust the stack
+        // of a test thread, even in unoptimized builds
+        let deep_table = format!("value = {}1{}", "{ a = ".repeat(200), " }".repeat(200));
+        let deep_block = format!("{}a = 1{}", "b {\n".repeat(200), "\n}".repeat(200));
+        for deep in [nested(200), deep_table, deep_block] {
+            assert!(matches!(
+                parser!(deep.as_str()).parse(),
+                Err(crate::error::Error::NestingLimit { limit: 64, .. })
+            ));
+        }
+        let deep = nested(60);
+        assert!(parser!(deep.as_str()).parse().is_ok());
+    }
+
+    #[test]
+    fn element_comments() {
+        let comments = |value: &Value| -> Vec<Option<String>> {
+            match &value.data {
+                crate::ast::Data::Array(values) => {
+                    values.iter().map(|x| x.meta.comment.clone()).collect()
+                }
+                crate::ast::Data::Table(values) => {
+                    values.values().map(|x| x.meta.comment.clone()).collect()
+                }
+                _ => panic!("expected a collection"),
+            }
+        };
+        let expected = vec![Some("first".to_string()), Some("second".to_string())];
+
+        for case in [
+            "[ # first\n 1, # second\n 2 # trailing\n ]",
+            "{ # first\n a = 1, /* second */\n b = 2, # trailing\n }",
+        ] {
+            let (value, _) = parser!(case).value().unwrap();
+            assert_eq!(comments(&value), expected, "{case}");
+
+            // Comments survive being displayed and parsed again
+            let displayed = value.to_string();
+            let (reparsed, _) = parser!(displayed.as_str()).value().unwrap();
+            assert_eq!(comments(&reparsed), expected, "{displayed}");
+            assert_eq!(reparsed, value);
+        }
+    }
 }
@@ -5,44 +5,156 @@ use logos::Lexer;
 use crate::ast::Location;
 
 use super::lexer::Token;
-use crate::Result;
+use crate::{Error, Result};
 
 pub trait Read<'source> {
     fn peek(&mut self) -> Result<Option<Token>>;
     fn next(&mut self) -> Result<Option<Token>>;
     fn discard(&mut self);
     fn location(&mut self) -> Location;
+    fn line(&self) -> usize;
 }
 
 pub struct TokenReader<'source> {
     pub module_name: String,
     pub lexer: Peekable<Lexer<'source, Token>>,
     pub location: Location,
+    /// Whether only `true` and `false` are read as booleans
+    pub strict_booleans: bool,
+    /// Path of the file being read, recorded in the location of every token
+    pub file_path: Option<String>,
+    /// Line of the last token read or discarded
+    pub line: usize,
+    /// Whether the next token starts a statement, where a literal such as `x'ab'` is read
+    /// as a block id directly followed by its label
+    pub statement_start: bool,
+    /// Tokens split off a literal at the start of a statement, the last one is read first
+    pub pending: Vec<Token>,
+}
+
+impl TokenReader<'_> {
+    /// Applies the reader settings to a token read from the lexer
+    fn prepare(strict_booleans: bool, file_path: Option<&str>, token: Token) -> Token {
+        let mut token = Self::reinterpret(strict_booleans, token);
+        if let (Some(file_path), Some(source)) = (file_path, token.source_mut()) {
+            source.set_file_path(file_path);
+        }
+        token
+    }
+
+    /// Attributes an error from the lexer to the module and file being read
+    fn locate(module_name: &str, file_path: Option<&str>, mut error: Error) -> Error {
+        if let Some(location) = error.location_mut() {
+            location.set_module(module_name);
+            if let Some(file_path) = file_path {
+                location.set_file_path(file_path);
+            }
+        }
+        error
+    }
+
+    /// Splits a prefixed literal at the start of a statement into the block id and label
+    /// it reads as, so `x'ab' { }` stays a block named `x` with the label `ab`
+    fn split_statement_start(&mut self) {
+        if !self.statement_start {
+            return;
+        }
+        if !self.pending.is_empty() {
+            self.statement_start = false;
+            return;
+        }
+        let location = match self.lexer.peek() {
+            Some(Ok(
+                Token::LineComment(_) | Token::MultiLineComment(_) | Token::LabelIdentifier(_),
+            )) => return,
+            Some(Ok(Token::ByteString((location, _)) | Token::Regex((location, _))))
+            | Some(Err(Error::Hex { location, .. })) => location.clone(),
+            _ => {
+                self.statement_start = false;
+                return;
+            }
+        };
+        self.statement_start = false;
+
+        let Some(text) = location.source_text.as_deref() else {
+            return;
+        };
+        if !(text.starts_with("x'") || text.starts_with("r'") || text.starts_with("r\"")) {
+            return;
+        }
+        let mut id_location = location.clone();
+        id_location.set_source_text(&text[..1]);
+        let mut label_location = location.clone();
+        label_location.set_source_text(&text[1..]);
+        label_location.column += 1;
+        label_location.byte_start += 1;
+
+        self.lexer.next();
+        self.pending = vec![
+            Token::String((label_location, text[2..text.len() - 1].to_string())),
+            Token::Identifier((id_location, text[..1].to_string())),
+        ];
+    }
+
+    /// Turns the lenient boolean spellings such as `on` or `No` into strings when
+    /// strict booleans are enabled
+    fn reinterpret(strict_booleans: bool, token: Token) -> Token {
+        match token {
+            Token::True(location) | Token::False(location)
+                if strict_booleans
+                    && location
+                        .source_text
+                        .as_deref()
+                        .is_some_and(|x| x != "true" && x != "false") =>
+            {
+                let text = location.source_text.clone().unwrap_or_default();
+                Token::String((location, text))
+            }
+            token => token,
+        }
+    }
 }
 
 impl<'source> Read<'source> for TokenReader<'source> {
     fn peek(&mut self) -> Result<Option<Token>> {
-        if let Some(Ok(token)) = self.lexer.peek() {
-            Ok(Some(token.clone()))
+        self.split_statement_start();
+        let strict_booleans = self.strict_booleans;
+        let file_path = self.file_path.as_deref();
+        if let Some(token) = self.pending.last() {
+            Ok(Some(Self::prepare(
+                strict_booleans,
+                file_path,
+                token.clone(),
+            )))
+        } else if let Some(Ok(token)) = self.lexer.peek() {
+            Ok(Some(Self::prepare(
+                strict_booleans,
+                file_path,
+                token.clone(),
+            )))
         } else if let Some(Err(e)) = self.lexer.peek() {
-            Err(e.clone())
+            Err(Self::locate(&self.module_name, file_path, e.clone()))
         } else {
             Ok(None)
         }
     }
 
     fn next(&mut self) -> Result<Option<Token>> {
-        if let Some(token) = self.lexer.next() {
-            let token = token?;
+        self.split_statement_start();
+        if let Some(token) = self.pending.pop().map(Ok).or_else(|| self.lexer.next()) {
+            let token =
+                token.map_err(|e| Self::locate(&self.module_name, self.file_path.as_deref(), e))?;
+            let token = Self::prepare(self.strict_booleans, self.file_path.as_deref(), token);
 
             // Update location with more detailed information
             let mut loc = token.location(Some(self.module_name.clone()));
 
-            // Preserve file path if it exists in the current location
-            if let Some(file_path) = &self.location.file_path {
+            // Tokens without a location of their own still belong to the file
+            if let Some(file_path) = &self.file_path {
                 loc.file_path = Some(file_path.clone());
             }
 
+            self.line = loc.line;
             self.location = loc;
             Ok(Some(token.clone()))
         } else {
@@ -51,10 +163,19 @@ impl<'source> Read<'source> for TokenReader<'source> {
     }
 
     fn discard(&mut self) {
-        self.lexer.next();
+        self.split_statement_start();
+        if let Some(Ok(token)) = self.pending.pop().map(Ok).or_else(|| self.lexer.next())
+            && let Some(source) = token.source()
+        {
+            self.line = source.line;
+        }
     }
 
     fn location(&mut self) -> Location {
         self.location.clone()
     }
+
+    fn line(&self) -> usize {
+        self.line
+    }
 }
@@ -0,0 +1,80 @@
+//! Assertions for testing code built on BarkML
+//!
+//! These helpers are available to other crates behind the `testing` feature. They panic
+//! with the offending source and error so they read naturally inside `#[test]` functions.
+
+// Local modules
+use crate::{Statement, from_str};
+
+/// Parses a source with [`from_str`] and returns the resolved module, panicking when it
+/// does not parse.
+///
+/// # Panics
+///
+/// Panics with the parse error and the source if the source fails to parse or its macros
+/// fail to resolve.
+#[track_caller]
+pub fn assert_parses(input: &str) -> Statement {
+    match from_str(input) {
+        Ok(module) => module,
+        Err(error) => panic!("failed to parse BarkML: {error}\n--- source ---\n{input}"),
+    }
+}
+
+/// Asserts that a source parses and that the displayed module parses back to the same tree.
+///
+/// The source is parsed with [`assert_parses`], written out with its `Display`
+/// implementation and parsed again. Both trees must hold the same statements and values,
+/// while uids, locations and comments are not compared. Returns the module parsed from
+/// the original source.
+///
+/// # Panics
+///
+/// Panics if either source fails to parse or the two trees differ, showing both sources.
+#[track_caller]
+pub fn assert_roundtrip(input: &str) -> Statement {
+    let module = assert_parses(input);
+    let displayed = module.to_string();
+    let reparsed = match from_str(&displayed) {
+        Ok(reparsed) => reparsed,
+        Err(error) => panic!(
+            "failed to parse displayed BarkML: {error}\n--- source ---\n{input}\n--- displayed ---\n{displayed}"
+        ),
+    };
+    assert!(
+        module == reparsed,
+        "displayed BarkML does not parse to the same tree\n--- source ---\n{input}\n--- displayed ---\n{displayed}"
+    );
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn examples_roundtrip() {
+        for input in [
+            include_str!("../examples/simple.bml"),
+            include_str!("../examples/example.bml"),
+            include_str!("../examples/append.d/00-first.bml"),
+        ] {
+            assert_roundtrip(input);
+        }
+    }
+
+    #[test]
+    fn assert_parses_returns_module() {
+        let module = assert_parses("name = 'app'\n[server]\nport = 80u16\n");
+        assert!(module.find_by_path("server.port").is_some());
+
+        let module = assert_roundtrip("listener 'http' {\n  hosts = ['a', { port = 80 }]\n}\n");
+        assert!(module.find_block("listener", &["http"]).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to parse BarkML")]
+    fn assert_parses_panics_on_errors() {
+        assert_parses("port = = 80\n");
+    }
+}